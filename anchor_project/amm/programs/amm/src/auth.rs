@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+use crate::states::Multisig;
+
+/// Checks that `authority` approved the instruction. A wallet has to sign it, a `Multisig` needs `threshold` of
+/// its signers to sign the transaction and be passed as remaining accounts.
+pub fn authorize(authority: &AccountInfo, remaining_accounts: &[AccountInfo]) -> Result<()> {
+    if authority.is_signer {
+        return Ok(());
+    }
+    require_keys_eq!(*authority.owner, crate::ID, AmmError::Unauthorized);
+
+    let multisig = Multisig::try_deserialize(&mut &authority.try_borrow_data()?[..])
+        .map_err(|_| AmmError::Unauthorized)?;
+    let approvals = multisig
+        .signers
+        .iter()
+        .filter(|signer| {
+            remaining_accounts
+                .iter()
+                .any(|account| account.is_signer && account.key == *signer)
+        })
+        .count();
+    require!(
+        approvals >= multisig.threshold as usize,
+        AmmError::NotEnoughSigners
+    );
+    Ok(())
+}
@@ -26,4 +26,102 @@ pub enum AmmError {
     EmptyPool,
     #[msg("Invalid fee")]
     InvalidFee,
+    #[msg("Signer is not allowed to perform this action")]
+    Unauthorized,
+    #[msg("There is no pending admin transfer")]
+    NoPendingAdmin,
+    #[msg("AMM or pool is paused")]
+    Paused,
+    #[msg("Fee tier is not registered on the AMM")]
+    FeeTierNotFound,
+    #[msg("Fee tier is already registered on the AMM")]
+    FeeTierAlreadyExists,
+    #[msg("Too many fee tiers")]
+    TooManyFeeTiers,
+    #[msg("Signer is not allowed to create pools on this AMM")]
+    PoolCreationNotAllowed,
+    #[msg("Mint is not allowed on this AMM")]
+    MintNotAllowed,
+    #[msg("Invalid timelock delay")]
+    InvalidTimelockDelay,
+    #[msg("A config change is already queued")]
+    ConfigChangeAlreadyQueued,
+    #[msg("There is no queued config change")]
+    NoPendingConfigChange,
+    #[msg("Timelock delay has not elapsed yet")]
+    TimelockNotElapsed,
+    #[msg("Pool does not match the config change")]
+    PoolMismatch,
+    #[msg("Multisig signers or threshold are invalid")]
+    InvalidMultisig,
+    #[msg("Not enough multisig signers approved the instruction")]
+    NotEnoughSigners,
+    #[msg("Role cannot be set with this instruction")]
+    InvalidRole,
+    #[msg("Input amount is more than expected max")]
+    InputAmountTooHigh,
+    #[msg("Pool does not hold enough liquidity for the output amount")]
+    InsufficientLiquidity,
+    #[msg("Transaction deadline has passed")]
+    DeadlineExceeded,
+    #[msg("Output mint does not match the swap direction")]
+    InvalidOutputMint,
+    #[msg("Route accounts do not form a path from the input to the output mint")]
+    InvalidRoute,
+    #[msg("Pool invariant does not hold after the flash swap")]
+    InvariantViolated,
+    #[msg("Pool has a flash loan in progress")]
+    FlashLoanActive,
+    #[msg("Pool has no flash loan to repay")]
+    NoFlashLoan,
+    #[msg("No matching flash_repay later in the transaction")]
+    FlashRepayMissing,
+    #[msg("Token account does not match the flash loan side")]
+    InvalidFlashLoanAccount,
+    #[msg("Flash loans cannot be borrowed or repaid through CPI")]
+    FlashLoanNotTopLevel,
+    #[msg("No observation is old enough for the TWAP window")]
+    ObservationNotFound,
+    #[msg("Order price and TWAP window must be greater than zero, the window within the observation history")]
+    InvalidOrderPrice,
+    #[msg("Mint does not match the order side")]
+    InvalidOrderMint,
+    #[msg("Pool price does not meet the order condition")]
+    OrderNotTriggered,
+    #[msg("DCA amount, interval, period count and TWAP window must be non-zero, the window within the observation history, slippage at most 10000 bps and the keeper fee at most 100 bps")]
+    InvalidDcaParams,
+    #[msg("Mint does not match the DCA side")]
+    InvalidDcaMint,
+    #[msg("DCA interval has not elapsed")]
+    DcaNotDue,
+    #[msg("All DCA periods have been executed")]
+    DcaCompleted,
+    #[msg("Long-term order must sell at least 0.1% of the input reserve per interval and expire within 24 intervals")]
+    InvalidLongTermOrder,
+    #[msg("Pool has no room for another long-term order expiry")]
+    TwammExpiriesFull,
+    #[msg("Long-term orders have not been executed past the expiry yet")]
+    TwammExpiryNotCrossed,
+    #[msg("Pool has no long-term order expiry at the timestamp")]
+    TwammExpiryNotFound,
+    #[msg("Account does not match the payer of the expiry snapshot")]
+    InvalidSnapshotPayer,
+    #[msg("Neither mint of the pool is the native mint")]
+    NotNativeMintPool,
+    #[msg("Unwrapped SOL can only be paid to the signer")]
+    InvalidNativeSolRecipient,
+    #[msg("Transfer hook program is already approved")]
+    TransferHookProgramAlreadyApproved,
+    #[msg("Too many approved transfer hook programs")]
+    TooManyTransferHookPrograms,
+    #[msg("Transfer hook program is not approved")]
+    TransferHookProgramNotApproved,
+    #[msg("Mint has a Token-2022 extension pools do not support")]
+    UnsupportedMintExtension,
+    #[msg("Transfer hook program of the mint can still be changed")]
+    TransferHookAuthoritySet,
+    #[msg("DCA slippage bound must exceed the pool fee and the keeper fee")]
+    DcaSlippageBelowFees,
+    #[msg("Multisig does not match the config change")]
+    MultisigMismatch,
 }
@@ -0,0 +1,33 @@
+use crate::auth::authorize;
+use crate::errors::AmmError;
+use crate::states::{Amm, AMM_SEED};
+use anchor_lang::prelude::*;
+
+pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+    authorize(&ctx.accounts.new_admin, ctx.remaining_accounts)?;
+
+    let amm = &mut ctx.accounts.amm;
+    let pending_admin = amm.pending_admin.ok_or(AmmError::NoPendingAdmin)?;
+    require_keys_eq!(
+        pending_admin,
+        ctx.accounts.new_admin.key(),
+        AmmError::Unauthorized
+    );
+
+    amm.admin = pending_admin;
+    amm.pending_admin = None;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    /// CHECK: Wallet or `Multisig` matching `Amm.pending_admin`, its approval is checked by `authorize`
+    pub new_admin: UncheckedAccount<'info>,
+}
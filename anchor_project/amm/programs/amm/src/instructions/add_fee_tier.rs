@@ -0,0 +1,40 @@
+use crate::auth::authorize;
+use crate::errors::AmmError;
+use crate::states::{Amm, AMM_SEED, DEFAULT_FEE_TIER, MAX_FEE_BPS, MAX_FEE_TIERS};
+use anchor_lang::prelude::*;
+
+pub fn add_fee_tier(ctx: Context<AddFeeTier>, fee_tier: u16) -> Result<()> {
+    authorize(&ctx.accounts.fee_manager, ctx.remaining_accounts)?;
+
+    require!(
+        fee_tier != DEFAULT_FEE_TIER && fee_tier < MAX_FEE_BPS,
+        AmmError::InvalidFee
+    );
+
+    let amm = &mut ctx.accounts.amm;
+    require!(
+        !amm.fee_tiers.contains(&fee_tier),
+        AmmError::FeeTierAlreadyExists
+    );
+    require!(
+        amm.fee_tiers.len() < MAX_FEE_TIERS,
+        AmmError::TooManyFeeTiers
+    );
+
+    amm.fee_tiers.push(fee_tier);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AddFeeTier<'info> {
+    #[account(
+        mut,
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+        has_one = fee_manager @ AmmError::Unauthorized,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    /// CHECK: Wallet or `Multisig` matching `Amm.fee_manager`, its approval is checked by `authorize`
+    pub fee_manager: UncheckedAccount<'info>,
+}
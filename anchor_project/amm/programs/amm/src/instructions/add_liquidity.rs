@@ -1,29 +1,97 @@
 use crate::errors::AmmError;
-use crate::states::{AmmPool, AMM_MINT_LIQUIDITY_SEED, AMM_POOL_AUTHORITY_SEED, AMM_POOL_SEED};
+use crate::states::{
+    Amm, AmmPool, Observations, AMM_MINT_LIQUIDITY_SEED, AMM_POOL_AUTHORITY_SEED, AMM_POOL_SEED,
+    AMM_SEED, OBSERVATIONS_SEED,
+};
+use crate::twamm::execute_virtual_orders;
+use crate::utils::{
+    amount_with_transfer_fee, check_deadline, native_side, transfer_checked_with_hook,
+    transfer_fee, wrap_sol,
+};
 use anchor_lang::prelude::*;
 use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token::{
-    mint_to, transfer_checked, Mint, MintTo, Token, TokenAccount, TransferChecked,
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{
+    mint_to, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked,
 };
 
-pub fn add_liquidity(ctx: Context<AddLiquidity>, amount_a: u64, amount_b: u64) -> Result<()> {
+/// Accounts of transfer hooks of the mints go into `remaining_accounts`
+pub fn add_liquidity<'info>(
+    ctx: Context<'_, '_, 'info, 'info, AddLiquidity<'info>>,
+    amount_a: u64,
+    amount_b: u64,
+    deadline: Option<i64>,
+    native_sol: bool,
+) -> Result<()> {
+    check_deadline(deadline)?;
     require!(amount_a > 0 && amount_b > 0, AmmError::AmountIsZero);
+    require!(
+        !ctx.accounts.amm.paused && !ctx.accounts.pool.paused,
+        AmmError::Paused
+    );
+    require!(
+        !ctx.accounts.pool.flash_loan_active,
+        AmmError::FlashLoanActive
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    execute_virtual_orders(
+        &mut ctx.accounts.pool,
+        &ctx.accounts.amm,
+        ctx.accounts.pool_account_a.amount,
+        ctx.accounts.pool_account_b.amount,
+        now,
+    )?;
+
+    let (reserve_a, reserve_b) = ctx.accounts.pool.reserves(
+        ctx.accounts.pool_account_a.amount,
+        ctx.accounts.pool_account_b.amount,
+    )?;
+    ctx.accounts
+        .pool
+        .update_price_accumulators(reserve_a, reserve_b, now);
+    ctx.accounts.observations.record(&ctx.accounts.pool);
+
+    let ((deposit_a, deposit_b), (amount_a, amount_b)) = deposit_amounts_with_transfer_fees(
+        &ctx.accounts.mint_a.to_account_info(),
+        &ctx.accounts.mint_b.to_account_info(),
+        reserve_a,
+        amount_a,
+        reserve_b,
+        amount_b,
+    )?;
+
+    // exactly what the deposit sends is wrapped, so nothing is left to unwrap afterwards
+    if native_sol {
+        let native_is_a = native_side(ctx.accounts.mint_a.key(), ctx.accounts.mint_b.key())?;
+        let (depositor_native, amount, token_program) = if native_is_a {
+            (
+                &mut ctx.accounts.depositor_account_a,
+                amount_a,
+                &ctx.accounts.token_program_a,
+            )
+        } else {
+            (
+                &mut ctx.accounts.depositor_account_b,
+                amount_b,
+                &ctx.accounts.token_program_b,
+            )
+        };
+        wrap_sol(
+            ctx.accounts.depositor.to_account_info(),
+            depositor_native.to_account_info(),
+            amount,
+            ctx.accounts.system_program.to_account_info(),
+            token_program.to_account_info(),
+        )?;
+        depositor_native.reload()?;
+    }
 
     let depositor_account_a = &ctx.accounts.depositor_account_a;
     let depositor_account_b = &ctx.accounts.depositor_account_b;
 
-    let pool_a = &mut ctx.accounts.pool_account_a;
-    let pool_b = &mut ctx.accounts.pool_account_b;
-
-    let reserve_a = pool_a.amount;
-    let reserve_b = pool_b.amount;
-
-    let is_new_pool = reserve_a == 0 && reserve_b == 0;
-    let (amount_a, amount_b) = if is_new_pool {
-        (amount_a, amount_b)
-    } else {
-        calculate_liquidity_amounts(reserve_a, amount_a, reserve_b, amount_b)?
-    };
+    let pool_a = &ctx.accounts.pool_account_a;
+    let pool_b = &ctx.accounts.pool_account_b;
 
     require!(
         depositor_account_a.amount >= amount_a,
@@ -36,7 +104,7 @@ pub fn add_liquidity(ctx: Context<AddLiquidity>, amount_a: u64, amount_b: u64) -
 
     let lp_mint = &ctx.accounts.mint_liquidity;
     let total_lp = lp_mint.supply;
-    let lp_amount = calculate_lp(amount_a, amount_b, reserve_a, reserve_b, total_lp)?;
+    let lp_amount = calculate_lp(deposit_a, deposit_b, reserve_a, reserve_b, total_lp)?;
 
     // transfer token a
     let cpi_accounts = TransferChecked {
@@ -45,9 +113,10 @@ pub fn add_liquidity(ctx: Context<AddLiquidity>, amount_a: u64, amount_b: u64) -
         to: pool_a.to_account_info(),
         authority: ctx.accounts.depositor.to_account_info(),
     };
-    let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_context = CpiContext::new(cpi_program, cpi_accounts);
-    transfer_checked(cpi_context, amount_a, ctx.accounts.mint_a.decimals)?;
+    let cpi_program = ctx.accounts.token_program_a.to_account_info();
+    let cpi_context = CpiContext::new(cpi_program, cpi_accounts)
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+    transfer_checked_with_hook(cpi_context, amount_a, ctx.accounts.mint_a.decimals)?;
 
     // transfer token b
     let cpi_accounts = TransferChecked {
@@ -56,17 +125,19 @@ pub fn add_liquidity(ctx: Context<AddLiquidity>, amount_a: u64, amount_b: u64) -
         to: pool_b.to_account_info(),
         authority: ctx.accounts.depositor.to_account_info(),
     };
-    let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_context = CpiContext::new(cpi_program, cpi_accounts);
-    transfer_checked(cpi_context, amount_b, ctx.accounts.mint_b.decimals)?;
+    let cpi_program = ctx.accounts.token_program_b.to_account_info();
+    let cpi_context = CpiContext::new(cpi_program, cpi_accounts)
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+    transfer_checked_with_hook(cpi_context, amount_b, ctx.accounts.mint_b.decimals)?;
 
     // mint lp
     let authority = &ctx.accounts.authority;
     let authority_signer_seeds: &[&[&[u8]]] = &[&[
-        &AMM_POOL_AUTHORITY_SEED.as_bytes(),
+        AMM_POOL_AUTHORITY_SEED.as_bytes(),
         &ctx.accounts.pool.amm.to_bytes(),
         &ctx.accounts.mint_a.key().to_bytes(),
         &ctx.accounts.mint_b.key().to_bytes(),
+        &ctx.accounts.pool.fee_tier.to_le_bytes(),
         &[ctx.bumps.authority],
     ]];
     let cpi_accounts = MintTo {
@@ -82,6 +153,47 @@ pub fn add_liquidity(ctx: Context<AddLiquidity>, amount_a: u64, amount_b: u64) -
     Ok(())
 }
 
+/// Returns the amounts that arrive in the pool when the depositor sends at most `amount_a` and `amount_b`, and
+/// what the depositor sends for them with the transfer fees of the mints on top. Grossing a deposit back up can
+/// round past what was offered, that fails with `InputAmountTooHigh` rather than debiting more.
+pub(crate) fn deposit_amounts_with_transfer_fees(
+    mint_a: &AccountInfo,
+    mint_b: &AccountInfo,
+    reserve_a: u64,
+    amount_a: u64,
+    reserve_b: u64,
+    amount_b: u64,
+) -> Result<((u64, u64), (u64, u64))> {
+    let (deposit_a, deposit_b) = deposit_amounts(
+        reserve_a,
+        amount_a - transfer_fee(mint_a, amount_a)?,
+        reserve_b,
+        amount_b - transfer_fee(mint_b, amount_b)?,
+    )?;
+    let sent_a = amount_with_transfer_fee(mint_a, deposit_a)?;
+    let sent_b = amount_with_transfer_fee(mint_b, deposit_b)?;
+    require!(
+        sent_a <= amount_a && sent_b <= amount_b,
+        AmmError::InputAmountTooHigh
+    );
+    Ok(((deposit_a, deposit_b), (sent_a, sent_b)))
+}
+
+/// Returns the amounts added to the reserves, the first deposit sets the price of the pool
+fn deposit_amounts(
+    reserve_a: u64,
+    amount_a: u64,
+    reserve_b: u64,
+    amount_b: u64,
+) -> Result<(u64, u64)> {
+    let is_new_pool = reserve_a == 0 && reserve_b == 0;
+    if is_new_pool {
+        Ok((amount_a, amount_b))
+    } else {
+        calculate_liquidity_amounts(reserve_a, amount_a, reserve_b, amount_b)
+    }
+}
+
 fn calculate_liquidity_amounts(
     reserve_a: u64,
     amount_a: u64,
@@ -117,7 +229,7 @@ fn calculate_liquidity_amounts(
     }
 }
 
-fn calculate_lp(
+pub(crate) fn calculate_lp(
     amount_a: u64,
     amount_b: u64,
     reserve_a: u64,
@@ -157,7 +269,7 @@ pub fn integer_sqrt_u128(n: u128) -> u128 {
     }
 
     let mut x = n;
-    let mut y = (x + 1) / 2;
+    let mut y = x.div_ceil(2);
 
     while y < x {
         x = y;
@@ -169,45 +281,67 @@ pub fn integer_sqrt_u128(n: u128) -> u128 {
 #[derive(Accounts)]
 pub struct AddLiquidity<'info> {
     #[account(
-        seeds = [AMM_POOL_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref()],
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub amm: Box<Account<'info, Amm>>,
+
+    #[account(
+        mut,
+        seeds = [AMM_POOL_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
         bump,
         has_one = mint_a,
         has_one = mint_b,
+        has_one = amm,
+        has_one = token_program_a,
+        has_one = token_program_b,
     )]
     pub pool: Box<Account<'info, AmmPool>>,
 
-    pub mint_a: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [OBSERVATIONS_SEED.as_bytes(), pool.key().as_ref()],
+        bump,
+        has_one = pool,
+    )]
+    pub observations: Box<Account<'info, Observations>>,
+
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
 
-    pub mint_b: Box<Account<'info, Mint>>,
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
 
     /// CHECK readonly
     #[account(
-        seeds=[AMM_POOL_AUTHORITY_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref()],
+        seeds=[AMM_POOL_AUTHORITY_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
         bump,
     )]
     pub authority: AccountInfo<'info>,
 
     #[account(
         mut,
-        seeds = [AMM_MINT_LIQUIDITY_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref()],
+        seeds = [AMM_MINT_LIQUIDITY_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
         bump,
     )]
-    pub mint_liquidity: Box<Account<'info, Mint>>,
+    pub mint_liquidity: Box<InterfaceAccount<'info, Mint>>,
 
     #[account(
         mut,
         associated_token::mint = mint_a,
+        associated_token::token_program = token_program_a,
         associated_token::authority = authority
     )]
-    pub pool_account_a: Box<Account<'info, TokenAccount>>,
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
         associated_token::mint = mint_b,
+        associated_token::token_program = token_program_b,
         associated_token::authority = authority
     )]
-    pub pool_account_b: Box<Account<'info, TokenAccount>>,
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
 
+    /// Pays the lamports when `native_sol` is set
+    #[account(mut)]
     pub depositor: Signer<'info>,
 
     #[account(
@@ -216,26 +350,33 @@ pub struct AddLiquidity<'info> {
         associated_token::mint = mint_liquidity,
         associated_token::authority = depositor,
     )]
-    pub depositor_account_liquidity: Box<Account<'info, TokenAccount>>,
+    pub depositor_account_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
-        mut,
+        init_if_needed,
+        payer = payer,
         associated_token::mint = mint_a,
+        associated_token::token_program = token_program_a,
         associated_token::authority = depositor
     )]
-    pub depositor_account_a: Box<Account<'info, TokenAccount>>,
+    pub depositor_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
-        mut,
+        init_if_needed,
+        payer = payer,
         associated_token::mint = mint_b,
+        associated_token::token_program = token_program_b,
         associated_token::authority = depositor
     )]
-    pub depositor_account_b: Box<Account<'info, TokenAccount>>,
+    pub depositor_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(mut)]
     pub payer: Signer<'info>,
 
+    /// Mints the LP token
     pub token_program: Program<'info, Token>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
@@ -0,0 +1,41 @@
+use crate::auth::authorize;
+use crate::errors::AmmError;
+use crate::states::{Amm, PoolCreator, AMM_SEED, POOL_CREATOR_SEED};
+use anchor_lang::prelude::*;
+
+pub fn add_pool_creator(ctx: Context<AddPoolCreator>, creator: Pubkey) -> Result<()> {
+    authorize(&ctx.accounts.pool_curator, ctx.remaining_accounts)?;
+
+    let pool_creator = &mut ctx.accounts.pool_creator;
+    pool_creator.amm = ctx.accounts.amm.key();
+    pool_creator.creator = creator;
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(creator: Pubkey)]
+pub struct AddPoolCreator<'info> {
+    #[account(
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+        has_one = pool_curator @ AmmError::Unauthorized,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + PoolCreator::INIT_SPACE,
+        seeds = [POOL_CREATOR_SEED.as_bytes(), amm.key().as_ref(), creator.as_ref()],
+        bump,
+    )]
+    pub pool_creator: Account<'info, PoolCreator>,
+
+    /// CHECK: Wallet or `Multisig` matching `Amm.pool_curator`, its approval is checked by `authorize`
+    pub pool_curator: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
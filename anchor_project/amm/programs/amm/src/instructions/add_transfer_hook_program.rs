@@ -0,0 +1,39 @@
+use crate::auth::authorize;
+use crate::errors::AmmError;
+use crate::states::{Amm, AMM_SEED, MAX_TRANSFER_HOOK_PROGRAMS};
+use anchor_lang::prelude::*;
+
+/// Approves a transfer hook program, pools can then be created with Token-2022 mints that use it
+pub fn add_transfer_hook_program(
+    ctx: Context<AddTransferHookProgram>,
+    program_id: Pubkey,
+) -> Result<()> {
+    authorize(&ctx.accounts.admin, ctx.remaining_accounts)?;
+
+    let amm = &mut ctx.accounts.amm;
+    require!(
+        !amm.transfer_hook_programs.contains(&program_id),
+        AmmError::TransferHookProgramAlreadyApproved
+    );
+    require!(
+        amm.transfer_hook_programs.len() < MAX_TRANSFER_HOOK_PROGRAMS,
+        AmmError::TooManyTransferHookPrograms
+    );
+
+    amm.transfer_hook_programs.push(program_id);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AddTransferHookProgram<'info> {
+    #[account(
+        mut,
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+        has_one = admin @ AmmError::Unauthorized,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    /// CHECK: Wallet or `Multisig` matching `Amm.admin`, its approval is checked by `authorize`
+    pub admin: UncheckedAccount<'info>,
+}
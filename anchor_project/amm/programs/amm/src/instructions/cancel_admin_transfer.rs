@@ -0,0 +1,28 @@
+use crate::auth::authorize;
+use crate::errors::AmmError;
+use crate::states::{Amm, AMM_SEED};
+use anchor_lang::prelude::*;
+
+pub fn cancel_admin_transfer(ctx: Context<CancelAdminTransfer>) -> Result<()> {
+    authorize(&ctx.accounts.admin, ctx.remaining_accounts)?;
+
+    let amm = &mut ctx.accounts.amm;
+    require!(amm.pending_admin.is_some(), AmmError::NoPendingAdmin);
+
+    amm.pending_admin = None;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelAdminTransfer<'info> {
+    #[account(
+        mut,
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+        has_one = admin @ AmmError::Unauthorized,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    /// CHECK: Wallet or `Multisig` matching `Amm.admin`, its approval is checked by `authorize`
+    pub admin: UncheckedAccount<'info>,
+}
@@ -0,0 +1,46 @@
+use crate::auth::authorize;
+use crate::errors::AmmError;
+use crate::states::{Amm, AmmPool, ConfigChangeKind, PendingConfigChange, AMM_POOL_SEED, AMM_SEED};
+use anchor_lang::prelude::*;
+
+/// Drops the queued change of `kind`, only the role that can queue it can cancel it. Changes that target a pool are
+/// dropped from the queue of `pool`.
+pub fn cancel_config_change(
+    ctx: Context<CancelConfigChange>,
+    kind: ConfigChangeKind,
+) -> Result<()> {
+    let amm = &mut ctx.accounts.amm;
+    let authority = &ctx.accounts.authority;
+    let queue = amm.config_change_queue(ctx.accounts.pool.as_deref_mut(), kind)?;
+    let index = PendingConfigChange::position(queue, kind)?;
+    let pending = queue.remove(index);
+
+    require_keys_eq!(
+        authority.key(),
+        pending.change.authority(amm),
+        AmmError::Unauthorized
+    );
+    authorize(authority, ctx.remaining_accounts)
+}
+
+#[derive(Accounts)]
+pub struct CancelConfigChange<'info> {
+    #[account(
+        mut,
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    /// Only needed for changes that target a pool
+    #[account(
+        mut,
+        seeds = [AMM_POOL_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
+        bump,
+        has_one = amm,
+    )]
+    pub pool: Option<Account<'info, AmmPool>>,
+
+    /// CHECK: Wallet or `Multisig` holding the role of the change, its approval is checked by `authorize`
+    pub authority: UncheckedAccount<'info>,
+}
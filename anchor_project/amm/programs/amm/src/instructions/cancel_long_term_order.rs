@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+use crate::instructions::UpdateLongTermOrder;
+
+/// Stops a long-term order, pays out what it bought and returns the input it has not sold yet or could not sell
+/// while the pool had no liquidity. Accounts of transfer
+/// hooks of the mints go into `remaining_accounts`.
+pub fn cancel_long_term_order<'info>(
+    ctx: Context<'_, '_, 'info, 'info, UpdateLongTermOrder<'info>>,
+) -> Result<()> {
+    let now = ctx.accounts.execute()?;
+    let (proceeds, refund) = ctx.accounts.take_proceeds(now)?;
+
+    let order = &ctx.accounts.order;
+    let is_sell_a = order.is_sell_a;
+    let (sell_rate, expiry) = (order.sell_rate, order.expiry);
+
+    // an expired order sold everything, its rate already left the pool
+    let mut unsold = refund;
+    if now < expiry {
+        let remaining = sell_rate
+            .checked_mul((expiry - now) as u64)
+            .ok_or(AmmError::MathOverflow)?;
+        let twamm = &mut ctx.accounts.pool.twamm;
+        twamm.cancel_order(is_sell_a, sell_rate, expiry)?;
+        let balance = twamm.balance(is_sell_a);
+        *balance = balance
+            .checked_sub(remaining)
+            .ok_or(AmmError::MathOverflow)?;
+        unsold = unsold
+            .checked_add(remaining)
+            .ok_or(AmmError::MathOverflow)?;
+    }
+
+    let bump = ctx.bumps.authority;
+    let hook_accounts = ctx.remaining_accounts;
+    ctx.accounts
+        .pay(!is_sell_a, proceeds, bump, hook_accounts)?;
+    ctx.accounts.pay(is_sell_a, unsold, bump, hook_accounts)?;
+    ctx.accounts.close()
+}
@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+use crate::states::{AmmPool, Order, ORDER_SEED};
+use crate::utils::{close_token_account, transfer_checked_with_hook};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
+
+/// Returns the escrow, bounty included, to the owner and closes the order. Accounts of a transfer hook of the input
+/// mint go into `remaining_accounts`.
+pub fn cancel_order<'info>(ctx: Context<'_, '_, 'info, 'info, CancelOrder<'info>>) -> Result<()> {
+    let order = &ctx.accounts.order;
+    let order_signer_seeds: &[&[&[u8]]] = &[&[
+        ORDER_SEED.as_bytes(),
+        order.pool.as_ref(),
+        order.owner.as_ref(),
+        &order.id.to_le_bytes(),
+        &[ctx.bumps.order],
+    ]];
+    let cpi_program = &ctx.accounts.input_token_program;
+
+    let input_mint = &ctx.accounts.input_mint;
+    let cpi_accounts = TransferChecked {
+        mint: input_mint.to_account_info(),
+        from: ctx.accounts.escrow.to_account_info(),
+        to: ctx.accounts.owner_input_account.to_account_info(),
+        authority: order.to_account_info(),
+    };
+    let cpi_context = CpiContext::new(cpi_program.to_account_info(), cpi_accounts)
+        .with_signer(order_signer_seeds)
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+    transfer_checked_with_hook(cpi_context, ctx.accounts.escrow.amount, input_mint.decimals)?;
+
+    close_token_account(
+        ctx.accounts.escrow.to_account_info(),
+        ctx.accounts.owner.to_account_info(),
+        order.to_account_info(),
+        input_mint.to_account_info(),
+        cpi_program.to_account_info(),
+        order_signer_seeds,
+    )
+}
+
+#[derive(Accounts)]
+pub struct CancelOrder<'info> {
+    #[account(
+        mut,
+        seeds = [ORDER_SEED.as_bytes(), order.pool.as_ref(), owner.key().as_ref(), order.id.to_le_bytes().as_ref()],
+        bump,
+        has_one = pool,
+        has_one = owner,
+        close = owner,
+    )]
+    pub order: Box<Account<'info, Order>>,
+
+    pub pool: Box<Account<'info, AmmPool>>,
+
+    /// `pool.mint_a` when the order sells A, `pool.mint_b` otherwise, writable for the harvest of withheld
+    /// transfer fees
+    #[account(
+        mut,
+        constraint = input_mint.key() == if order.is_swap_a { pool.mint_a } else { pool.mint_b } @ AmmError::InvalidOrderMint,
+        mint::token_program = input_token_program,
+    )]
+    pub input_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = input_mint,
+        associated_token::token_program = input_token_program,
+        associated_token::authority = order,
+    )]
+    pub escrow: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = input_mint,
+        associated_token::token_program = input_token_program,
+        associated_token::authority = owner,
+    )]
+    pub owner_input_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub input_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,132 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+use crate::states::{AmmPool, Dca, DCA_SEED};
+use crate::utils::{close_token_account, transfer_checked_with_hook};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
+
+/// Returns the unsold deposit and the proceeds to the owner and closes the DCA, finished or not. Accounts of transfer
+/// hooks of the mints go into `remaining_accounts`.
+pub fn close_dca<'info>(ctx: Context<'_, '_, 'info, 'info, CloseDca<'info>>) -> Result<()> {
+    let dca = &ctx.accounts.dca;
+    let dca_signer_seeds: &[&[&[u8]]] = &[&[
+        DCA_SEED.as_bytes(),
+        dca.pool.as_ref(),
+        dca.owner.as_ref(),
+        &dca.id.to_le_bytes(),
+        &[ctx.bumps.dca],
+    ]];
+
+    let vaults = [
+        (
+            &ctx.accounts.input_mint,
+            &ctx.accounts.input_vault,
+            &ctx.accounts.owner_input_account,
+            &ctx.accounts.input_token_program,
+        ),
+        (
+            &ctx.accounts.output_mint,
+            &ctx.accounts.output_vault,
+            &ctx.accounts.owner_output_account,
+            &ctx.accounts.output_token_program,
+        ),
+    ];
+    for (mint, vault, owner_account, cpi_program) in vaults {
+        let cpi_accounts = TransferChecked {
+            mint: mint.to_account_info(),
+            from: vault.to_account_info(),
+            to: owner_account.to_account_info(),
+            authority: dca.to_account_info(),
+        };
+        let cpi_context = CpiContext::new(cpi_program.to_account_info(), cpi_accounts)
+            .with_signer(dca_signer_seeds)
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        transfer_checked_with_hook(cpi_context, vault.amount, mint.decimals)?;
+
+        close_token_account(
+            vault.to_account_info(),
+            ctx.accounts.owner.to_account_info(),
+            dca.to_account_info(),
+            mint.to_account_info(),
+            cpi_program.to_account_info(),
+            dca_signer_seeds,
+        )?;
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseDca<'info> {
+    #[account(
+        mut,
+        seeds = [DCA_SEED.as_bytes(), dca.pool.as_ref(), owner.key().as_ref(), dca.id.to_le_bytes().as_ref()],
+        bump,
+        has_one = pool,
+        has_one = owner,
+        close = owner,
+    )]
+    pub dca: Box<Account<'info, Dca>>,
+
+    pub pool: Box<Account<'info, AmmPool>>,
+
+    /// `pool.mint_a` when the DCA sells A, `pool.mint_b` otherwise. Both mints are writable for the harvest of
+    /// withheld transfer fees.
+    #[account(
+        mut,
+        constraint = input_mint.key() == if dca.is_swap_a { pool.mint_a } else { pool.mint_b } @ AmmError::InvalidDcaMint,
+        mint::token_program = input_token_program,
+    )]
+    pub input_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// `pool.mint_b` when the DCA sells A, `pool.mint_a` otherwise
+    #[account(
+        mut,
+        constraint = output_mint.key() == if dca.is_swap_a { pool.mint_b } else { pool.mint_a } @ AmmError::InvalidDcaMint,
+        mint::token_program = output_token_program,
+    )]
+    pub output_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = input_mint,
+        associated_token::token_program = input_token_program,
+        associated_token::authority = dca,
+    )]
+    pub input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = output_mint,
+        associated_token::token_program = output_token_program,
+        associated_token::authority = dca,
+    )]
+    pub output_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = input_mint,
+        associated_token::token_program = input_token_program,
+        associated_token::authority = owner,
+    )]
+    pub owner_input_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = output_mint,
+        associated_token::token_program = output_token_program,
+        associated_token::authority = owner,
+    )]
+    pub owner_output_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub input_token_program: Interface<'info, TokenInterface>,
+    pub output_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,142 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+use crate::states::{Amm, AmmPool, AMM_POOL_AUTHORITY_SEED, AMM_POOL_SEED, AMM_SEED};
+use crate::utils::transfer_checked_with_hook;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
+
+/// Sends the protocol fees of the pool to the treasury, accounts of transfer hooks of the mints go into
+/// `remaining_accounts`
+pub fn collect_protocol_fees<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CollectProtocolFees<'info>>,
+) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let amount_a = pool.protocol_fees_a;
+    let amount_b = pool.protocol_fees_b;
+    require!(amount_a > 0 || amount_b > 0, AmmError::AmountIsZero);
+
+    pool.protocol_fees_a = 0;
+    pool.protocol_fees_b = 0;
+
+    let authority = &ctx.accounts.authority;
+    let authority_signer_seeds: &[&[&[u8]]] = &[&[
+        AMM_POOL_AUTHORITY_SEED.as_bytes(),
+        &ctx.accounts.pool.amm.to_bytes(),
+        &ctx.accounts.mint_a.key().to_bytes(),
+        &ctx.accounts.mint_b.key().to_bytes(),
+        &ctx.accounts.pool.fee_tier.to_le_bytes(),
+        &[ctx.bumps.authority],
+    ]];
+    // send fees of token a to the treasury
+    if amount_a > 0 {
+        let mint_a = &ctx.accounts.mint_a;
+        let cpi_accounts = TransferChecked {
+            mint: mint_a.to_account_info(),
+            from: ctx.accounts.pool_account_a.to_account_info(),
+            to: ctx.accounts.treasury_account_a.to_account_info(),
+            authority: authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program_a.to_account_info();
+        let cpi_context = CpiContext::new(cpi_program, cpi_accounts)
+            .with_signer(authority_signer_seeds)
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        transfer_checked_with_hook(cpi_context, amount_a, mint_a.decimals)?;
+    }
+
+    // send fees of token b to the treasury
+    if amount_b > 0 {
+        let mint_b = &ctx.accounts.mint_b;
+        let cpi_accounts = TransferChecked {
+            mint: mint_b.to_account_info(),
+            from: ctx.accounts.pool_account_b.to_account_info(),
+            to: ctx.accounts.treasury_account_b.to_account_info(),
+            authority: authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program_b.to_account_info();
+        let cpi_context = CpiContext::new(cpi_program, cpi_accounts)
+            .with_signer(authority_signer_seeds)
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        transfer_checked_with_hook(cpi_context, amount_b, mint_b.decimals)?;
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CollectProtocolFees<'info> {
+    #[account(
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+        has_one = treasury,
+    )]
+    pub amm: Box<Account<'info, Amm>>,
+
+    #[account(
+        mut,
+        seeds = [AMM_POOL_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
+        bump,
+        has_one = mint_a,
+        has_one = mint_b,
+        has_one = amm,
+        has_one = token_program_a,
+        has_one = token_program_b,
+    )]
+    pub pool: Box<Account<'info, AmmPool>>,
+
+    /// CHECK: readonly
+    #[account(
+        seeds=[AMM_POOL_AUTHORITY_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub authority: AccountInfo<'info>,
+
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::token_program = token_program_a,
+        associated_token::authority = authority,
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::token_program = token_program_b,
+        associated_token::authority = authority,
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: only receives the fees, matched against `Amm.treasury`
+    pub treasury: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_a,
+        associated_token::token_program = token_program_a,
+        associated_token::authority = treasury,
+    )]
+    pub treasury_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_b,
+        associated_token::token_program = token_program_b,
+        associated_token::authority = treasury,
+    )]
+    pub treasury_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
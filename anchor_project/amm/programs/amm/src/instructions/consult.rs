@@ -0,0 +1,132 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+use crate::states::{
+    Amm, AmmPool, Observations, AMM_POOL_AUTHORITY_SEED, AMM_POOL_SEED, AMM_SEED, OBSERVATIONS_SEED,
+};
+use crate::twamm::execute_virtual_orders;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Time weighted average prices of a pool, both Q64.64
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TwapPrice {
+    /// Price of token A in token B
+    pub price_a_x64: u128,
+    /// Price of token B in token A
+    pub price_b_x64: u128,
+    /// Seconds actually covered, at least the requested window
+    pub window_seconds: i64,
+}
+
+/// Averages the pool price since the newest observation that is at least `window_seconds` old,
+/// the result is set as return data
+pub fn consult(ctx: Context<Consult>, window_seconds: u32) -> Result<TwapPrice> {
+    require!(
+        !ctx.accounts.pool.flash_loan_active,
+        AmmError::FlashLoanActive
+    );
+
+    // the long-term orders still owed up to now move the price the average extends with
+    let mut pool = (**ctx.accounts.pool).clone();
+    execute_virtual_orders(
+        &mut pool,
+        &ctx.accounts.amm,
+        ctx.accounts.pool_account_a.amount,
+        ctx.accounts.pool_account_b.amount,
+        Clock::get()?.unix_timestamp,
+    )?;
+    let (reserve_a, reserve_b) = pool.reserves(
+        ctx.accounts.pool_account_a.amount,
+        ctx.accounts.pool_account_b.amount,
+    )?;
+    twap(
+        &pool,
+        &ctx.accounts.observations,
+        reserve_a,
+        reserve_b,
+        window_seconds,
+    )
+}
+
+/// Averages the price of `pool` over at least `window_seconds`, extending the accumulators to now
+pub(crate) fn twap(
+    pool: &AmmPool,
+    observations: &Observations,
+    reserve_a: u64,
+    reserve_b: u64,
+    window_seconds: u32,
+) -> Result<TwapPrice> {
+    let now = Clock::get()?.unix_timestamp;
+    let (price_a_cumulative, price_b_cumulative) =
+        pool.cumulative_prices(reserve_a, reserve_b, now);
+
+    let observation = observations
+        .at_or_before(now - window_seconds as i64)
+        .ok_or(AmmError::ObservationNotFound)?;
+    let elapsed = now - observation.timestamp;
+    require!(elapsed > 0, AmmError::ObservationNotFound);
+
+    // the accumulators wrap, their difference does not as long as it fits the window
+    Ok(TwapPrice {
+        price_a_x64: price_a_cumulative.wrapping_sub(observation.price_a_cumulative)
+            / elapsed as u128,
+        price_b_x64: price_b_cumulative.wrapping_sub(observation.price_b_cumulative)
+            / elapsed as u128,
+        window_seconds: elapsed,
+    })
+}
+
+#[derive(Accounts)]
+pub struct Consult<'info> {
+    #[account(
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub amm: Box<Account<'info, Amm>>,
+
+    #[account(
+        seeds = [AMM_POOL_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
+        bump,
+        has_one = mint_a,
+        has_one = mint_b,
+        has_one = amm,
+        has_one = token_program_a,
+        has_one = token_program_b,
+    )]
+    pub pool: Box<Account<'info, AmmPool>>,
+
+    #[account(
+        seeds = [OBSERVATIONS_SEED.as_bytes(), pool.key().as_ref()],
+        bump,
+        has_one = pool,
+    )]
+    pub observations: Box<Account<'info, Observations>>,
+
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK readonly
+    #[account(
+        seeds=[AMM_POOL_AUTHORITY_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub authority: AccountInfo<'info>,
+
+    #[account(
+        associated_token::mint = mint_a,
+        associated_token::token_program = token_program_a,
+        associated_token::authority = authority
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        associated_token::mint = mint_b,
+        associated_token::token_program = token_program_b,
+        associated_token::authority = authority
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+}
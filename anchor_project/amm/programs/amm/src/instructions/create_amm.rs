@@ -1,14 +1,36 @@
 use crate::errors::AmmError;
-use crate::states::{Amm, AMM_SEED, MAX_FEE_BPS};
+use crate::states::{Amm, MintPolicy, AMM_SEED, MAX_FEE_BPS, MAX_TIMELOCK_DELAY};
 use anchor_lang::prelude::*;
 
-pub fn create_amm(ctx: Context<CreateAmm>, fee: u16, index: u16) -> Result<()> {
+pub fn create_amm(
+    ctx: Context<CreateAmm>,
+    fee: u16,
+    index: u16,
+    timelock_delay: i64,
+) -> Result<()> {
     require!(fee < MAX_FEE_BPS, AmmError::InvalidFee);
+    require!(
+        (0..=MAX_TIMELOCK_DELAY).contains(&timelock_delay),
+        AmmError::InvalidTimelockDelay
+    );
 
     let amm = &mut ctx.accounts.amm;
     amm.fee = fee;
     amm.index = index;
     amm.admin = ctx.accounts.admin_account.key();
+    amm.pending_admin = None;
+    amm.paused = false;
+    amm.protocol_fee_bps = 0;
+    amm.treasury = amm.admin;
+    amm.fee_manager = amm.admin;
+    amm.pauser = amm.admin;
+    amm.pool_curator = amm.admin;
+    amm.fee_tiers = Vec::new();
+    amm.permissioned_pool_creation = false;
+    amm.mint_policy = MintPolicy::Open;
+    amm.timelock_delay = timelock_delay;
+    amm.pending_config_changes = Vec::new();
+    amm.transfer_hook_programs = Vec::new();
     Ok(())
 }
 
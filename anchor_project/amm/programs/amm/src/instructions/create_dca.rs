@@ -0,0 +1,160 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+use crate::states::{
+    Amm, AmmPool, Dca, AMM_POOL_SEED, AMM_SEED, DCA_SEED, MAX_DCA_KEEPER_FEE_BPS, MAX_FEE_BPS,
+    MAX_TWAP_WINDOW,
+};
+use crate::utils::{amount_with_transfer_fee, transfer_checked_with_hook};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct DcaParams {
+    pub is_swap_a: bool,
+    pub amount_per_period: u64,
+    pub interval: i64,
+    pub total_periods: u32,
+    pub twap_window: u32,
+    pub max_slippage_bps: u16,
+    pub keeper_fee_bps: u16,
+}
+
+/// Deposits `amount_per_period * total_periods` and stores the schedule, the first slice can run right away. A
+/// transfer fee of the input mint is paid on top so that every slice is covered, accounts of its transfer hook go into
+/// `remaining_accounts`. `max_slippage_bps` has to leave room for the pool fee and the keeper fee, which every slice
+/// pays before its price is compared to the TWAP.
+pub fn create_dca<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CreateDca<'info>>,
+    id: u64,
+    params: DcaParams,
+) -> Result<()> {
+    require!(
+        params.amount_per_period > 0
+            && params.interval > 0
+            && params.total_periods > 0
+            && params.twap_window > 0
+            && params.twap_window <= MAX_TWAP_WINDOW
+            && params.max_slippage_bps <= MAX_FEE_BPS
+            && params.keeper_fee_bps <= MAX_DCA_KEEPER_FEE_BPS,
+        AmmError::InvalidDcaParams
+    );
+    let fees_bps =
+        ctx.accounts.pool.effective_fee(&ctx.accounts.amm) as u32 + params.keeper_fee_bps as u32;
+    require!(
+        params.max_slippage_bps as u32 > fees_bps,
+        AmmError::DcaSlippageBelowFees
+    );
+
+    let deposit = params
+        .amount_per_period
+        .checked_mul(params.total_periods as u64)
+        .ok_or(AmmError::MathOverflow)?;
+    let amount_sent =
+        amount_with_transfer_fee(&ctx.accounts.input_mint.to_account_info(), deposit)?;
+    require!(
+        ctx.accounts.owner_input_account.amount >= amount_sent,
+        AmmError::InsufficientBalance
+    );
+
+    let dca = &mut ctx.accounts.dca;
+    dca.owner = ctx.accounts.owner.key();
+    dca.pool = ctx.accounts.pool.key();
+    dca.id = id;
+    dca.is_swap_a = params.is_swap_a;
+    dca.amount_per_period = params.amount_per_period;
+    dca.interval = params.interval;
+    dca.total_periods = params.total_periods;
+    dca.periods_executed = 0;
+    dca.next_execution = Clock::get()?.unix_timestamp;
+    dca.twap_window = params.twap_window;
+    dca.max_slippage_bps = params.max_slippage_bps;
+    dca.keeper_fee_bps = params.keeper_fee_bps;
+
+    let input_mint = &ctx.accounts.input_mint;
+    let cpi_accounts = TransferChecked {
+        mint: input_mint.to_account_info(),
+        from: ctx.accounts.owner_input_account.to_account_info(),
+        to: ctx.accounts.input_vault.to_account_info(),
+        authority: ctx.accounts.owner.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.input_token_program.to_account_info();
+    let cpi_context = CpiContext::new(cpi_program, cpi_accounts)
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+    transfer_checked_with_hook(cpi_context, amount_sent, input_mint.decimals)
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64, params: DcaParams)]
+pub struct CreateDca<'info> {
+    #[account(
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub amm: Box<Account<'info, Amm>>,
+
+    #[account(
+        seeds = [AMM_POOL_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
+        bump,
+        has_one = amm,
+    )]
+    pub pool: Box<Account<'info, AmmPool>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Dca::INIT_SPACE,
+        seeds = [DCA_SEED.as_bytes(), pool.key().as_ref(), owner.key().as_ref(), id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub dca: Box<Account<'info, Dca>>,
+
+    /// `pool.mint_a` when selling A, `pool.mint_b` otherwise
+    #[account(
+        constraint = input_mint.key() == if params.is_swap_a { pool.mint_a } else { pool.mint_b } @ AmmError::InvalidDcaMint,
+        mint::token_program = input_token_program,
+    )]
+    pub input_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// `pool.mint_b` when selling A, `pool.mint_a` otherwise
+    #[account(
+        constraint = output_mint.key() == if params.is_swap_a { pool.mint_b } else { pool.mint_a } @ AmmError::InvalidDcaMint,
+        mint::token_program = output_token_program,
+    )]
+    pub output_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = input_mint,
+        associated_token::token_program = input_token_program,
+        associated_token::authority = dca,
+    )]
+    pub input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Collects the proceeds until the owner withdraws them
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = output_mint,
+        associated_token::token_program = output_token_program,
+        associated_token::authority = dca,
+    )]
+    pub output_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = input_mint,
+        associated_token::token_program = input_token_program,
+        associated_token::authority = owner,
+    )]
+    pub owner_input_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub input_token_program: Interface<'info, TokenInterface>,
+    pub output_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,39 @@
+use crate::states::{Multisig, MULTISIG_SEED};
+use anchor_lang::prelude::*;
+
+pub fn create_multisig(
+    ctx: Context<CreateMultisig>,
+    create_key: Pubkey,
+    amm: Pubkey,
+    signers: Vec<Pubkey>,
+    threshold: u8,
+) -> Result<()> {
+    Multisig::validate(&signers, threshold)?;
+
+    let multisig = &mut ctx.accounts.multisig;
+    multisig.create_key = create_key;
+    multisig.amm = amm;
+    multisig.signers = signers;
+    multisig.threshold = threshold;
+    multisig.pending_signers = Vec::new();
+    multisig.pending_threshold = 0;
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(create_key: Pubkey)]
+pub struct CreateMultisig<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Multisig::INIT_SPACE,
+        seeds = [MULTISIG_SEED.as_bytes(), create_key.as_ref()],
+        bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
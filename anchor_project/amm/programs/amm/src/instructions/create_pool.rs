@@ -1,28 +1,146 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{Mint, Token, TokenAccount},
+    token::Token,
+    token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
 use crate::{
-    errors::AmmError, states::Amm, states::AmmPool, states::AMM_MINT_LIQUIDITY_SEED,
-    states::AMM_POOL_AUTHORITY_SEED, states::AMM_POOL_SEED, states::AMM_SEED,
+    auth::authorize,
+    errors::AmmError,
+    states::Amm,
+    states::AmmPool,
+    states::AMM_MINT_LIQUIDITY_SEED,
+    states::AMM_POOL_AUTHORITY_SEED,
+    states::AMM_POOL_SEED,
+    states::AMM_SEED,
+    states::DEFAULT_FEE_TIER,
+    states::MAX_FEE_BPS,
+    states::MINT_ENTRY_SEED,
+    states::OBSERVATIONS_SEED,
+    states::POOL_CREATOR_SEED,
+    states::{MintEntry, MintPolicy, Observations, PoolCreator, Twamm},
+    utils::{check_mint_extensions, transfer_hook_authority, transfer_hook_program},
 };
 
-pub fn create_pool(ctx: Context<CreatePool>) -> Result<()> {
+pub fn create_pool(ctx: Context<CreatePool>, fee_tier: u16, fee_bps: Option<u16>) -> Result<()> {
     let pool = &mut ctx.accounts.pool;
     let mint_a = ctx.accounts.mint_a.key();
     let mint_b = ctx.accounts.mint_b.key();
     require!(mint_a != mint_b, AmmError::MintAccountsAreEqual);
 
+    let amm = &ctx.accounts.amm;
+    if amm.permissioned_pool_creation && ctx.accounts.pool_creator.is_none() {
+        let pool_curator = ctx
+            .accounts
+            .pool_curator
+            .as_ref()
+            .ok_or(AmmError::PoolCreationNotAllowed)?;
+        require_keys_eq!(
+            pool_curator.key(),
+            amm.pool_curator,
+            AmmError::PoolCreationNotAllowed
+        );
+        authorize(pool_curator, ctx.remaining_accounts)?;
+    }
+    check_mint_policy(amm, &ctx.accounts.mint_a_entry)?;
+    check_mint_policy(amm, &ctx.accounts.mint_b_entry)?;
+    check_mint_extensions(&ctx.accounts.mint_a.to_account_info())?;
+    check_mint_extensions(&ctx.accounts.mint_b.to_account_info())?;
+    check_transfer_hook(amm, &ctx.accounts.mint_a.to_account_info())?;
+    check_transfer_hook(amm, &ctx.accounts.mint_b.to_account_info())?;
+
+    if let Some(fee_bps) = fee_bps {
+        let fee_manager = ctx
+            .accounts
+            .fee_manager
+            .as_ref()
+            .ok_or(AmmError::Unauthorized)?;
+        require_keys_eq!(fee_manager.key(), amm.fee_manager, AmmError::Unauthorized);
+        authorize(fee_manager, ctx.remaining_accounts)?;
+        require!(fee_bps < MAX_FEE_BPS, AmmError::InvalidFee);
+    }
+    if fee_tier != DEFAULT_FEE_TIER {
+        require!(
+            ctx.accounts.amm.fee_tiers.contains(&fee_tier),
+            AmmError::FeeTierNotFound
+        );
+    }
+
     pool.amm = ctx.accounts.amm.key();
     pool.mint_a = mint_a;
     pool.mint_b = mint_b;
+    pool.token_program_a = ctx.accounts.token_program_a.key();
+    pool.token_program_b = ctx.accounts.token_program_b.key();
+    pool.fee_tier = fee_tier;
+    pool.paused = false;
+    // pools of a registered tier charge the tier fee unless the fee manager overrides it
+    pool.fee_bps = if fee_tier == DEFAULT_FEE_TIER {
+        fee_bps
+    } else {
+        fee_bps.or(Some(fee_tier))
+    };
+    pool.protocol_fees_a = 0;
+    pool.protocol_fees_b = 0;
+    pool.flash_loan_active = false;
+    pool.pending_config_changes = Vec::new();
+    pool.price_a_cumulative = 0;
+    pool.price_b_cumulative = 0;
+    pool.last_update_timestamp = Clock::get()?.unix_timestamp;
+    pool.twamm = Twamm {
+        last_execution: pool.last_update_timestamp,
+        ..Twamm::default()
+    };
+
+    ctx.accounts.observations.pool = pool.key();
 
     Ok(())
 }
 
+fn check_mint_policy(amm: &Amm, mint_entry: &UncheckedAccount) -> Result<()> {
+    if amm.mint_policy == MintPolicy::Open {
+        return Ok(());
+    }
+
+    // the entry PDA is always passed, it only holds data if the pool curator registered the mint
+    let allowed = if mint_entry.owner == &crate::ID && !mint_entry.data_is_empty() {
+        let data = mint_entry.try_borrow_data()?;
+        Some(MintEntry::try_deserialize(&mut &data[..])?.allowed)
+    } else {
+        None
+    };
+
+    match amm.mint_policy {
+        MintPolicy::Open => Ok(()),
+        MintPolicy::Denylist => {
+            require!(allowed != Some(false), AmmError::MintNotAllowed);
+            Ok(())
+        }
+        MintPolicy::Allowlist => {
+            require!(allowed == Some(true), AmmError::MintNotAllowed);
+            Ok(())
+        }
+    }
+}
+
+/// Mints with a transfer hook run its program on every transfer out of the pool, only approved ones are accepted,
+/// and only when no authority is left to switch the mint to another program afterwards
+fn check_transfer_hook(amm: &Amm, mint: &AccountInfo) -> Result<()> {
+    if let Some(program_id) = transfer_hook_program(mint)? {
+        require!(
+            amm.transfer_hook_programs.contains(&program_id),
+            AmmError::TransferHookProgramNotApproved
+        );
+        require!(
+            transfer_hook_authority(mint)?.is_none(),
+            AmmError::TransferHookAuthoritySet
+        );
+    }
+    Ok(())
+}
+
 #[derive(Accounts)]
+#[instruction(fee_tier: u16)]
 pub struct CreatePool<'info> {
     #[account(
        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
@@ -33,7 +151,7 @@ pub struct CreatePool<'info> {
     #[account(
         init,
         payer = signer,
-        seeds = [AMM_POOL_SEED.as_bytes(), amm.key().as_ref(), mint_a.key().as_ref(), mint_b.key().as_ref()],
+        seeds = [AMM_POOL_SEED.as_bytes(), amm.key().as_ref(), mint_a.key().as_ref(), mint_b.key().as_ref(), fee_tier.to_le_bytes().as_ref()],
         bump,
         space = 8 + AmmPool::INIT_SPACE,
     )]
@@ -42,44 +160,89 @@ pub struct CreatePool<'info> {
     #[account(
         init,
         payer = signer,
-        seeds = [AMM_MINT_LIQUIDITY_SEED.as_bytes(), amm.key().as_ref(), mint_a.key().as_ref(), mint_b.key().as_ref()],
+        seeds = [OBSERVATIONS_SEED.as_bytes(), pool.key().as_ref()],
+        bump,
+        space = 8 + Observations::INIT_SPACE,
+    )]
+    pub observations: Box<Account<'info, Observations>>,
+
+    #[account(
+        init,
+        payer = signer,
+        seeds = [AMM_MINT_LIQUIDITY_SEED.as_bytes(), amm.key().as_ref(), mint_a.key().as_ref(), mint_b.key().as_ref(), fee_tier.to_le_bytes().as_ref()],
         bump,
         mint::decimals = 6,
         mint::authority = authority,
     )]
-    pub mint_liquidity: Box<Account<'info, Mint>>,
+    pub mint_liquidity: Box<InterfaceAccount<'info, Mint>>,
 
     #[account(
     init,
     payer = signer,
     associated_token::mint = mint_a,
+    associated_token::token_program = token_program_a,
     associated_token::authority = authority,
     )]
-    pub pool_account_a: Box<Account<'info, TokenAccount>>,
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
     init,
     payer = signer,
     associated_token::mint = mint_b,
+    associated_token::token_program = token_program_b,
     associated_token::authority = authority,
     )]
-    pub pool_account_b: Box<Account<'info, TokenAccount>>,
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// CHECK readonly
     #[account(
-        seeds = [AMM_POOL_AUTHORITY_SEED.as_bytes(), amm.key().as_ref(), mint_a.key().as_ref(), mint_b.key().as_ref()],
+        seeds = [AMM_POOL_AUTHORITY_SEED.as_bytes(), amm.key().as_ref(), mint_a.key().as_ref(), mint_b.key().as_ref(), fee_tier.to_le_bytes().as_ref()],
         bump,
     )]
     pub authority: AccountInfo<'info>,
 
-    pub mint_a: Box<Account<'info, Mint>>,
+    #[account(mint::token_program = token_program_a)]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
 
-    pub mint_b: Box<Account<'info, Mint>>,
+    #[account(mint::token_program = token_program_b)]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
 
     #[account(mut)]
     pub signer: Signer<'info>,
 
+    /// Allowlist entry of the signer, only needed on permissioned AMMs
+    #[account(
+        seeds = [POOL_CREATOR_SEED.as_bytes(), amm.key().as_ref(), signer.key().as_ref()],
+        bump,
+    )]
+    pub pool_creator: Option<Box<Account<'info, PoolCreator>>>,
+
+    /// CHECK: Wallet or `Multisig` matching `Amm.pool_curator`, only needed on permissioned AMMs without a
+    /// `pool_creator` entry, its approval is checked by `authorize`
+    pub pool_curator: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Wallet or `Multisig` matching `Amm.fee_manager`, only needed with `fee_bps`, its approval is checked by
+    /// `authorize`
+    pub fee_manager: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: registry entry of mint_a, may not exist
+    #[account(
+        seeds = [MINT_ENTRY_SEED.as_bytes(), amm.key().as_ref(), mint_a.key().as_ref()],
+        bump,
+    )]
+    pub mint_a_entry: UncheckedAccount<'info>,
+
+    /// CHECK: registry entry of mint_b, may not exist
+    #[account(
+        seeds = [MINT_ENTRY_SEED.as_bytes(), amm.key().as_ref(), mint_b.key().as_ref()],
+        bump,
+    )]
+    pub mint_b_entry: UncheckedAccount<'info>,
+
     pub system_program: Program<'info, System>,
+    /// Mints the LP token
     pub token_program: Program<'info, Token>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
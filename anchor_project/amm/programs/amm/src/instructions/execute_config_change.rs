@@ -0,0 +1,80 @@
+use crate::errors::AmmError;
+use crate::states::{
+    Amm, AmmPool, ConfigChange, ConfigChangeKind, Multisig, PendingConfigChange, AMM_POOL_SEED,
+    AMM_SEED, MULTISIG_SEED,
+};
+use anchor_lang::prelude::*;
+
+/// Applies the queued change of `kind` once its delay has passed, anyone can call it. Changes that target a pool are
+/// taken from the queue of `pool`.
+pub fn execute_config_change(
+    ctx: Context<ExecuteConfigChange>,
+    kind: ConfigChangeKind,
+) -> Result<()> {
+    let amm = &mut ctx.accounts.amm;
+    let queue = amm.config_change_queue(ctx.accounts.pool.as_deref_mut(), kind)?;
+    let index = PendingConfigChange::position(queue, kind)?;
+    require!(
+        Clock::get()?.unix_timestamp >= queue[index].effective_at,
+        AmmError::TimelockNotElapsed
+    );
+    let pending = queue.remove(index);
+
+    match pending.change {
+        ConfigChange::Fee { fee } => amm.fee = fee,
+        ConfigChange::ProtocolFee { protocol_fee_bps } => amm.protocol_fee_bps = protocol_fee_bps,
+        ConfigChange::PoolFee { pool, fee_bps } => {
+            let pool_account = ctx.accounts.pool.as_mut().ok_or(AmmError::PoolMismatch)?;
+            require_keys_eq!(pool_account.key(), pool, AmmError::PoolMismatch);
+            pool_account.fee_bps = fee_bps;
+        }
+        ConfigChange::Admin { new_admin } => amm.pending_admin = Some(new_admin),
+        ConfigChange::Unpause => amm.paused = false,
+        ConfigChange::UnpausePool { pool } => {
+            let pool_account = ctx.accounts.pool.as_mut().ok_or(AmmError::PoolMismatch)?;
+            require_keys_eq!(pool_account.key(), pool, AmmError::PoolMismatch);
+            pool_account.paused = false;
+        }
+        ConfigChange::TimelockDelay { timelock_delay } => amm.timelock_delay = timelock_delay,
+        ConfigChange::MultisigConfig { multisig } => {
+            let multisig_account = ctx
+                .accounts
+                .multisig
+                .as_mut()
+                .ok_or(AmmError::MultisigMismatch)?;
+            require_keys_eq!(multisig_account.key(), multisig, AmmError::MultisigMismatch);
+            multisig_account.signers = std::mem::take(&mut multisig_account.pending_signers);
+            multisig_account.threshold = multisig_account.pending_threshold;
+            multisig_account.pending_threshold = 0;
+        }
+    }
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ExecuteConfigChange<'info> {
+    #[account(
+        mut,
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    /// Only needed for changes that target a pool
+    #[account(
+        mut,
+        seeds = [AMM_POOL_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
+        bump,
+        has_one = amm,
+    )]
+    pub pool: Option<Account<'info, AmmPool>>,
+
+    /// Only needed for `MultisigConfig`
+    #[account(
+        mut,
+        seeds = [MULTISIG_SEED.as_bytes(), multisig.create_key.as_ref()],
+        bump,
+        has_one = amm,
+    )]
+    pub multisig: Option<Account<'info, Multisig>>,
+}
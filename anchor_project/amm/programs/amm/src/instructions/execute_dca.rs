@@ -0,0 +1,279 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+use crate::instructions::{quote_exact_in_with_transfer_fees, twap};
+use crate::states::{
+    Amm, AmmPool, Dca, Observations, AMM_POOL_AUTHORITY_SEED, AMM_POOL_SEED, AMM_SEED, DCA_SEED,
+    OBSERVATIONS_SEED,
+};
+use crate::twamm::execute_virtual_orders;
+use crate::utils::transfer_checked_with_hook;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
+
+/// Swaps the next slice of a DCA through its pool into the output vault. Anyone can call it once per
+/// interval and gets `keeper_fee_bps` of the slice, the rest has to come out within `max_slippage_bps` of the pool
+/// TWAP. Accounts of transfer hooks of the mints go into `remaining_accounts`.
+pub fn execute_dca<'info>(ctx: Context<'_, '_, 'info, 'info, ExecuteDca<'info>>) -> Result<()> {
+    require!(
+        !ctx.accounts.amm.paused && !ctx.accounts.pool.paused,
+        AmmError::Paused
+    );
+    require!(
+        !ctx.accounts.pool.flash_loan_active,
+        AmmError::FlashLoanActive
+    );
+    let now = Clock::get()?.unix_timestamp;
+    execute_virtual_orders(
+        &mut ctx.accounts.pool,
+        &ctx.accounts.amm,
+        ctx.accounts.pool_account_a.amount,
+        ctx.accounts.pool_account_b.amount,
+        now,
+    )?;
+
+    let dca = &ctx.accounts.dca;
+    require!(
+        dca.periods_executed < dca.total_periods,
+        AmmError::DcaCompleted
+    );
+    require!(now >= dca.next_execution, AmmError::DcaNotDue);
+
+    let is_swap_a = dca.is_swap_a;
+    let keeper_fee = (dca.amount_per_period as u128 * dca.keeper_fee_bps as u128 / 10_000) as u64;
+    let amount_in = dca.amount_per_period - keeper_fee;
+
+    let (reserve_a, reserve_b) = ctx.accounts.pool.reserves(
+        ctx.accounts.pool_account_a.amount,
+        ctx.accounts.pool_account_b.amount,
+    )?;
+    let (input_reserve, output_reserve) = if is_swap_a {
+        (reserve_a, reserve_b)
+    } else {
+        (reserve_b, reserve_a)
+    };
+    let fee_bps = ctx.accounts.pool.effective_fee(&ctx.accounts.amm);
+    let quote = quote_exact_in_with_transfer_fees(
+        &ctx.accounts.input_mint.to_account_info(),
+        &ctx.accounts.output_mint.to_account_info(),
+        input_reserve,
+        output_reserve,
+        amount_in,
+        fee_bps,
+        ctx.accounts.amm.protocol_fee_bps,
+    )?;
+
+    // the TWAP of the input token bounds the output, a price pushed within the transaction has no weight
+    let price = twap(
+        &ctx.accounts.pool,
+        &ctx.accounts.observations,
+        reserve_a,
+        reserve_b,
+        dca.twap_window,
+    )?;
+    let price_x64 = if is_swap_a {
+        price.price_a_x64
+    } else {
+        price.price_b_x64
+    };
+    let at_twap = (amount_in as u128)
+        .checked_mul(price_x64)
+        .ok_or(AmmError::MathOverflow)?
+        >> 64;
+    let min_amount_out = at_twap * (10_000 - dca.max_slippage_bps as u128) / 10_000;
+    require!(
+        quote.amount_out as u128 >= min_amount_out,
+        AmmError::OutputAmountTooLow
+    );
+
+    let pool = &mut ctx.accounts.pool;
+    pool.update_price_accumulators(reserve_a, reserve_b, now);
+    ctx.accounts.observations.record(pool);
+    pool.add_protocol_fee(is_swap_a, quote.protocol_fee)?;
+
+    let dca = &mut ctx.accounts.dca;
+    dca.periods_executed += 1;
+    dca.next_execution = now
+        .checked_add(dca.interval)
+        .ok_or(AmmError::MathOverflow)?;
+
+    let dca = &ctx.accounts.dca;
+    let dca_signer_seeds: &[&[&[u8]]] = &[&[
+        DCA_SEED.as_bytes(),
+        dca.pool.as_ref(),
+        dca.owner.as_ref(),
+        &dca.id.to_le_bytes(),
+        &[ctx.bumps.dca],
+    ]];
+    let authority_signer_seeds: &[&[&[u8]]] = &[&[
+        AMM_POOL_AUTHORITY_SEED.as_bytes(),
+        &ctx.accounts.pool.amm.to_bytes(),
+        &ctx.accounts.mint_a.key().to_bytes(),
+        &ctx.accounts.mint_b.key().to_bytes(),
+        &ctx.accounts.pool.fee_tier.to_le_bytes(),
+        &[ctx.bumps.authority],
+    ]];
+    let (input_pool, output_pool) = if is_swap_a {
+        (&ctx.accounts.pool_account_a, &ctx.accounts.pool_account_b)
+    } else {
+        (&ctx.accounts.pool_account_b, &ctx.accounts.pool_account_a)
+    };
+    let input_mint = &ctx.accounts.input_mint;
+    let output_mint = &ctx.accounts.output_mint;
+
+    let cpi_accounts = TransferChecked {
+        mint: input_mint.to_account_info(),
+        from: ctx.accounts.input_vault.to_account_info(),
+        to: input_pool.to_account_info(),
+        authority: dca.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.input_token_program.to_account_info();
+    let cpi_context = CpiContext::new(cpi_program, cpi_accounts)
+        .with_signer(dca_signer_seeds)
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+    transfer_checked_with_hook(cpi_context, amount_in, input_mint.decimals)?;
+
+    // pay the keeper
+    if keeper_fee > 0 {
+        let cpi_accounts = TransferChecked {
+            mint: input_mint.to_account_info(),
+            from: ctx.accounts.input_vault.to_account_info(),
+            to: ctx.accounts.keeper_account.to_account_info(),
+            authority: dca.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.input_token_program.to_account_info();
+        let cpi_context = CpiContext::new(cpi_program, cpi_accounts)
+            .with_signer(dca_signer_seeds)
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        transfer_checked_with_hook(cpi_context, keeper_fee, input_mint.decimals)?;
+    }
+
+    let cpi_accounts = TransferChecked {
+        mint: output_mint.to_account_info(),
+        from: output_pool.to_account_info(),
+        to: ctx.accounts.output_vault.to_account_info(),
+        authority: ctx.accounts.authority.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.output_token_program.to_account_info();
+    let cpi_context = CpiContext::new(cpi_program, cpi_accounts)
+        .with_signer(authority_signer_seeds)
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+    transfer_checked_with_hook(
+        cpi_context,
+        quote.amount_out + quote.transfer_fee_out,
+        output_mint.decimals,
+    )
+}
+
+#[derive(Accounts)]
+pub struct ExecuteDca<'info> {
+    #[account(
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub amm: Box<Account<'info, Amm>>,
+
+    #[account(
+        mut,
+        seeds = [AMM_POOL_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
+        bump,
+        has_one = mint_a,
+        has_one = mint_b,
+        has_one = amm,
+        has_one = token_program_a,
+        has_one = token_program_b,
+    )]
+    pub pool: Box<Account<'info, AmmPool>>,
+
+    #[account(
+        mut,
+        seeds = [OBSERVATIONS_SEED.as_bytes(), pool.key().as_ref()],
+        bump,
+        has_one = pool,
+    )]
+    pub observations: Box<Account<'info, Observations>>,
+
+    /// CHECK: readonly
+    #[account(
+        seeds=[AMM_POOL_AUTHORITY_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub authority: AccountInfo<'info>,
+
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::token_program = token_program_a,
+        associated_token::authority = authority,
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::token_program = token_program_b,
+        associated_token::authority = authority,
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [DCA_SEED.as_bytes(), dca.pool.as_ref(), dca.owner.as_ref(), dca.id.to_le_bytes().as_ref()],
+        bump,
+        has_one = pool,
+    )]
+    pub dca: Box<Account<'info, Dca>>,
+
+    /// `mint_a` when the DCA sells A, `mint_b` otherwise
+    #[account(
+        constraint = input_mint.key() == if dca.is_swap_a { mint_a.key() } else { mint_b.key() } @ AmmError::InvalidDcaMint,
+        mint::token_program = input_token_program,
+    )]
+    pub input_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// `mint_b` when the DCA sells A, `mint_a` otherwise
+    #[account(
+        constraint = output_mint.key() == if dca.is_swap_a { mint_b.key() } else { mint_a.key() } @ AmmError::InvalidDcaMint,
+        mint::token_program = output_token_program,
+    )]
+    pub output_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = input_mint,
+        associated_token::token_program = input_token_program,
+        associated_token::authority = dca,
+    )]
+    pub input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = output_mint,
+        associated_token::token_program = output_token_program,
+        associated_token::authority = dca,
+    )]
+    pub output_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = keeper,
+        associated_token::mint = input_mint,
+        associated_token::token_program = input_token_program,
+        associated_token::authority = keeper,
+    )]
+    pub keeper_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub input_token_program: Interface<'info, TokenInterface>,
+    pub output_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,218 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT};
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked, ID as INSTRUCTIONS_SYSVAR_ID,
+};
+use anchor_lang::Discriminator;
+
+use crate::errors::AmmError;
+use crate::states::{Amm, AmmPool, AMM_POOL_AUTHORITY_SEED, AMM_POOL_SEED, AMM_SEED};
+use crate::twamm::execute_virtual_orders;
+use crate::utils::transfer_checked_with_hook;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
+
+/// Position of `pool` in the accounts of `flash_repay`
+const FLASH_REPAY_POOL_INDEX: usize = 1;
+
+/// Lends `amount` of token A or B out of the pool. A `flash_repay` for the same pool, side and amount
+/// has to follow later in the transaction, the pool is locked until it runs. Accounts of a transfer hook of the mint
+/// go into `remaining_accounts`.
+pub fn flash_borrow<'info>(
+    ctx: Context<'_, '_, 'info, 'info, FlashLoan<'info>>,
+    is_a: bool,
+    amount: u64,
+) -> Result<()> {
+    check_top_level()?;
+    require!(amount > 0, AmmError::AmountIsZero);
+    require!(
+        !ctx.accounts.amm.paused && !ctx.accounts.pool.paused,
+        AmmError::Paused
+    );
+    require!(
+        !ctx.accounts.pool.flash_loan_active,
+        AmmError::FlashLoanActive
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    execute_virtual_orders(
+        &mut ctx.accounts.pool,
+        &ctx.accounts.amm,
+        ctx.accounts.pool_account_a.amount,
+        ctx.accounts.pool_account_b.amount,
+        now,
+    )?;
+    let (reserve_a, reserve_b) = ctx.accounts.pool.reserves(
+        ctx.accounts.pool_account_a.amount,
+        ctx.accounts.pool_account_b.amount,
+    )?;
+    let reserve = if is_a { reserve_a } else { reserve_b };
+    require!(amount < reserve, AmmError::InsufficientLiquidity);
+
+    ctx.accounts.check_repay_follows(is_a, amount)?;
+    ctx.accounts
+        .pool
+        .update_price_accumulators(reserve_a, reserve_b, now);
+    ctx.accounts.pool.flash_loan_active = true;
+
+    let authority_signer_seeds: &[&[&[u8]]] = &[&[
+        AMM_POOL_AUTHORITY_SEED.as_bytes(),
+        &ctx.accounts.pool.amm.to_bytes(),
+        &ctx.accounts.mint_a.key().to_bytes(),
+        &ctx.accounts.mint_b.key().to_bytes(),
+        &ctx.accounts.pool.fee_tier.to_le_bytes(),
+        &[ctx.bumps.authority],
+    ]];
+
+    let (mint, pool_account, token_program) = ctx.accounts.side(is_a);
+    let cpi_accounts = TransferChecked {
+        mint: mint.to_account_info(),
+        from: pool_account.to_account_info(),
+        to: ctx.accounts.borrower_account.to_account_info(),
+        authority: ctx.accounts.authority.to_account_info(),
+    };
+    let cpi_program = token_program.to_account_info();
+    let cpi_context = CpiContext::new(cpi_program, cpi_accounts)
+        .with_signer(authority_signer_seeds)
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+    transfer_checked_with_hook(cpi_context, amount, mint.decimals)
+}
+
+/// Fails when called through CPI, a program could otherwise repay and borrow again before the checked repay
+pub(crate) fn check_top_level() -> Result<()> {
+    require!(
+        get_stack_height() == TRANSACTION_LEVEL_STACK_HEIGHT,
+        AmmError::FlashLoanNotTopLevel
+    );
+    Ok(())
+}
+
+/// Returns the flash loan fee on `amount`, rounded up against the borrower
+pub(crate) fn flash_loan_fee(amount: u64, fee_bps: u16) -> Result<u64> {
+    let fee = (amount as u128)
+        .checked_mul(fee_bps as u128)
+        .ok_or(AmmError::MathOverflow)?
+        .div_ceil(10_000);
+    Ok(fee as u64)
+}
+
+impl<'info> FlashLoan<'info> {
+    /// Returns the mint, the pool account and the token program of the borrowed token
+    pub(crate) fn side(
+        &self,
+        is_a: bool,
+    ) -> (
+        &InterfaceAccount<'info, Mint>,
+        &InterfaceAccount<'info, TokenAccount>,
+        &Interface<'info, TokenInterface>,
+    ) {
+        if is_a {
+            (&self.mint_a, &self.pool_account_a, &self.token_program_a)
+        } else {
+            (&self.mint_b, &self.pool_account_b, &self.token_program_b)
+        }
+    }
+
+    /// Looks through the rest of the transaction for the `flash_repay` of this pool
+    fn check_repay_follows(&self, is_a: bool, amount: u64) -> Result<()> {
+        let instructions = &self.instructions.to_account_info();
+        let current_index = load_current_index_checked(instructions)? as usize;
+
+        let mut index = current_index + 1;
+        while let Ok(ix) = load_instruction_at_checked(index, instructions) {
+            index += 1;
+            if ix.program_id != crate::ID
+                || !ix
+                    .data
+                    .starts_with(crate::instruction::FlashRepay::DISCRIMINATOR)
+            {
+                continue;
+            }
+            let repays_pool = ix
+                .accounts
+                .get(FLASH_REPAY_POOL_INDEX)
+                .is_some_and(|meta| meta.pubkey == self.pool.key());
+            if !repays_pool {
+                continue;
+            }
+
+            // the first repay of this pool has to be the one for this loan
+            let args = crate::instruction::FlashRepay::try_from_slice(
+                &ix.data[crate::instruction::FlashRepay::DISCRIMINATOR.len()..],
+            )?;
+            require!(
+                args.is_a == is_a && args.amount == amount,
+                AmmError::FlashRepayMissing
+            );
+            return Ok(());
+        }
+
+        err!(AmmError::FlashRepayMissing)
+    }
+}
+
+/// Accounts of `flash_borrow` and `flash_repay`, `pool` has to stay second
+#[derive(Accounts)]
+#[instruction(is_a: bool)]
+pub struct FlashLoan<'info> {
+    #[account(
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub amm: Box<Account<'info, Amm>>,
+
+    #[account(
+        mut,
+        seeds = [AMM_POOL_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
+        bump,
+        has_one = mint_a,
+        has_one = mint_b,
+        has_one = amm,
+        has_one = token_program_a,
+        has_one = token_program_b,
+    )]
+    pub pool: Box<Account<'info, AmmPool>>,
+
+    /// CHECK: readonly
+    #[account(
+        seeds=[AMM_POOL_AUTHORITY_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub authority: AccountInfo<'info>,
+
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::token_program = token_program_a,
+        associated_token::authority = authority,
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::token_program = token_program_b,
+        associated_token::authority = authority,
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub borrower: Signer<'info>,
+
+    /// Receives the loan and pays it back, holds `mint_a` when `is_a`, `mint_b` otherwise
+    #[account(
+        mut,
+        token::authority = borrower,
+        constraint = borrower_account.mint == if is_a { mint_a.key() } else { mint_b.key() } @ AmmError::InvalidFlashLoanAccount,
+    )]
+    pub borrower_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: instructions sysvar, read by `flash_borrow` to find the repay
+    #[account(address = INSTRUCTIONS_SYSVAR_ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+}
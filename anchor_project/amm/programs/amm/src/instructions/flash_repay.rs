@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+use crate::instructions::flash_borrow::{check_top_level, flash_loan_fee};
+use crate::instructions::FlashLoan;
+use crate::utils::{amount_with_transfer_fee, transfer_checked_with_hook};
+use anchor_spl::token_interface::TransferChecked;
+
+/// Pays back a flash loan with the pool fee on top and unlocks the pool, the fee stays with the LPs. A transfer
+/// fee of the mint is paid on top as well, the whole loan and fee have to arrive in the pool. Accounts of a transfer
+/// hook of the mint go into `remaining_accounts`.
+pub fn flash_repay<'info>(
+    ctx: Context<'_, '_, 'info, 'info, FlashLoan<'info>>,
+    is_a: bool,
+    amount: u64,
+) -> Result<()> {
+    check_top_level()?;
+    require!(ctx.accounts.pool.flash_loan_active, AmmError::NoFlashLoan);
+
+    let fee_bps = ctx.accounts.pool.effective_fee(&ctx.accounts.amm);
+    let (mint, pool_account, token_program) = ctx.accounts.side(is_a);
+    let repay_amount = amount
+        .checked_add(flash_loan_fee(amount, fee_bps)?)
+        .ok_or(AmmError::MathOverflow)?;
+    let repay_amount = amount_with_transfer_fee(&mint.to_account_info(), repay_amount)?;
+    require!(
+        ctx.accounts.borrower_account.amount >= repay_amount,
+        AmmError::InsufficientBalance
+    );
+
+    let cpi_accounts = TransferChecked {
+        mint: mint.to_account_info(),
+        from: ctx.accounts.borrower_account.to_account_info(),
+        to: pool_account.to_account_info(),
+        authority: ctx.accounts.borrower.to_account_info(),
+    };
+    let cpi_program = token_program.to_account_info();
+    let cpi_context = CpiContext::new(cpi_program, cpi_accounts)
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+    transfer_checked_with_hook(cpi_context, repay_amount, mint.decimals)?;
+
+    ctx.accounts.pool.flash_loan_active = false;
+    Ok(())
+}
@@ -0,0 +1,250 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke;
+
+use crate::errors::AmmError;
+use crate::states::{
+    Amm, AmmPool, Observations, AMM_POOL_AUTHORITY_SEED, AMM_POOL_SEED, AMM_SEED, OBSERVATIONS_SEED,
+};
+use crate::twamm::execute_virtual_orders;
+use crate::utils::transfer_checked_with_hook;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
+
+/// Anchor discriminator of `flash_swap_callback`, the first 8 bytes of `sha256("global:flash_swap_callback")`
+const FLASH_SWAP_CALLBACK_DISCRIMINATOR: [u8; 8] = [225, 54, 80, 1, 45, 208, 202, 124];
+
+/// Arguments of the callback, an Anchor program receives them as
+/// `flash_swap_callback(amount_a_out: u64, amount_b_out: u64, data: Vec<u8>)`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct FlashSwapCallback {
+    pub amount_a_out: u64,
+    pub amount_b_out: u64,
+    pub data: Vec<u8>,
+}
+
+/// Sends the output first, calls `callback_program` with `remaining_accounts`, then checks that what came
+/// back into the pool pays the swap fee and keeps x * y = k. Accounts of transfer hooks of the mints go into
+/// `remaining_accounts` as well, the outgoing transfers look them up there.
+/// The callback cannot reenter the AMM, the runtime only allows direct self recursion.
+pub fn flash_swap<'info>(
+    ctx: Context<'_, '_, 'info, 'info, FlashSwap<'info>>,
+    amount_a_out: u64,
+    amount_b_out: u64,
+    data: Vec<u8>,
+) -> Result<()> {
+    require!(amount_a_out > 0 || amount_b_out > 0, AmmError::AmountIsZero);
+    require!(
+        !ctx.accounts.amm.paused && !ctx.accounts.pool.paused,
+        AmmError::Paused
+    );
+    require!(
+        !ctx.accounts.pool.flash_loan_active,
+        AmmError::FlashLoanActive
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    execute_virtual_orders(
+        &mut ctx.accounts.pool,
+        &ctx.accounts.amm,
+        ctx.accounts.pool_account_a.amount,
+        ctx.accounts.pool_account_b.amount,
+        now,
+    )?;
+    let (reserve_a, reserve_b) = ctx.accounts.pool.reserves(
+        ctx.accounts.pool_account_a.amount,
+        ctx.accounts.pool_account_b.amount,
+    )?;
+    require!(reserve_a > 0 && reserve_b > 0, AmmError::EmptyPool);
+    ctx.accounts
+        .pool
+        .update_price_accumulators(reserve_a, reserve_b, now);
+    ctx.accounts.observations.record(&ctx.accounts.pool);
+    require!(
+        amount_a_out < reserve_a && amount_b_out < reserve_b,
+        AmmError::InsufficientLiquidity
+    );
+
+    // send the output before anything is paid
+    let authority_signer_seeds: &[&[&[u8]]] = &[&[
+        AMM_POOL_AUTHORITY_SEED.as_bytes(),
+        &ctx.accounts.pool.amm.to_bytes(),
+        &ctx.accounts.mint_a.key().to_bytes(),
+        &ctx.accounts.mint_b.key().to_bytes(),
+        &ctx.accounts.pool.fee_tier.to_le_bytes(),
+        &[ctx.bumps.authority],
+    ]];
+    if amount_a_out > 0 {
+        let mint_a = &ctx.accounts.mint_a;
+        let cpi_accounts = TransferChecked {
+            mint: mint_a.to_account_info(),
+            from: ctx.accounts.pool_account_a.to_account_info(),
+            to: ctx.accounts.recipient_account_a.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program_a.to_account_info();
+        let cpi_context = CpiContext::new(cpi_program, cpi_accounts)
+            .with_signer(authority_signer_seeds)
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        transfer_checked_with_hook(cpi_context, amount_a_out, mint_a.decimals)?;
+    }
+
+    if amount_b_out > 0 {
+        let mint_b = &ctx.accounts.mint_b;
+        let cpi_accounts = TransferChecked {
+            mint: mint_b.to_account_info(),
+            from: ctx.accounts.pool_account_b.to_account_info(),
+            to: ctx.accounts.recipient_account_b.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program_b.to_account_info();
+        let cpi_context = CpiContext::new(cpi_program, cpi_accounts)
+            .with_signer(authority_signer_seeds)
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        transfer_checked_with_hook(cpi_context, amount_b_out, mint_b.decimals)?;
+    }
+
+    // hand over to the borrower, who has to pay the pool back
+    let callback = FlashSwapCallback {
+        amount_a_out,
+        amount_b_out,
+        data,
+    };
+    let mut callback_data = FLASH_SWAP_CALLBACK_DISCRIMINATOR.to_vec();
+    callback.serialize(&mut callback_data)?;
+
+    let callback_ix = Instruction {
+        program_id: ctx.accounts.callback_program.key(),
+        accounts: ctx
+            .remaining_accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: account.key(),
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+        data: callback_data,
+    };
+    let mut callback_accounts = ctx.remaining_accounts.to_vec();
+    callback_accounts.push(ctx.accounts.callback_program.to_account_info());
+    invoke(&callback_ix, &callback_accounts)?;
+
+    ctx.accounts.pool_account_a.reload()?;
+    ctx.accounts.pool_account_b.reload()?;
+    let (balance_a, balance_b) = ctx.accounts.pool.reserves(
+        ctx.accounts.pool_account_a.amount,
+        ctx.accounts.pool_account_b.amount,
+    )?;
+
+    let fee_bps = ctx.accounts.pool.effective_fee(&ctx.accounts.amm);
+    let fee_a = amount_in_fee(reserve_a, amount_a_out, balance_a, fee_bps)?;
+    let fee_b = amount_in_fee(reserve_b, amount_b_out, balance_b, fee_bps)?;
+
+    // the balances less the fee on what came in must keep k
+    let adjusted_a = (balance_a - fee_a) as u128;
+    let adjusted_b = (balance_b - fee_b) as u128;
+    let k = (reserve_a as u128)
+        .checked_mul(reserve_b as u128)
+        .ok_or(AmmError::MathOverflow)?;
+    require!(
+        adjusted_a
+            .checked_mul(adjusted_b)
+            .ok_or(AmmError::MathOverflow)?
+            >= k,
+        AmmError::InvariantViolated
+    );
+
+    let protocol_fee_bps = ctx.accounts.amm.protocol_fee_bps as u128;
+    let pool = &mut ctx.accounts.pool;
+    pool.add_protocol_fee(true, (fee_a as u128 * protocol_fee_bps / 10_000) as u64)?;
+    pool.add_protocol_fee(false, (fee_b as u128 * protocol_fee_bps / 10_000) as u64)?;
+
+    Ok(())
+}
+
+/// Returns the swap fee on what came into one side of the pool, rounded up against the borrower
+fn amount_in_fee(reserve: u64, amount_out: u64, balance: u64, fee_bps: u16) -> Result<u64> {
+    let amount_in = balance.saturating_sub(reserve - amount_out);
+    let fee = (amount_in as u128)
+        .checked_mul(fee_bps as u128)
+        .ok_or(AmmError::MathOverflow)?
+        .div_ceil(10_000);
+    Ok(fee as u64)
+}
+
+#[derive(Accounts)]
+pub struct FlashSwap<'info> {
+    #[account(
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub amm: Box<Account<'info, Amm>>,
+
+    #[account(
+        mut,
+        seeds = [AMM_POOL_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
+        bump,
+        has_one = mint_a,
+        has_one = mint_b,
+        has_one = amm,
+        has_one = token_program_a,
+        has_one = token_program_b,
+    )]
+    pub pool: Box<Account<'info, AmmPool>>,
+
+    #[account(
+        mut,
+        seeds = [OBSERVATIONS_SEED.as_bytes(), pool.key().as_ref()],
+        bump,
+        has_one = pool,
+    )]
+    pub observations: Box<Account<'info, Observations>>,
+
+    /// CHECK: readonly
+    #[account(
+        seeds=[AMM_POOL_AUTHORITY_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub authority: AccountInfo<'info>,
+
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::token_program = token_program_a,
+        associated_token::authority = authority,
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::token_program = token_program_b,
+        associated_token::authority = authority,
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub borrower: Signer<'info>,
+
+    #[account(
+        mut,
+        token::mint = mint_a,
+    )]
+    pub recipient_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = mint_b,
+    )]
+    pub recipient_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: any program, it only receives `remaining_accounts` and cannot move pool funds
+    #[account(executable)]
+    pub callback_program: UncheckedAccount<'info>,
+
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+}
@@ -1,8 +1,53 @@
+mod accept_admin;
+mod add_fee_tier;
 mod add_liquidity;
+mod add_pool_creator;
+mod add_transfer_hook_program;
+mod cancel_admin_transfer;
+mod cancel_config_change;
+mod cancel_long_term_order;
+mod cancel_order;
+mod close_dca;
+mod collect_protocol_fees;
+mod consult;
 mod create_amm;
+mod create_dca;
+mod create_multisig;
 mod create_pool;
+mod execute_config_change;
+mod execute_dca;
+mod fill_order;
+mod flash_borrow;
+mod flash_repay;
+mod flash_swap;
+mod pause;
+mod pause_pool;
+mod place_long_term_order;
+mod place_order;
+mod preview_add_liquidity;
+mod preview_withdraw;
+mod propose_admin;
+mod prune_twamm_expiry;
+mod quote_swap;
+mod quote_swap_exact_out;
+mod remove_fee_tier;
+mod remove_mint_entry;
+mod remove_pool_creator;
+mod remove_transfer_hook_program;
+mod set_mint_entry;
+mod set_mint_policy;
+mod set_multisig_config;
+mod set_permissioned_pool_creation;
+mod set_pool_fee;
+mod set_protocol_fee;
+mod set_role;
 mod swap;
+mod swap_exact_out;
+mod swap_route;
+mod update_amm_config;
+mod withdraw_dca;
 mod withdraw_liquidity;
+mod withdraw_long_term_order;
 
 pub use create_amm::*;
 
@@ -13,3 +58,93 @@ pub use add_liquidity::*;
 pub use swap::*;
 
 pub use withdraw_liquidity::*;
+
+pub use update_amm_config::*;
+
+pub use propose_admin::*;
+
+pub use accept_admin::*;
+
+pub use cancel_admin_transfer::*;
+
+pub use pause::*;
+
+pub use pause_pool::*;
+
+pub use set_pool_fee::*;
+
+pub use set_role::*;
+
+pub use set_protocol_fee::*;
+
+pub use collect_protocol_fees::*;
+
+pub use add_fee_tier::*;
+
+pub use remove_fee_tier::*;
+
+pub use set_permissioned_pool_creation::*;
+
+pub use add_pool_creator::*;
+
+pub use remove_pool_creator::*;
+
+pub use set_mint_policy::*;
+
+pub use set_mint_entry::*;
+
+pub use remove_mint_entry::*;
+
+pub use add_transfer_hook_program::*;
+
+pub use remove_transfer_hook_program::*;
+
+pub use execute_config_change::*;
+
+pub use cancel_config_change::*;
+
+pub use create_multisig::*;
+
+pub use set_multisig_config::*;
+
+pub use swap_exact_out::*;
+
+pub use swap_route::*;
+
+pub use quote_swap::*;
+
+pub use quote_swap_exact_out::*;
+
+pub use preview_add_liquidity::*;
+
+pub use preview_withdraw::*;
+
+pub use flash_swap::*;
+
+pub use flash_borrow::*;
+
+pub use flash_repay::*;
+
+pub use consult::*;
+
+pub use place_order::*;
+
+pub use fill_order::*;
+
+pub use cancel_order::*;
+
+pub use create_dca::*;
+
+pub use execute_dca::*;
+
+pub use withdraw_dca::*;
+
+pub use close_dca::*;
+
+pub use place_long_term_order::*;
+
+pub use withdraw_long_term_order::*;
+
+pub use cancel_long_term_order::*;
+
+pub use prune_twamm_expiry::*;
@@ -0,0 +1,27 @@
+use crate::auth::authorize;
+use crate::errors::AmmError;
+use crate::states::{Amm, AMM_SEED};
+use anchor_lang::prelude::*;
+
+/// Pauses the AMM right away, lifting the pause goes through `update_amm_config`
+pub fn pause(ctx: Context<Pause>) -> Result<()> {
+    authorize(&ctx.accounts.pauser, ctx.remaining_accounts)?;
+
+    let amm = &mut ctx.accounts.amm;
+    amm.paused = true;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Pause<'info> {
+    #[account(
+        mut,
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+        has_one = pauser @ AmmError::Unauthorized,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    /// CHECK: Wallet or `Multisig` matching `Amm.pauser`, its approval is checked by `authorize`
+    pub pauser: UncheckedAccount<'info>,
+}
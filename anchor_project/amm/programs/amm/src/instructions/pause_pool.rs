@@ -0,0 +1,34 @@
+use crate::auth::authorize;
+use crate::errors::AmmError;
+use crate::states::{Amm, AmmPool, AMM_POOL_SEED, AMM_SEED};
+use anchor_lang::prelude::*;
+
+/// Pauses the pool right away, lifting the pause goes through `update_amm_config`
+pub fn pause_pool(ctx: Context<PausePool>) -> Result<()> {
+    authorize(&ctx.accounts.pauser, ctx.remaining_accounts)?;
+
+    let pool = &mut ctx.accounts.pool;
+    pool.paused = true;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct PausePool<'info> {
+    #[account(
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+        has_one = pauser @ AmmError::Unauthorized,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    #[account(
+        mut,
+        seeds = [AMM_POOL_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
+        bump,
+        has_one = amm,
+    )]
+    pub pool: Account<'info, AmmPool>,
+
+    /// CHECK: Wallet or `Multisig` matching `Amm.pauser`, its approval is checked by `authorize`
+    pub pauser: UncheckedAccount<'info>,
+}
@@ -0,0 +1,185 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+use crate::states::{
+    Amm, AmmPool, LongTermOrder, AMM_POOL_AUTHORITY_SEED, AMM_POOL_SEED, AMM_SEED,
+    LONG_TERM_ORDER_SEED, MAX_TWAMM_INTERVALS, MIN_TWAMM_ORDER_BPS, TWAMM_INTERVAL,
+};
+use crate::twamm::execute_virtual_orders;
+use crate::utils::{amount_with_transfer_fee, transfer_checked_with_hook};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
+
+/// Sells `amount` of token A, or of token B, evenly over every second until the end of the `intervals`th
+/// `TWAMM_INTERVAL` from now. The deposit is rounded down to a whole sell rate, a transfer fee of the input mint
+/// is paid on top so that the whole deposit arrives in the pool. The order has to sell `MIN_TWAMM_ORDER_BPS` of the
+/// input reserve per interval, so dust orders cannot take the expiry slots of the pool. Accounts of a transfer hook
+/// of the input mint go into `remaining_accounts`.
+pub fn place_long_term_order<'info>(
+    ctx: Context<'_, '_, 'info, 'info, PlaceLongTermOrder<'info>>,
+    id: u64,
+    is_sell_a: bool,
+    amount: u64,
+    intervals: u32,
+) -> Result<()> {
+    require!(amount > 0, AmmError::AmountIsZero);
+    require!(
+        intervals <= MAX_TWAMM_INTERVALS,
+        AmmError::InvalidLongTermOrder
+    );
+    require!(
+        !ctx.accounts.amm.paused && !ctx.accounts.pool.paused,
+        AmmError::Paused
+    );
+    require!(
+        !ctx.accounts.pool.flash_loan_active,
+        AmmError::FlashLoanActive
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    execute_virtual_orders(
+        &mut ctx.accounts.pool,
+        &ctx.accounts.amm,
+        ctx.accounts.pool_account_a.amount,
+        ctx.accounts.pool_account_b.amount,
+        now,
+    )?;
+    let (reserve_a, reserve_b) = ctx.accounts.pool.reserves(
+        ctx.accounts.pool_account_a.amount,
+        ctx.accounts.pool_account_b.amount,
+    )?;
+    require!(reserve_a > 0 && reserve_b > 0, AmmError::EmptyPool);
+
+    // expiries sit on interval boundaries so that orders share them
+    let expiry = (now / TWAMM_INTERVAL)
+        .checked_add(intervals as i64)
+        .and_then(|boundary| boundary.checked_mul(TWAMM_INTERVAL))
+        .filter(|&expiry| expiry > now)
+        .ok_or(AmmError::InvalidLongTermOrder)?;
+    let duration = (expiry - now) as u64;
+    let sell_rate = amount / duration;
+    let reserve_in = if is_sell_a { reserve_a } else { reserve_b };
+    require!(
+        sell_rate > 0
+            && sell_rate as u128 * TWAMM_INTERVAL as u128 * 10_000
+                >= reserve_in as u128 * MIN_TWAMM_ORDER_BPS as u128,
+        AmmError::InvalidLongTermOrder
+    );
+    let deposit = sell_rate * duration;
+
+    let (input_mint, input_pool, token_program) = if is_sell_a {
+        (
+            &ctx.accounts.mint_a,
+            &ctx.accounts.pool_account_a,
+            &ctx.accounts.token_program_a,
+        )
+    } else {
+        (
+            &ctx.accounts.mint_b,
+            &ctx.accounts.pool_account_b,
+            &ctx.accounts.token_program_b,
+        )
+    };
+    let amount_sent = amount_with_transfer_fee(&input_mint.to_account_info(), deposit)?;
+    require!(
+        ctx.accounts.owner_input_account.amount >= amount_sent,
+        AmmError::InsufficientBalance
+    );
+    let cpi_accounts = TransferChecked {
+        mint: input_mint.to_account_info(),
+        from: ctx.accounts.owner_input_account.to_account_info(),
+        to: input_pool.to_account_info(),
+        authority: ctx.accounts.owner.to_account_info(),
+    };
+    let cpi_context = CpiContext::new(token_program.to_account_info(), cpi_accounts)
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+    transfer_checked_with_hook(cpi_context, amount_sent, input_mint.decimals)?;
+
+    let twamm = &mut ctx.accounts.pool.twamm;
+    twamm.open_order(is_sell_a, sell_rate, expiry)?;
+    let balance = twamm.balance(is_sell_a);
+    *balance = balance.checked_add(deposit).ok_or(AmmError::MathOverflow)?;
+
+    let order = &mut ctx.accounts.order;
+    order.owner = ctx.accounts.owner.key();
+    order.pool = ctx.accounts.pool.key();
+    order.id = id;
+    order.is_sell_a = is_sell_a;
+    order.sell_rate = sell_rate;
+    order.expiry = expiry;
+    order.earnings_per_rate_x64 = ctx.accounts.pool.twamm.earnings_per_rate(is_sell_a);
+    order.idle_seconds = ctx.accounts.pool.twamm.idle_seconds;
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64, is_sell_a: bool)]
+pub struct PlaceLongTermOrder<'info> {
+    #[account(
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub amm: Box<Account<'info, Amm>>,
+
+    #[account(
+        mut,
+        seeds = [AMM_POOL_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
+        bump,
+        has_one = mint_a,
+        has_one = mint_b,
+        has_one = amm,
+        has_one = token_program_a,
+        has_one = token_program_b,
+    )]
+    pub pool: Box<Account<'info, AmmPool>>,
+
+    /// CHECK: readonly
+    #[account(
+        seeds=[AMM_POOL_AUTHORITY_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub authority: AccountInfo<'info>,
+
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::token_program = token_program_a,
+        associated_token::authority = authority,
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::token_program = token_program_b,
+        associated_token::authority = authority,
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + LongTermOrder::INIT_SPACE,
+        seeds = [LONG_TERM_ORDER_SEED.as_bytes(), pool.key().as_ref(), owner.key().as_ref(), id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub order: Box<Account<'info, LongTermOrder>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Holds `pool.mint_a` when selling A, `pool.mint_b` otherwise
+    #[account(
+        mut,
+        token::authority = owner,
+        constraint = owner_input_account.mint == if is_sell_a { pool.mint_a } else { pool.mint_b } @ AmmError::InvalidOrderMint,
+    )]
+    pub owner_input_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,135 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+use crate::states::{AmmPool, Order, OrderKind, AMM_POOL_SEED, MAX_TWAP_WINDOW, ORDER_SEED};
+use crate::utils::{amount_with_transfer_fee, transfer_checked_with_hook};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct OrderParams {
+    pub kind: OrderKind,
+    pub is_swap_a: bool,
+    pub amount: u64,
+    /// Q64.64 price of the input token in the output token
+    pub trigger_price_x64: u128,
+    /// Minimum seconds of the pool TWAP the trigger price is compared with
+    pub twap_window: u32,
+    pub min_amount_out: u64,
+    pub keeper_bounty: u64,
+}
+
+/// Escrows `amount` plus the keeper bounty and stores the order, `fill_order` executes it later. A transfer fee
+/// of the input mint is paid on top so that the whole escrow arrives, accounts of its transfer hook go into
+/// `remaining_accounts`.
+pub fn place_order<'info>(
+    ctx: Context<'_, '_, 'info, 'info, PlaceOrder<'info>>,
+    id: u64,
+    params: OrderParams,
+) -> Result<()> {
+    require!(params.amount > 0, AmmError::AmountIsZero);
+    require!(
+        params.trigger_price_x64 > 0
+            && params.twap_window > 0
+            && params.twap_window <= MAX_TWAP_WINDOW,
+        AmmError::InvalidOrderPrice
+    );
+
+    let escrow_amount = params
+        .amount
+        .checked_add(params.keeper_bounty)
+        .ok_or(AmmError::MathOverflow)?;
+    let amount_sent =
+        amount_with_transfer_fee(&ctx.accounts.input_mint.to_account_info(), escrow_amount)?;
+    require!(
+        ctx.accounts.owner_input_account.amount >= amount_sent,
+        AmmError::InsufficientBalance
+    );
+
+    // a limit order never fills below its price
+    let min_amount_out = if params.kind == OrderKind::Limit {
+        let at_price = (params.amount as u128)
+            .checked_mul(params.trigger_price_x64)
+            .ok_or(AmmError::MathOverflow)?
+            .div_ceil(1 << 64);
+        let at_price = u64::try_from(at_price).map_err(|_| AmmError::MathOverflow)?;
+        params.min_amount_out.max(at_price)
+    } else {
+        params.min_amount_out
+    };
+
+    let order = &mut ctx.accounts.order;
+    order.owner = ctx.accounts.owner.key();
+    order.pool = ctx.accounts.pool.key();
+    order.id = id;
+    order.kind = params.kind;
+    order.is_swap_a = params.is_swap_a;
+    order.amount = params.amount;
+    order.trigger_price_x64 = params.trigger_price_x64;
+    order.twap_window = params.twap_window;
+    order.min_amount_out = min_amount_out;
+    order.keeper_bounty = params.keeper_bounty;
+
+    let input_mint = &ctx.accounts.input_mint;
+    let cpi_accounts = TransferChecked {
+        mint: input_mint.to_account_info(),
+        from: ctx.accounts.owner_input_account.to_account_info(),
+        to: ctx.accounts.escrow.to_account_info(),
+        authority: ctx.accounts.owner.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.input_token_program.to_account_info();
+    let cpi_context = CpiContext::new(cpi_program, cpi_accounts)
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+    transfer_checked_with_hook(cpi_context, amount_sent, input_mint.decimals)
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64, params: OrderParams)]
+pub struct PlaceOrder<'info> {
+    #[account(
+        seeds = [AMM_POOL_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub pool: Box<Account<'info, AmmPool>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Order::INIT_SPACE,
+        seeds = [ORDER_SEED.as_bytes(), pool.key().as_ref(), owner.key().as_ref(), id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub order: Box<Account<'info, Order>>,
+
+    /// `pool.mint_a` when selling A, `pool.mint_b` otherwise
+    #[account(
+        constraint = input_mint.key() == if params.is_swap_a { pool.mint_a } else { pool.mint_b } @ AmmError::InvalidOrderMint,
+        mint::token_program = input_token_program,
+    )]
+    pub input_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Created if needed, the address is known before the order exists and anyone can create it first
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = input_mint,
+        associated_token::token_program = input_token_program,
+        associated_token::authority = order,
+    )]
+    pub escrow: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = input_mint,
+        associated_token::token_program = input_token_program,
+        associated_token::authority = owner,
+    )]
+    pub owner_input_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub input_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+use crate::instructions::add_liquidity::{calculate_lp, deposit_amounts_with_transfer_fees};
+use crate::instructions::QuotePool;
+
+/// Amounts `add_liquidity` would take from the depositor, transfer fees included, and the LP it would mint
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AddLiquidityPreview {
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub lp_amount: u64,
+}
+
+/// Previews `add_liquidity` without moving tokens, the preview is set as return data
+pub fn preview_add_liquidity(
+    ctx: Context<QuotePool>,
+    amount_a: u64,
+    amount_b: u64,
+) -> Result<AddLiquidityPreview> {
+    require!(amount_a > 0 && amount_b > 0, AmmError::AmountIsZero);
+    ctx.accounts.check_tradable()?;
+
+    let (reserve_a, reserve_b) = ctx.accounts.reserves(true)?;
+    let ((deposit_a, deposit_b), (amount_a, amount_b)) = deposit_amounts_with_transfer_fees(
+        &ctx.accounts.mint_a.to_account_info(),
+        &ctx.accounts.mint_b.to_account_info(),
+        reserve_a,
+        amount_a,
+        reserve_b,
+        amount_b,
+    )?;
+    let total_lp = ctx.accounts.mint_liquidity.supply;
+    let lp_amount = calculate_lp(deposit_a, deposit_b, reserve_a, reserve_b, total_lp)?;
+
+    Ok(AddLiquidityPreview {
+        amount_a,
+        amount_b,
+        lp_amount,
+    })
+}
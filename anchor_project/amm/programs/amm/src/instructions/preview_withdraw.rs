@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+use crate::instructions::withdraw_liquidity::calculate_out_amounts;
+use crate::instructions::QuotePool;
+use crate::utils::transfer_fee;
+
+/// Amounts the recipient of `withdraw_liquidity` would receive for the burned LP, after transfer fees
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct WithdrawPreview {
+    pub amount_a: u64,
+    pub amount_b: u64,
+}
+
+/// Previews `withdraw_liquidity` without moving tokens, the preview is set as return data
+pub fn preview_withdraw(ctx: Context<QuotePool>, lp_amount: u64) -> Result<WithdrawPreview> {
+    require!(lp_amount > 0, AmmError::AmountIsZero);
+    require!(
+        !ctx.accounts.pool.flash_loan_active,
+        AmmError::FlashLoanActive
+    );
+    let total_lp = ctx.accounts.mint_liquidity.supply;
+    require!(total_lp > 0, AmmError::LpSupplyIsZero);
+    require!(lp_amount <= total_lp, AmmError::InsufficientLpBalance);
+
+    let (reserve_a, reserve_b) = ctx.accounts.reserves(true)?;
+    require!(reserve_a > 0 && reserve_b > 0, AmmError::InvalidPoolState);
+    let (amount_a, amount_b) = calculate_out_amounts(lp_amount, total_lp, reserve_a, reserve_b)?;
+    let amount_a = amount_a - transfer_fee(&ctx.accounts.mint_a.to_account_info(), amount_a)?;
+    let amount_b = amount_b - transfer_fee(&ctx.accounts.mint_b.to_account_info(), amount_b)?;
+
+    Ok(WithdrawPreview { amount_a, amount_b })
+}
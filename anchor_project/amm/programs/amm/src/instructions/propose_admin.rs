@@ -0,0 +1,29 @@
+use crate::errors::AmmError;
+use crate::instructions::update_amm_config::queue_config_change;
+use crate::states::{Amm, ConfigChange, AMM_SEED};
+use anchor_lang::prelude::*;
+
+/// Queues a `ConfigChange::Admin` for `new_admin`, the same as `update_amm_config` does
+pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+    queue_config_change(
+        &mut ctx.accounts.amm,
+        None,
+        &ctx.accounts.admin,
+        ctx.remaining_accounts,
+        ConfigChange::Admin { new_admin },
+    )
+}
+
+#[derive(Accounts)]
+pub struct ProposeAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+        has_one = admin @ AmmError::Unauthorized,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    /// CHECK: Wallet or `Multisig` matching `Amm.admin`, its approval is checked by `authorize`
+    pub admin: UncheckedAccount<'info>,
+}
@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+
+use crate::states::{AmmPool, TwammExpirySnapshot, AMM_POOL_SEED, TWAMM_EXPIRY_SEED};
+
+/// Moves a crossed expiry out of the pool into its own snapshot account, freeing its slot for new expiries.
+/// Anyone can call it, the rent goes back to `payer` once the last order expiring there is closed.
+pub fn prune_twamm_expiry(ctx: Context<PruneTwammExpiry>, timestamp: i64) -> Result<()> {
+    let expiry = ctx.accounts.pool.twamm.prune_expiry(timestamp)?;
+
+    let snapshot = &mut ctx.accounts.snapshot;
+    snapshot.pool = ctx.accounts.pool.key();
+    snapshot.timestamp = timestamp;
+    snapshot.earnings_per_rate_a_x64 = expiry.earnings_per_rate_a_x64;
+    snapshot.earnings_per_rate_b_x64 = expiry.earnings_per_rate_b_x64;
+    snapshot.idle_seconds = expiry.idle_seconds;
+    snapshot.orders = expiry.orders;
+    snapshot.payer = ctx.accounts.payer.key();
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(timestamp: i64)]
+pub struct PruneTwammExpiry<'info> {
+    #[account(
+        mut,
+        seeds = [AMM_POOL_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub pool: Box<Account<'info, AmmPool>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + TwammExpirySnapshot::INIT_SPACE,
+        seeds = [TWAMM_EXPIRY_SEED.as_bytes(), pool.key().as_ref(), timestamp.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub snapshot: Box<Account<'info, TwammExpirySnapshot>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
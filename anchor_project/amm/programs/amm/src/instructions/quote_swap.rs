@@ -0,0 +1,122 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+use crate::instructions::{quote_exact_in_with_transfer_fees, SwapQuote};
+use crate::states::{
+    Amm, AmmPool, AMM_MINT_LIQUIDITY_SEED, AMM_POOL_AUTHORITY_SEED, AMM_POOL_SEED, AMM_SEED,
+};
+use crate::twamm::execute_virtual_orders;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Quotes `swap` for exactly `amount` in without moving tokens, the quote is set as return data
+pub fn quote_swap(ctx: Context<QuotePool>, is_swap_a: bool, amount: u64) -> Result<SwapQuote> {
+    require!(amount > 0, AmmError::AmountIsZero);
+    ctx.accounts.check_tradable()?;
+
+    let (input_reserve, output_reserve) = ctx.accounts.reserves(is_swap_a)?;
+    let fee_bps = ctx.accounts.pool.effective_fee(&ctx.accounts.amm);
+    let (input_mint, output_mint) = ctx.accounts.mints(is_swap_a);
+    quote_exact_in_with_transfer_fees(
+        &input_mint,
+        &output_mint,
+        input_reserve,
+        output_reserve,
+        amount,
+        fee_bps,
+        ctx.accounts.amm.protocol_fee_bps,
+    )
+}
+
+impl<'info> QuotePool<'info> {
+    /// Returns the input and output mints of a swap
+    pub(crate) fn mints(&self, is_swap_a: bool) -> (AccountInfo<'info>, AccountInfo<'info>) {
+        let (mint_a, mint_b) = (self.mint_a.to_account_info(), self.mint_b.to_account_info());
+        if is_swap_a {
+            (mint_a, mint_b)
+        } else {
+            (mint_b, mint_a)
+        }
+    }
+
+    /// Fails where the mirrored instruction would, while paused or during a flash loan
+    pub(crate) fn check_tradable(&self) -> Result<()> {
+        require!(!self.amm.paused && !self.pool.paused, AmmError::Paused);
+        require!(!self.pool.flash_loan_active, AmmError::FlashLoanActive);
+        Ok(())
+    }
+
+    /// Returns the input and output reserves of a swap, without uncollected protocol fees, after the long-term
+    /// orders executed up to now
+    pub(crate) fn reserves(&self, is_swap_a: bool) -> Result<(u64, u64)> {
+        let mut pool = (**self.pool).clone();
+        execute_virtual_orders(
+            &mut pool,
+            &self.amm,
+            self.pool_account_a.amount,
+            self.pool_account_b.amount,
+            Clock::get()?.unix_timestamp,
+        )?;
+        let (reserve_a, reserve_b) =
+            pool.reserves(self.pool_account_a.amount, self.pool_account_b.amount)?;
+        Ok(if is_swap_a {
+            (reserve_a, reserve_b)
+        } else {
+            (reserve_b, reserve_a)
+        })
+    }
+}
+
+/// Read-only view of a pool, shared by the quote and preview instructions
+#[derive(Accounts)]
+pub struct QuotePool<'info> {
+    #[account(
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub amm: Box<Account<'info, Amm>>,
+
+    #[account(
+        seeds = [AMM_POOL_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
+        bump,
+        has_one = mint_a,
+        has_one = mint_b,
+        has_one = amm,
+        has_one = token_program_a,
+        has_one = token_program_b,
+    )]
+    pub pool: Box<Account<'info, AmmPool>>,
+
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK readonly
+    #[account(
+        seeds=[AMM_POOL_AUTHORITY_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub authority: AccountInfo<'info>,
+
+    #[account(
+        seeds = [AMM_MINT_LIQUIDITY_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub mint_liquidity: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        associated_token::mint = mint_a,
+        associated_token::token_program = token_program_a,
+        associated_token::authority = authority
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        associated_token::mint = mint_b,
+        associated_token::token_program = token_program_b,
+        associated_token::authority = authority
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+}
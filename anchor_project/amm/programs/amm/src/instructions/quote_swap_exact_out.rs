@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+use crate::instructions::{quote_exact_out_with_transfer_fees, QuotePool, SwapQuote};
+
+/// Quotes `swap_exact_out` for exactly `amount_out` out without moving tokens, the quote is set as return data
+pub fn quote_swap_exact_out(
+    ctx: Context<QuotePool>,
+    is_swap_a: bool,
+    amount_out: u64,
+) -> Result<SwapQuote> {
+    require!(amount_out > 0, AmmError::AmountIsZero);
+    ctx.accounts.check_tradable()?;
+
+    let (input_reserve, output_reserve) = ctx.accounts.reserves(is_swap_a)?;
+    let fee_bps = ctx.accounts.pool.effective_fee(&ctx.accounts.amm);
+    let (input_mint, output_mint) = ctx.accounts.mints(is_swap_a);
+    quote_exact_out_with_transfer_fees(
+        &input_mint,
+        &output_mint,
+        input_reserve,
+        output_reserve,
+        amount_out,
+        fee_bps,
+        ctx.accounts.amm.protocol_fee_bps,
+    )
+}
@@ -0,0 +1,33 @@
+use crate::auth::authorize;
+use crate::errors::AmmError;
+use crate::states::{Amm, AMM_SEED};
+use anchor_lang::prelude::*;
+
+/// Existing pools of the removed tier keep working, only new pools can't use it
+pub fn remove_fee_tier(ctx: Context<RemoveFeeTier>, fee_tier: u16) -> Result<()> {
+    authorize(&ctx.accounts.fee_manager, ctx.remaining_accounts)?;
+
+    let amm = &mut ctx.accounts.amm;
+    let position = amm
+        .fee_tiers
+        .iter()
+        .position(|tier| *tier == fee_tier)
+        .ok_or(AmmError::FeeTierNotFound)?;
+
+    amm.fee_tiers.remove(position);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RemoveFeeTier<'info> {
+    #[account(
+        mut,
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+        has_one = fee_manager @ AmmError::Unauthorized,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    /// CHECK: Wallet or `Multisig` matching `Amm.fee_manager`, its approval is checked by `authorize`
+    pub fee_manager: UncheckedAccount<'info>,
+}
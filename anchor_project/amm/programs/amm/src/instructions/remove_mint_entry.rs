@@ -0,0 +1,32 @@
+use crate::auth::authorize;
+use crate::errors::AmmError;
+use crate::states::{Amm, MintEntry, AMM_SEED, MINT_ENTRY_SEED};
+use anchor_lang::prelude::*;
+
+pub fn remove_mint_entry(ctx: Context<RemoveMintEntry>) -> Result<()> {
+    authorize(&ctx.accounts.pool_curator, ctx.remaining_accounts)?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RemoveMintEntry<'info> {
+    #[account(
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+        has_one = pool_curator @ AmmError::Unauthorized,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    #[account(
+        mut,
+        close = pool_curator,
+        seeds = [MINT_ENTRY_SEED.as_bytes(), amm.key().as_ref(), mint_entry.mint.as_ref()],
+        bump,
+        has_one = amm,
+    )]
+    pub mint_entry: Account<'info, MintEntry>,
+
+    /// CHECK: Wallet or `Multisig` matching `Amm.pool_curator`, its approval is checked by `authorize`
+    #[account(mut)]
+    pub pool_curator: UncheckedAccount<'info>,
+}
@@ -0,0 +1,32 @@
+use crate::auth::authorize;
+use crate::errors::AmmError;
+use crate::states::{Amm, PoolCreator, AMM_SEED, POOL_CREATOR_SEED};
+use anchor_lang::prelude::*;
+
+pub fn remove_pool_creator(ctx: Context<RemovePoolCreator>) -> Result<()> {
+    authorize(&ctx.accounts.pool_curator, ctx.remaining_accounts)?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RemovePoolCreator<'info> {
+    #[account(
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+        has_one = pool_curator @ AmmError::Unauthorized,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    #[account(
+        mut,
+        close = pool_curator,
+        seeds = [POOL_CREATOR_SEED.as_bytes(), amm.key().as_ref(), pool_creator.creator.as_ref()],
+        bump,
+        has_one = amm,
+    )]
+    pub pool_creator: Account<'info, PoolCreator>,
+
+    /// CHECK: Wallet or `Multisig` matching `Amm.pool_curator`, its approval is checked by `authorize`
+    #[account(mut)]
+    pub pool_curator: UncheckedAccount<'info>,
+}
@@ -0,0 +1,36 @@
+use crate::auth::authorize;
+use crate::errors::AmmError;
+use crate::states::{Amm, AMM_SEED};
+use anchor_lang::prelude::*;
+
+/// Existing pools of mints with the removed hook keep working, only new pools can't use it
+pub fn remove_transfer_hook_program(
+    ctx: Context<RemoveTransferHookProgram>,
+    program_id: Pubkey,
+) -> Result<()> {
+    authorize(&ctx.accounts.admin, ctx.remaining_accounts)?;
+
+    let amm = &mut ctx.accounts.amm;
+    let position = amm
+        .transfer_hook_programs
+        .iter()
+        .position(|program| *program == program_id)
+        .ok_or(AmmError::TransferHookProgramNotApproved)?;
+
+    amm.transfer_hook_programs.remove(position);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RemoveTransferHookProgram<'info> {
+    #[account(
+        mut,
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+        has_one = admin @ AmmError::Unauthorized,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    /// CHECK: Wallet or `Multisig` matching `Amm.admin`, its approval is checked by `authorize`
+    pub admin: UncheckedAccount<'info>,
+}
@@ -0,0 +1,44 @@
+use crate::auth::authorize;
+use crate::errors::AmmError;
+use crate::states::{Amm, MintEntry, AMM_SEED, MINT_ENTRY_SEED};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+pub fn set_mint_entry(ctx: Context<SetMintEntry>, allowed: bool) -> Result<()> {
+    authorize(&ctx.accounts.pool_curator, ctx.remaining_accounts)?;
+
+    let mint_entry = &mut ctx.accounts.mint_entry;
+    mint_entry.amm = ctx.accounts.amm.key();
+    mint_entry.mint = ctx.accounts.mint.key();
+    mint_entry.allowed = allowed;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetMintEntry<'info> {
+    #[account(
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+        has_one = pool_curator @ AmmError::Unauthorized,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + MintEntry::INIT_SPACE,
+        seeds = [MINT_ENTRY_SEED.as_bytes(), amm.key().as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub mint_entry: Account<'info, MintEntry>,
+
+    /// CHECK: Wallet or `Multisig` matching `Amm.pool_curator`, its approval is checked by `authorize`
+    pub pool_curator: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
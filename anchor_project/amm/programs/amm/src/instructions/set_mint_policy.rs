@@ -0,0 +1,26 @@
+use crate::auth::authorize;
+use crate::errors::AmmError;
+use crate::states::{Amm, MintPolicy, AMM_SEED};
+use anchor_lang::prelude::*;
+
+pub fn set_mint_policy(ctx: Context<SetMintPolicy>, mint_policy: MintPolicy) -> Result<()> {
+    authorize(&ctx.accounts.pool_curator, ctx.remaining_accounts)?;
+
+    let amm = &mut ctx.accounts.amm;
+    amm.mint_policy = mint_policy;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetMintPolicy<'info> {
+    #[account(
+        mut,
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+        has_one = pool_curator @ AmmError::Unauthorized,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    /// CHECK: Wallet or `Multisig` matching `Amm.pool_curator`, its approval is checked by `authorize`
+    pub pool_curator: UncheckedAccount<'info>,
+}
@@ -0,0 +1,47 @@
+use crate::errors::AmmError;
+use crate::instructions::update_amm_config::queue_config_change;
+use crate::states::{Amm, ConfigChange, Multisig, AMM_SEED, MULTISIG_SEED};
+use anchor_lang::prelude::*;
+
+/// Queues a `ConfigChange::MultisigConfig` that replaces the signer set once the timelock of the AMM of the
+/// multisig has passed, approved by the current signers
+pub fn set_multisig_config(
+    ctx: Context<SetMultisigConfig>,
+    signers: Vec<Pubkey>,
+    threshold: u8,
+) -> Result<()> {
+    Multisig::validate(&signers, threshold)?;
+    let change = ConfigChange::MultisigConfig {
+        multisig: ctx.accounts.multisig.key(),
+    };
+    queue_config_change(
+        &mut ctx.accounts.amm,
+        None,
+        &ctx.accounts.multisig.to_account_info(),
+        ctx.remaining_accounts,
+        change,
+    )?;
+
+    let multisig = &mut ctx.accounts.multisig;
+    multisig.pending_signers = signers;
+    multisig.pending_threshold = threshold;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetMultisigConfig<'info> {
+    #[account(
+        mut,
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    #[account(
+        mut,
+        seeds = [MULTISIG_SEED.as_bytes(), multisig.create_key.as_ref()],
+        bump,
+        has_one = amm @ AmmError::MultisigMismatch,
+    )]
+    pub multisig: Account<'info, Multisig>,
+}
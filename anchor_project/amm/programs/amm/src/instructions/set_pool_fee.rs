@@ -0,0 +1,41 @@
+use crate::errors::AmmError;
+use crate::instructions::update_amm_config::queue_config_change;
+use crate::states::{Amm, AmmPool, ConfigChange, AMM_POOL_SEED, AMM_SEED};
+use anchor_lang::prelude::*;
+
+/// Queues a `ConfigChange::PoolFee` for `pool`, the same as `update_amm_config` does
+pub fn set_pool_fee(ctx: Context<SetPoolFee>, fee_bps: Option<u16>) -> Result<()> {
+    let change = ConfigChange::PoolFee {
+        pool: ctx.accounts.pool.key(),
+        fee_bps,
+    };
+    queue_config_change(
+        &mut ctx.accounts.amm,
+        Some(&mut ctx.accounts.pool),
+        &ctx.accounts.fee_manager,
+        ctx.remaining_accounts,
+        change,
+    )
+}
+
+#[derive(Accounts)]
+pub struct SetPoolFee<'info> {
+    #[account(
+        mut,
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+        has_one = fee_manager @ AmmError::Unauthorized,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    #[account(
+        mut,
+        seeds = [AMM_POOL_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
+        bump,
+        has_one = amm,
+    )]
+    pub pool: Account<'info, AmmPool>,
+
+    /// CHECK: Wallet or `Multisig` matching `Amm.fee_manager`, its approval is checked by `authorize`
+    pub fee_manager: UncheckedAccount<'info>,
+}
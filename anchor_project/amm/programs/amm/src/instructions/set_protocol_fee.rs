@@ -0,0 +1,29 @@
+use crate::errors::AmmError;
+use crate::instructions::update_amm_config::queue_config_change;
+use crate::states::{Amm, ConfigChange, AMM_SEED};
+use anchor_lang::prelude::*;
+
+/// Queues a `ConfigChange::ProtocolFee`, the same as `update_amm_config` does
+pub fn set_protocol_fee(ctx: Context<SetProtocolFee>, protocol_fee_bps: u16) -> Result<()> {
+    queue_config_change(
+        &mut ctx.accounts.amm,
+        None,
+        &ctx.accounts.fee_manager,
+        ctx.remaining_accounts,
+        ConfigChange::ProtocolFee { protocol_fee_bps },
+    )
+}
+
+#[derive(Accounts)]
+pub struct SetProtocolFee<'info> {
+    #[account(
+        mut,
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+        has_one = fee_manager @ AmmError::Unauthorized,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    /// CHECK: Wallet or `Multisig` matching `Amm.fee_manager`, its approval is checked by `authorize`
+    pub fee_manager: UncheckedAccount<'info>,
+}
@@ -0,0 +1,33 @@
+use crate::auth::authorize;
+use crate::errors::AmmError;
+use crate::states::{Amm, Role, AMM_SEED};
+use anchor_lang::prelude::*;
+
+/// Assigns a role to a key, the admin itself changes through the two-step admin transfer
+pub fn set_role(ctx: Context<SetRole>, role: Role, key: Pubkey) -> Result<()> {
+    authorize(&ctx.accounts.admin, ctx.remaining_accounts)?;
+
+    let amm = &mut ctx.accounts.amm;
+    match role {
+        Role::Admin => return err!(AmmError::InvalidRole),
+        Role::FeeManager => amm.fee_manager = key,
+        Role::Pauser => amm.pauser = key,
+        Role::Treasury => amm.treasury = key,
+        Role::PoolCurator => amm.pool_curator = key,
+    }
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetRole<'info> {
+    #[account(
+        mut,
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+        has_one = admin @ AmmError::Unauthorized,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    /// CHECK: Wallet or `Multisig` matching `Amm.admin`, its approval is checked by `authorize`
+    pub admin: UncheckedAccount<'info>,
+}
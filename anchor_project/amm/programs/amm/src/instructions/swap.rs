@@ -1,45 +1,205 @@
+use crate::twamm::execute_virtual_orders;
+use crate::utils::{
+    amount_with_transfer_fee, check_deadline, native_side, transfer_checked_with_hook,
+    transfer_fee, unwrap_sol, wrap_sol,
+};
 use anchor_lang::prelude::*;
 
 use crate::errors::AmmError;
-use crate::states::{Amm, AmmPool, AMM_POOL_AUTHORITY_SEED, AMM_POOL_SEED, AMM_SEED};
-use anchor_spl::token::{transfer_checked, Mint, Token, TokenAccount, TransferChecked};
+use crate::states::{
+    Amm, AmmPool, Observations, AMM_POOL_AUTHORITY_SEED, AMM_POOL_SEED, AMM_SEED,
+    OBSERVATIONS_SEED, WSOL_SEED,
+};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
 
 use anchor_spl::associated_token::AssociatedToken;
 
-pub fn swap(ctx: Context<Swap>, is_swap_a: bool, amount: u64, min_out_amount: u64) -> Result<()> {
+/// Swaps exactly `amount` in, `remaining_accounts` carries the transfer hook accounts of both mints
+pub fn swap<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Swap<'info>>,
+    is_swap_a: bool,
+    amount: u64,
+    min_out_amount: u64,
+    deadline: Option<i64>,
+    native_sol: bool,
+) -> Result<()> {
+    check_deadline(deadline)?;
     require!(amount > 0, AmmError::AmountIsZero);
-
-    let trader_input_balance = if is_swap_a {
-        ctx.accounts.trader_account_a.amount
-    } else {
-        ctx.accounts.trader_account_b.amount
-    };
-
     require!(
-        trader_input_balance >= amount,
+        !ctx.accounts.amm.paused && !ctx.accounts.pool.paused,
+        AmmError::Paused
+    );
+    require!(
+        !ctx.accounts.pool.flash_loan_active,
+        AmmError::FlashLoanActive
+    );
+    ctx.accounts.execute_virtual_orders()?;
+    if native_sol {
+        ctx.accounts.wrap_sol_input(is_swap_a, amount)?;
+    }
+    require!(
+        ctx.accounts.trader_input_balance(is_swap_a) >= amount,
         AmmError::InsufficientBalance
     );
 
-    let fee_bps = ctx.accounts.amm.fee;
+    let (input_reserve, output_reserve) = ctx.accounts.reserves(is_swap_a)?;
+    let fee_bps = ctx.accounts.pool.effective_fee(&ctx.accounts.amm);
+    let (input_mint, output_mint) = ctx.accounts.mints(is_swap_a);
+    let quote = quote_exact_in_with_transfer_fees(
+        &input_mint,
+        &output_mint,
+        input_reserve,
+        output_reserve,
+        amount,
+        fee_bps,
+        ctx.accounts.amm.protocol_fee_bps,
+    )?;
+    require!(
+        quote.amount_out >= min_out_amount,
+        AmmError::OutputAmountTooLow
+    );
 
-    let percent = 10_000 - fee_bps as u128;
-    let amount_eff = (amount as u128)
-        .checked_mul(percent)
-        .ok_or(AmmError::MathOverflow)?
-        / 10_000;
+    let bump = ctx.bumps.authority;
+    ctx.accounts
+        .settle(is_swap_a, &quote, bump, ctx.remaining_accounts)?;
+    if native_sol {
+        ctx.accounts.unwrap_sol_output(
+            is_swap_a,
+            quote.amount_out,
+            ctx.bumps.temporary_wsol_account,
+        )?;
+    }
+    Ok(())
+}
+
+/// Amounts of a swap, as settled by the swap instructions and returned by the quote instructions
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SwapQuote {
+    /// Sent by the trader
+    pub amount_in: u64,
+    /// Received by the recipient
+    pub amount_out: u64,
+    /// Swap fee taken from what arrives in the pool, including the protocol part
+    pub fee: u64,
+    /// Part of `fee` that goes to the protocol instead of the LPs
+    pub protocol_fee: u64,
+    /// Withheld by a Token-2022 transfer fee on the way into the pool
+    pub transfer_fee_in: u64,
+    /// Withheld by a Token-2022 transfer fee on the way to the recipient
+    pub transfer_fee_out: u64,
+}
+
+/// Quotes a swap of exactly `amount` sent by the trader. The curve prices what arrives in the pool after the
+/// transfer fee of `input_mint`, the recipient gets the output less the transfer fee of `output_mint`.
+pub fn quote_exact_in_with_transfer_fees(
+    input_mint: &AccountInfo,
+    output_mint: &AccountInfo,
+    input_reserve: u64,
+    output_reserve: u64,
+    amount: u64,
+    fee_bps: u16,
+    protocol_fee_bps: u16,
+) -> Result<SwapQuote> {
+    let transfer_fee_in = transfer_fee(input_mint, amount)?;
+    let mut quote = quote_exact_in(
+        input_reserve,
+        output_reserve,
+        amount - transfer_fee_in,
+        fee_bps,
+        protocol_fee_bps,
+    )?;
+    let transfer_fee_out = transfer_fee(output_mint, quote.amount_out)?;
+    quote.amount_in = amount;
+    quote.amount_out -= transfer_fee_out;
+    quote.transfer_fee_in = transfer_fee_in;
+    quote.transfer_fee_out = transfer_fee_out;
+    Ok(quote)
+}
+
+/// Quotes a swap that delivers exactly `amount_out` to the recipient. The pool sends enough to cover the transfer
+/// fee of `output_mint` and the trader enough to cover the transfer fee of `input_mint`.
+pub fn quote_exact_out_with_transfer_fees(
+    input_mint: &AccountInfo,
+    output_mint: &AccountInfo,
+    input_reserve: u64,
+    output_reserve: u64,
+    amount_out: u64,
+    fee_bps: u16,
+    protocol_fee_bps: u16,
+) -> Result<SwapQuote> {
+    let pool_amount_out = amount_with_transfer_fee(output_mint, amount_out)?;
+    let mut quote = quote_exact_out(
+        input_reserve,
+        output_reserve,
+        pool_amount_out,
+        fee_bps,
+        protocol_fee_bps,
+    )?;
+    let amount_in = amount_with_transfer_fee(input_mint, quote.amount_in)?;
+    quote.transfer_fee_in = amount_in - quote.amount_in;
+    quote.transfer_fee_out = pool_amount_out - amount_out;
+    quote.amount_in = amount_in;
+    quote.amount_out = amount_out;
+    Ok(quote)
+}
 
-    let pool_a = &ctx.accounts.pool_account_a;
-    let pool_b = &ctx.accounts.pool_account_b;
-    let (input_pool, output_pool) = if is_swap_a {
-        (pool_a, pool_b)
-    } else {
-        (pool_b, pool_a)
-    };
+/// Quotes a swap of exactly `amount` in against the given reserves
+pub fn quote_exact_in(
+    input_reserve: u64,
+    output_reserve: u64,
+    amount: u64,
+    fee_bps: u16,
+    protocol_fee_bps: u16,
+) -> Result<SwapQuote> {
+    require!(input_reserve > 0 && output_reserve > 0, AmmError::EmptyPool);
 
-    let (input_reserve, output_reserve) = (input_pool.amount, output_pool.amount);
+    let amount_eff = amount_after_fee(amount, fee_bps)?;
+    let amount_out = output_amount(input_reserve, output_reserve, amount_eff)?;
+    fee_split(amount, amount_eff, amount_out, protocol_fee_bps)
+}
 
+/// Quotes a swap of exactly `amount_out` out against the given reserves, rounding the input up against the trader
+pub fn quote_exact_out(
+    input_reserve: u64,
+    output_reserve: u64,
+    amount_out: u64,
+    fee_bps: u16,
+    protocol_fee_bps: u16,
+) -> Result<SwapQuote> {
     require!(input_reserve > 0 && output_reserve > 0, AmmError::EmptyPool);
+    require!(amount_out < output_reserve, AmmError::InsufficientLiquidity);
 
+    let k = (input_reserve as u128)
+        .checked_mul(output_reserve as u128)
+        .ok_or(AmmError::MathOverflow)?;
+
+    // smallest input reserve that keeps k once `amount_out` leaves the pool
+    let new_input_reserve = k.div_ceil((output_reserve - amount_out) as u128);
+    let amount_eff = new_input_reserve - input_reserve as u128;
+
+    let amount_in: u64 = amount_eff
+        .checked_mul(10_000)
+        .ok_or(AmmError::MathOverflow)?
+        .div_ceil(10_000 - fee_bps as u128)
+        .try_into()
+        .map_err(|_| AmmError::MathOverflow)?;
+
+    // the fee is charged on the rounded up input, the curve gets at least `amount_eff`
+    let amount_eff = amount_after_fee(amount_in, fee_bps)?;
+    fee_split(amount_in, amount_eff, amount_out, protocol_fee_bps)
+}
+
+/// Returns the part of `amount` that goes into the curve once the swap fee is taken
+fn amount_after_fee(amount: u64, fee_bps: u16) -> Result<u128> {
+    let percent = 10_000 - fee_bps as u128;
+    Ok((amount as u128)
+        .checked_mul(percent)
+        .ok_or(AmmError::MathOverflow)?
+        / 10_000)
+}
+
+/// Returns what leaves the output reserve when `amount_eff` enters the input reserve, keeping x * y = k
+fn output_amount(input_reserve: u64, output_reserve: u64, amount_eff: u128) -> Result<u64> {
     let k = (input_reserve as u128)
         .checked_mul(output_reserve as u128)
         .ok_or(AmmError::MathOverflow)?;
@@ -49,65 +209,213 @@ pub fn swap(ctx: Context<Swap>, is_swap_a: bool, amount: u64, min_out_amount: u6
         .ok_or(AmmError::MathOverflow)?;
     let new_output_reserve = k / new_input_reserve;
 
-    let output_amount = (output_reserve)
+    let output_amount = output_reserve
         .checked_sub(new_output_reserve as u64)
         .ok_or(AmmError::MathOverflow)?;
-    require!(
-        output_amount >= min_out_amount,
-        AmmError::OutputAmountTooLow
-    );
+    Ok(output_amount)
+}
+
+/// Splits the swap fee on `amount_in` between the LPs and the protocol
+fn fee_split(
+    amount_in: u64,
+    amount_eff: u128,
+    amount_out: u64,
+    protocol_fee_bps: u16,
+) -> Result<SwapQuote> {
+    let fee = amount_in as u128 - amount_eff;
+    let protocol_fee = fee
+        .checked_mul(protocol_fee_bps as u128)
+        .ok_or(AmmError::MathOverflow)?
+        / 10_000;
+    Ok(SwapQuote {
+        amount_in,
+        amount_out,
+        fee: fee as u64,
+        protocol_fee: protocol_fee as u64,
+        transfer_fee_in: 0,
+        transfer_fee_out: 0,
+    })
+}
+
+impl<'info> Swap<'info> {
+    /// Returns the input and output mints of the swap
+    pub(crate) fn mints(&self, is_swap_a: bool) -> (AccountInfo<'info>, AccountInfo<'info>) {
+        let (mint_a, mint_b) = (self.mint_a.to_account_info(), self.mint_b.to_account_info());
+        if is_swap_a {
+            (mint_a, mint_b)
+        } else {
+            (mint_b, mint_a)
+        }
+    }
+
+    /// Returns the token program of token A or B
+    pub(crate) fn token_program(&self, is_a: bool) -> AccountInfo<'info> {
+        if is_a {
+            self.token_program_a.to_account_info()
+        } else {
+            self.token_program_b.to_account_info()
+        }
+    }
 
-    let (input_mint, output_mint, trader_input, trader_output) = if is_swap_a {
-        (
-            &ctx.accounts.mint_a,
-            &ctx.accounts.mint_b,
-            &ctx.accounts.trader_account_a,
-            &ctx.accounts.trader_account_b,
+    pub(crate) fn trader_input_balance(&self, is_swap_a: bool) -> u64 {
+        if is_swap_a {
+            self.trader_account_a.amount
+        } else {
+            self.trader_account_b.amount
+        }
+    }
+
+    /// Wraps `amount` lamports of the trader into its input account when the input is the native mint
+    pub(crate) fn wrap_sol_input(&mut self, is_swap_a: bool, amount: u64) -> Result<()> {
+        if native_side(self.mint_a.key(), self.mint_b.key())? != is_swap_a {
+            return Ok(());
+        }
+        let token_program = self.token_program(is_swap_a);
+        let trader_input = if is_swap_a {
+            &mut self.trader_account_a
+        } else {
+            &mut self.trader_account_b
+        };
+        wrap_sol(
+            self.trader.to_account_info(),
+            trader_input.to_account_info(),
+            amount,
+            self.system_program.to_account_info(),
+            token_program,
+        )?;
+        trader_input.reload()
+    }
+
+    /// Pays the output of the swap out as lamports when it is the native mint, the rest of the WSOL account of the
+    /// trader stays wrapped. Native input was wrapped for exactly what the swap takes, nothing of it is left.
+    /// Unwrapped output can only go to the trader, the owner of the account has to sign for it.
+    pub(crate) fn unwrap_sol_output(
+        &self,
+        is_swap_a: bool,
+        amount_out: u64,
+        temporary_bump: u8,
+    ) -> Result<()> {
+        let is_native_a = native_side(self.mint_a.key(), self.mint_b.key())?;
+        if is_native_a == is_swap_a {
+            return Ok(());
+        }
+        require_keys_eq!(
+            self.recipient.key(),
+            self.trader.key(),
+            AmmError::InvalidNativeSolRecipient
+        );
+        unwrap_sol(
+            self.trader.to_account_info(),
+            self.recipient_account.to_account_info(),
+            self.temporary_wsol_account.to_account_info(),
+            temporary_bump,
+            self.output_mint.to_account_info(),
+            amount_out,
+            self.system_program.to_account_info(),
+            self.token_program(is_native_a),
         )
-    } else {
-        (
-            &ctx.accounts.mint_b,
-            &ctx.accounts.mint_a,
-            &ctx.accounts.trader_account_b,
-            &ctx.accounts.trader_account_a,
+    }
+
+    /// Executes the long-term orders up to now, the trade then sees the price they leave behind
+    pub(crate) fn execute_virtual_orders(&mut self) -> Result<()> {
+        execute_virtual_orders(
+            &mut self.pool,
+            &self.amm,
+            self.pool_account_a.amount,
+            self.pool_account_b.amount,
+            Clock::get()?.unix_timestamp,
         )
-    };
-
-    // transfer input amount from trader to input pool
-    let cpi_accounts = TransferChecked {
-        mint: input_mint.to_account_info(),
-        from: trader_input.to_account_info(),
-        to: input_pool.to_account_info(),
-        authority: ctx.accounts.trader.to_account_info(),
-    };
-    let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_context = CpiContext::new(cpi_program, cpi_accounts);
-    transfer_checked(cpi_context, amount, input_mint.decimals)?;
-
-    // transfer output amount to trader
-    let authority_signer_seeds: &[&[&[u8]]] = &[&[
-        &AMM_POOL_AUTHORITY_SEED.as_bytes(),
-        &ctx.accounts.pool.amm.to_bytes(),
-        &ctx.accounts.mint_a.key().to_bytes(),
-        &ctx.accounts.mint_b.key().to_bytes(),
-        &[ctx.bumps.authority],
-    ]];
-
-    let cpi_accounts = TransferChecked {
-        mint: output_mint.to_account_info(),
-        from: output_pool.to_account_info(),
-        to: trader_output.to_account_info(),
-        authority: ctx.accounts.authority.to_account_info(),
-    };
-    let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_context =
-        CpiContext::new(cpi_program, cpi_accounts).with_signer(authority_signer_seeds);
-    transfer_checked(cpi_context, output_amount, output_mint.decimals)?;
+    }
 
-    Ok(())
+    /// Returns the input and output reserves of the swap, without uncollected protocol fees
+    pub(crate) fn reserves(&self, is_swap_a: bool) -> Result<(u64, u64)> {
+        let (reserve_a, reserve_b) = self
+            .pool
+            .reserves(self.pool_account_a.amount, self.pool_account_b.amount)?;
+        Ok(if is_swap_a {
+            (reserve_a, reserve_b)
+        } else {
+            (reserve_b, reserve_a)
+        })
+    }
+
+    /// Records the price before the trade, books the protocol part of the fee, moves the input from the
+    /// trader and the output to the recipient. The pool sends the output with its transfer fee on top, both
+    /// transfers run the transfer hooks of their mints with `hook_accounts`.
+    pub(crate) fn settle(
+        &mut self,
+        is_swap_a: bool,
+        quote: &SwapQuote,
+        authority_bump: u8,
+        hook_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let (reserve_a, reserve_b) = self
+            .pool
+            .reserves(self.pool_account_a.amount, self.pool_account_b.amount)?;
+        self.pool
+            .update_price_accumulators(reserve_a, reserve_b, Clock::get()?.unix_timestamp);
+        self.observations.record(&self.pool);
+
+        self.pool.add_protocol_fee(is_swap_a, quote.protocol_fee)?;
+
+        let pool_a = &self.pool_account_a;
+        let pool_b = &self.pool_account_b;
+        let (input_pool, output_pool) = if is_swap_a {
+            (pool_a, pool_b)
+        } else {
+            (pool_b, pool_a)
+        };
+
+        let (input_mint, output_mint, trader_input) = if is_swap_a {
+            (&self.mint_a, &self.mint_b, &self.trader_account_a)
+        } else {
+            (&self.mint_b, &self.mint_a, &self.trader_account_b)
+        };
+
+        // transfer input amount from trader to input pool
+        let cpi_accounts = TransferChecked {
+            mint: input_mint.to_account_info(),
+            from: trader_input.to_account_info(),
+            to: input_pool.to_account_info(),
+            authority: self.trader.to_account_info(),
+        };
+        let cpi_program = self.token_program(is_swap_a);
+        let cpi_context = CpiContext::new(cpi_program, cpi_accounts)
+            .with_remaining_accounts(hook_accounts.to_vec());
+        transfer_checked_with_hook(cpi_context, quote.amount_in, input_mint.decimals)?;
+
+        // transfer output amount to recipient
+        let authority_signer_seeds: &[&[&[u8]]] = &[&[
+            AMM_POOL_AUTHORITY_SEED.as_bytes(),
+            &self.pool.amm.to_bytes(),
+            &self.mint_a.key().to_bytes(),
+            &self.mint_b.key().to_bytes(),
+            &self.pool.fee_tier.to_le_bytes(),
+            &[authority_bump],
+        ]];
+
+        let cpi_accounts = TransferChecked {
+            mint: output_mint.to_account_info(),
+            from: output_pool.to_account_info(),
+            to: self.recipient_account.to_account_info(),
+            authority: self.authority.to_account_info(),
+        };
+        let cpi_program = self.token_program(!is_swap_a);
+        let cpi_context = CpiContext::new(cpi_program, cpi_accounts)
+            .with_signer(authority_signer_seeds)
+            .with_remaining_accounts(hook_accounts.to_vec());
+        transfer_checked_with_hook(
+            cpi_context,
+            quote.amount_out + quote.transfer_fee_out,
+            output_mint.decimals,
+        )?;
+
+        Ok(())
+    }
 }
 
 #[derive(Accounts)]
+#[instruction(is_swap_a: bool)]
 pub struct Swap<'info> {
     #[account(
         seeds = [
@@ -118,60 +426,208 @@ pub struct Swap<'info> {
     pub amm: Account<'info, Amm>,
 
     #[account(
-        seeds = [AMM_POOL_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref()],
+        mut,
+        seeds = [AMM_POOL_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
         bump,
         has_one = mint_a,
         has_one = mint_b,
         has_one = amm,
+        has_one = token_program_a,
+        has_one = token_program_b,
     )]
     pub pool: Account<'info, AmmPool>,
 
+    #[account(
+        mut,
+        seeds = [OBSERVATIONS_SEED.as_bytes(), pool.key().as_ref()],
+        bump,
+        has_one = pool,
+    )]
+    pub observations: Box<Account<'info, Observations>>,
+
     /// CHECK: readonly
     #[account(
-        seeds=[AMM_POOL_AUTHORITY_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref()],
+        seeds=[AMM_POOL_AUTHORITY_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
         bump,
     )]
     pub authority: AccountInfo<'info>,
 
+    /// Pays and receives the lamports when `native_sol` is set
+    #[account(mut)]
     pub trader: Signer<'info>,
 
-    pub mint_a: Box<Account<'info, Mint>>,
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
 
-    pub mint_b: Box<Account<'info, Mint>>,
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
 
     #[account(
         mut,
         associated_token::mint = mint_a,
+        associated_token::token_program = token_program_a,
         associated_token::authority = authority,
     )]
-    pub pool_account_a: Box<Account<'info, TokenAccount>>,
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
         associated_token::mint = mint_b,
+        associated_token::token_program = token_program_b,
         associated_token::authority = authority,
     )]
-    pub pool_account_b: Box<Account<'info, TokenAccount>>,
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
-        mut,
+        init_if_needed,
+        payer = payer,
         associated_token::mint = mint_a,
+        associated_token::token_program = token_program_a,
         associated_token::authority = trader,
     )]
-    pub trader_account_a: Box<Account<'info, TokenAccount>>,
+    pub trader_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         init_if_needed,
         payer = payer,
         associated_token::mint = mint_b,
+        associated_token::token_program = token_program_b,
         associated_token::authority = trader,
     )]
-    pub trader_account_b: Box<Account<'info, TokenAccount>>,
+    pub trader_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// `mint_b` when swapping A to B, `mint_a` otherwise
+    #[account(
+        constraint = output_mint.key() == if is_swap_a { mint_b.key() } else { mint_a.key() } @ AmmError::InvalidOutputMint,
+        mint::token_program = output_token_program,
+    )]
+    pub output_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: only used as the owner of the account that receives the output
+    pub recipient: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = output_mint,
+        associated_token::token_program = output_token_program,
+        associated_token::authority = recipient,
+    )]
+    pub recipient_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Token account at the `["WSOL", trader]` PDA, created and closed within the instruction to pay a
+    /// native output out as lamports when `native_sol` is set
+    #[account(
+        mut,
+        seeds = [WSOL_SEED.as_bytes(), trader.key().as_ref()],
+        bump,
+    )]
+    pub temporary_wsol_account: UncheckedAccount<'info>,
 
     #[account(mut)]
     pub payer: Signer<'info>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+    /// Token program of `output_mint`
+    pub output_token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::states::MAX_FEE_BPS;
+
+    fn assert_error<T: std::fmt::Debug>(result: Result<T>, error: AmmError) {
+        assert_eq!(result.unwrap_err(), error.into());
+    }
+
+    /// Whether `amount_in` keeps k once the fee is taken and `amount_out` leaves the pool
+    fn keeps_k(
+        input_reserve: u64,
+        output_reserve: u64,
+        amount_in: u64,
+        amount_out: u64,
+        fee_bps: u16,
+    ) -> bool {
+        let amount_eff = amount_after_fee(amount_in, fee_bps).unwrap();
+        (input_reserve as u128 + amount_eff) * (output_reserve - amount_out) as u128
+            >= input_reserve as u128 * output_reserve as u128
+    }
+
+    #[test]
+    fn exact_out_rounds_the_input_up_against_the_trader() {
+        for (input_reserve, output_reserve, amount_out, fee_bps) in [
+            (1_000, 1_000, 1, 0),
+            (1_000, 1_000, 1, 30),
+            (1_000_003, 999_983, 12_345, 30),
+            (7, 1_000_000_000, 999_999_993, 9_999),
+            (u64::MAX / 2, u64::MAX / 2, 1, 30),
+        ] {
+            let quote =
+                quote_exact_out(input_reserve, output_reserve, amount_out, fee_bps, 0).unwrap();
+            assert_eq!(quote.amount_out, amount_out);
+            // the input keeps k, one unit less would not
+            assert!(keeps_k(
+                input_reserve,
+                output_reserve,
+                quote.amount_in,
+                amount_out,
+                fee_bps
+            ));
+            assert!(!keeps_k(
+                input_reserve,
+                output_reserve,
+                quote.amount_in - 1,
+                amount_out,
+                fee_bps
+            ));
+        }
+    }
+
+    #[test]
+    fn exact_out_charges_the_fee_on_the_rounded_input() {
+        let quote = quote_exact_out(5_000, 3_000, 1_000, 30, 0).unwrap();
+        let amount_eff = amount_after_fee(quote.amount_in, 30).unwrap();
+        assert_eq!(quote.fee as u128, quote.amount_in as u128 - amount_eff);
+        assert!(quote.fee > 0);
+    }
+
+    #[test]
+    fn exact_out_splits_the_protocol_fee_rounding_down() {
+        let quote = quote_exact_out(1_000_000, 1_000_000, 10_000, 30, 3_333).unwrap();
+        assert!(quote.fee > 0);
+        assert_eq!(quote.protocol_fee, quote.fee * 3_333 / 10_000);
+
+        let quote = quote_exact_out(1_000_000, 1_000_000, 10_000, 30, MAX_FEE_BPS).unwrap();
+        assert_eq!(quote.protocol_fee, quote.fee);
+    }
+
+    #[test]
+    fn exact_out_needs_liquidity() {
+        assert_error(quote_exact_out(0, 1_000, 1, 30, 0), AmmError::EmptyPool);
+        assert_error(quote_exact_out(1_000, 0, 1, 30, 0), AmmError::EmptyPool);
+        assert_error(
+            quote_exact_out(1_000, 1_000, 1_000, 30, 0),
+            AmmError::InsufficientLiquidity,
+        );
+        assert_error(
+            quote_exact_out(1_000, 1_000, u64::MAX, 30, 0),
+            AmmError::InsufficientLiquidity,
+        );
+    }
+
+    #[test]
+    fn exact_out_overflow_is_an_error() {
+        // the curve input does not fit in a u64
+        assert_error(
+            quote_exact_out(u64::MAX / 2, 1_000, 999, 30, 0),
+            AmmError::MathOverflow,
+        );
+        // nor does it once the fee is added
+        assert_error(
+            quote_exact_out(u64::MAX, u64::MAX, u64::MAX - 1, 30, 0),
+            AmmError::MathOverflow,
+        );
+    }
+}
@@ -0,0 +1,61 @@
+use crate::utils::check_deadline;
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+use crate::instructions::{quote_exact_out_with_transfer_fees, Swap};
+
+/// Swaps so that the trader receives exactly `amount_out`, rounding the input up against the trader. Accounts of
+/// transfer hooks of the mints go into `remaining_accounts`.
+pub fn swap_exact_out<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Swap<'info>>,
+    is_swap_a: bool,
+    amount_out: u64,
+    max_amount_in: u64,
+    deadline: Option<i64>,
+    native_sol: bool,
+) -> Result<()> {
+    check_deadline(deadline)?;
+    require!(amount_out > 0, AmmError::AmountIsZero);
+    require!(
+        !ctx.accounts.amm.paused && !ctx.accounts.pool.paused,
+        AmmError::Paused
+    );
+    require!(
+        !ctx.accounts.pool.flash_loan_active,
+        AmmError::FlashLoanActive
+    );
+    ctx.accounts.execute_virtual_orders()?;
+
+    let (input_reserve, output_reserve) = ctx.accounts.reserves(is_swap_a)?;
+    let fee_bps = ctx.accounts.pool.effective_fee(&ctx.accounts.amm);
+    let (input_mint, output_mint) = ctx.accounts.mints(is_swap_a);
+    let quote = quote_exact_out_with_transfer_fees(
+        &input_mint,
+        &output_mint,
+        input_reserve,
+        output_reserve,
+        amount_out,
+        fee_bps,
+        ctx.accounts.amm.protocol_fee_bps,
+    )?;
+    require!(
+        quote.amount_in <= max_amount_in,
+        AmmError::InputAmountTooHigh
+    );
+    if native_sol {
+        ctx.accounts.wrap_sol_input(is_swap_a, quote.amount_in)?;
+    }
+    require!(
+        ctx.accounts.trader_input_balance(is_swap_a) >= quote.amount_in,
+        AmmError::InsufficientBalance
+    );
+
+    let bump = ctx.bumps.authority;
+    ctx.accounts
+        .settle(is_swap_a, &quote, bump, ctx.remaining_accounts)?;
+    if native_sol {
+        ctx.accounts
+            .unwrap_sol_output(is_swap_a, amount_out, ctx.bumps.temporary_wsol_account)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,345 @@
+use crate::twamm::execute_virtual_orders;
+use crate::utils::{check_deadline, transfer_checked_with_hook};
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+use crate::instructions::quote_exact_in_with_transfer_fees;
+use crate::states::{Amm, AmmPool, Observations, AMM_POOL_AUTHORITY_SEED, AMM_SEED};
+use anchor_spl::associated_token::{get_associated_token_address_with_program_id, AssociatedToken};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
+
+/// Accounts per hop in `remaining_accounts`: pool, authority, pool_account_a, pool_account_b, mint_a, mint_b,
+/// token_program_a, token_program_b, observations
+pub const ROUTE_HOP_ACCOUNTS: usize = 9;
+
+/// Swaps `amount` of `input_mint` through the `hops` pools in `remaining_accounts`, one group of
+/// `ROUTE_HOP_ACCOUNTS` per hop, followed by the accounts of transfer hooks of the mints. Intermediate outputs go
+/// straight into the next pool, only the output of the last hop leaves the route. Every hop prices what arrives after
+/// the transfer fee of its input mint, `min_out_amount` is checked against what reaches the recipient.
+pub fn swap_route<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SwapRoute<'info>>,
+    amount: u64,
+    min_out_amount: u64,
+    hops: u8,
+    deadline: Option<i64>,
+) -> Result<()> {
+    check_deadline(deadline)?;
+    require!(amount > 0, AmmError::AmountIsZero);
+    require!(!ctx.accounts.amm.paused, AmmError::Paused);
+    require!(
+        ctx.accounts.trader_input_account.amount >= amount,
+        AmmError::InsufficientBalance
+    );
+
+    let route_len = hops as usize * ROUTE_HOP_ACCOUNTS;
+    require!(
+        hops > 0 && ctx.remaining_accounts.len() >= route_len,
+        AmmError::InvalidRoute
+    );
+    let (route_accounts, hook_accounts) = ctx.remaining_accounts.split_at(route_len);
+
+    let accounts = &ctx.accounts;
+    let amm = &accounts.amm;
+
+    let mut input_mint = accounts.input_mint.key();
+    // sent into the current hop and received out of the previous one, transfer fees make them differ
+    let mut amount_in = amount;
+    let mut amount_out = amount;
+    let mut previous: Option<RouteHop<'info>> = None;
+    let now = Clock::get()?.unix_timestamp;
+
+    for hop_accounts in route_accounts.chunks_exact(ROUTE_HOP_ACCOUNTS) {
+        let mut hop = RouteHop::load(amm.key(), input_mint, hop_accounts)?;
+        if let Some(previous) = &previous {
+            // the output of the previous hop still sits in its pool
+            require_keys_neq!(previous.pool.key(), hop.pool.key(), AmmError::InvalidRoute);
+        }
+        require!(!hop.pool.paused, AmmError::Paused);
+        require!(!hop.pool.flash_loan_active, AmmError::FlashLoanActive);
+        execute_virtual_orders(
+            &mut hop.pool,
+            amm,
+            hop.pool_account_a.amount,
+            hop.pool_account_b.amount,
+            now,
+        )?;
+
+        let (input_reserve, output_reserve) = hop.reserves()?;
+        let (reserve_a, reserve_b) = if hop.is_swap_a {
+            (input_reserve, output_reserve)
+        } else {
+            (output_reserve, input_reserve)
+        };
+        hop.pool
+            .update_price_accumulators(reserve_a, reserve_b, now);
+        hop.observations.record(&hop.pool);
+        hop.observations.exit(&crate::ID)?;
+        let fee_bps = hop.pool.effective_fee(amm);
+        let quote = quote_exact_in_with_transfer_fees(
+            &hop.input_mint().to_account_info(),
+            &hop.output_mint().to_account_info(),
+            input_reserve,
+            output_reserve,
+            amount_in,
+            fee_bps,
+            amm.protocol_fee_bps,
+        )?;
+        hop.pool
+            .add_protocol_fee(hop.is_swap_a, quote.protocol_fee)?;
+        hop.pool.exit(&crate::ID)?;
+
+        // move the input of this hop into the pool
+        let input_pool = hop.input_pool().to_account_info();
+        match &previous {
+            None => {
+                let cpi_accounts = TransferChecked {
+                    mint: accounts.input_mint.to_account_info(),
+                    from: accounts.trader_input_account.to_account_info(),
+                    to: input_pool,
+                    authority: accounts.trader.to_account_info(),
+                };
+                let cpi_program = accounts.input_token_program.to_account_info();
+                let cpi_context = CpiContext::new(cpi_program, cpi_accounts)
+                    .with_remaining_accounts(hook_accounts.to_vec());
+                transfer_checked_with_hook(cpi_context, amount_in, accounts.input_mint.decimals)?;
+            }
+            Some(previous) => {
+                previous.transfer_output(input_pool, amount_in, hook_accounts)?;
+            }
+        }
+
+        input_mint = hop.output_mint().key();
+        amount_in = quote.amount_out + quote.transfer_fee_out;
+        amount_out = quote.amount_out;
+        previous = Some(hop);
+    }
+
+    require_keys_eq!(
+        input_mint,
+        accounts.output_mint.key(),
+        AmmError::InvalidRoute
+    );
+    require!(amount_out >= min_out_amount, AmmError::OutputAmountTooLow);
+
+    let last = previous.ok_or(AmmError::InvalidRoute)?;
+    last.transfer_output(
+        accounts.recipient_account.to_account_info(),
+        amount_in,
+        hook_accounts,
+    )
+}
+
+/// One pool of a route, checked against the AMM and the mint coming into it
+struct RouteHop<'info> {
+    pool: Account<'info, AmmPool>,
+    observations: Account<'info, Observations>,
+    authority: &'info AccountInfo<'info>,
+    authority_bump: u8,
+    pool_account_a: InterfaceAccount<'info, TokenAccount>,
+    pool_account_b: InterfaceAccount<'info, TokenAccount>,
+    mint_a: InterfaceAccount<'info, Mint>,
+    mint_b: InterfaceAccount<'info, Mint>,
+    token_program_a: &'info AccountInfo<'info>,
+    token_program_b: &'info AccountInfo<'info>,
+    is_swap_a: bool,
+}
+
+impl<'info> RouteHop<'info> {
+    fn load(
+        amm: Pubkey,
+        input_mint: Pubkey,
+        accounts: &'info [AccountInfo<'info>],
+    ) -> Result<Self> {
+        require!(accounts[0].is_writable, AmmError::InvalidRoute);
+        let pool = Account::<AmmPool>::try_from(&accounts[0])?;
+        require_keys_eq!(pool.amm, amm, AmmError::InvalidRoute);
+
+        let mint_a = InterfaceAccount::<Mint>::try_from(&accounts[4])?;
+        let mint_b = InterfaceAccount::<Mint>::try_from(&accounts[5])?;
+        require_keys_eq!(mint_a.key(), pool.mint_a, AmmError::InvalidRoute);
+        require_keys_eq!(mint_b.key(), pool.mint_b, AmmError::InvalidRoute);
+        require_keys_eq!(
+            accounts[6].key(),
+            pool.token_program_a,
+            AmmError::InvalidRoute
+        );
+        require_keys_eq!(
+            accounts[7].key(),
+            pool.token_program_b,
+            AmmError::InvalidRoute
+        );
+
+        require!(accounts[8].is_writable, AmmError::InvalidRoute);
+        let observations = Account::<Observations>::try_from(&accounts[8])?;
+        require_keys_eq!(observations.pool, accounts[0].key(), AmmError::InvalidRoute);
+
+        let is_swap_a = input_mint == pool.mint_a;
+        require!(
+            is_swap_a || input_mint == pool.mint_b,
+            AmmError::InvalidRoute
+        );
+
+        let (authority_key, authority_bump) = Pubkey::find_program_address(
+            &[
+                AMM_POOL_AUTHORITY_SEED.as_bytes(),
+                pool.amm.as_ref(),
+                pool.mint_a.as_ref(),
+                pool.mint_b.as_ref(),
+                pool.fee_tier.to_le_bytes().as_ref(),
+            ],
+            &crate::ID,
+        );
+        require_keys_eq!(accounts[1].key(), authority_key, AmmError::InvalidRoute);
+        require_keys_eq!(
+            accounts[2].key(),
+            get_associated_token_address_with_program_id(
+                &authority_key,
+                &pool.mint_a,
+                &pool.token_program_a
+            ),
+            AmmError::InvalidRoute
+        );
+        require_keys_eq!(
+            accounts[3].key(),
+            get_associated_token_address_with_program_id(
+                &authority_key,
+                &pool.mint_b,
+                &pool.token_program_b
+            ),
+            AmmError::InvalidRoute
+        );
+
+        Ok(Self {
+            pool,
+            observations,
+            authority: &accounts[1],
+            authority_bump,
+            pool_account_a: InterfaceAccount::try_from(&accounts[2])?,
+            pool_account_b: InterfaceAccount::try_from(&accounts[3])?,
+            mint_a,
+            mint_b,
+            token_program_a: &accounts[6],
+            token_program_b: &accounts[7],
+            is_swap_a,
+        })
+    }
+
+    /// Returns the input and output reserves of the hop, without uncollected protocol fees
+    fn reserves(&self) -> Result<(u64, u64)> {
+        let (reserve_a, reserve_b) = self
+            .pool
+            .reserves(self.pool_account_a.amount, self.pool_account_b.amount)?;
+        Ok(if self.is_swap_a {
+            (reserve_a, reserve_b)
+        } else {
+            (reserve_b, reserve_a)
+        })
+    }
+
+    fn input_pool(&self) -> &InterfaceAccount<'info, TokenAccount> {
+        if self.is_swap_a {
+            &self.pool_account_a
+        } else {
+            &self.pool_account_b
+        }
+    }
+
+    fn input_mint(&self) -> &InterfaceAccount<'info, Mint> {
+        if self.is_swap_a {
+            &self.mint_a
+        } else {
+            &self.mint_b
+        }
+    }
+
+    fn output_mint(&self) -> &InterfaceAccount<'info, Mint> {
+        if self.is_swap_a {
+            &self.mint_b
+        } else {
+            &self.mint_a
+        }
+    }
+
+    /// Sends `amount` out of the output side of the pool, signed by the pool authority. A transfer hook of the
+    /// output mint runs with `hook_accounts`.
+    fn transfer_output(
+        &self,
+        to: AccountInfo<'info>,
+        amount: u64,
+        hook_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let (output_pool, token_program) = if self.is_swap_a {
+            (&self.pool_account_b, self.token_program_b)
+        } else {
+            (&self.pool_account_a, self.token_program_a)
+        };
+        let output_mint = self.output_mint();
+
+        let authority_signer_seeds: &[&[&[u8]]] = &[&[
+            AMM_POOL_AUTHORITY_SEED.as_bytes(),
+            &self.pool.amm.to_bytes(),
+            &self.pool.mint_a.to_bytes(),
+            &self.pool.mint_b.to_bytes(),
+            &self.pool.fee_tier.to_le_bytes(),
+            &[self.authority_bump],
+        ]];
+
+        let cpi_accounts = TransferChecked {
+            mint: output_mint.to_account_info(),
+            from: output_pool.to_account_info(),
+            to,
+            authority: self.authority.clone(),
+        };
+        let cpi_context = CpiContext::new(token_program.clone(), cpi_accounts)
+            .with_signer(authority_signer_seeds)
+            .with_remaining_accounts(hook_accounts.to_vec());
+        transfer_checked_with_hook(cpi_context, amount, output_mint.decimals)
+    }
+}
+
+#[derive(Accounts)]
+pub struct SwapRoute<'info> {
+    #[account(
+        seeds = [
+            AMM_SEED.as_bytes(), &amm.index.to_le_bytes()
+        ],
+        bump,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    pub trader: Signer<'info>,
+
+    #[account(mint::token_program = input_token_program)]
+    pub input_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = input_mint,
+        associated_token::token_program = input_token_program,
+        associated_token::authority = trader,
+    )]
+    pub trader_input_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mint::token_program = output_token_program)]
+    pub output_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: only used as the owner of the account that receives the output
+    pub recipient: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = output_mint,
+        associated_token::token_program = output_token_program,
+        associated_token::authority = recipient,
+    )]
+    pub recipient_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub input_token_program: Interface<'info, TokenInterface>,
+    pub output_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
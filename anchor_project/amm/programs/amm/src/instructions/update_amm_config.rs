@@ -0,0 +1,92 @@
+use crate::auth::authorize;
+use crate::errors::AmmError;
+use crate::states::{
+    Amm, AmmPool, ConfigChange, PendingConfigChange, AMM_POOL_SEED, AMM_SEED, MAX_FEE_BPS,
+    MAX_TIMELOCK_DELAY,
+};
+use anchor_lang::prelude::*;
+
+pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, change: ConfigChange) -> Result<()> {
+    queue_config_change(
+        &mut ctx.accounts.amm,
+        ctx.accounts.pool.as_mut(),
+        &ctx.accounts.authority,
+        ctx.remaining_accounts,
+        change,
+    )
+}
+
+/// Queues `change` once `authority` proved it holds the role of the change, or is the multisig a `MultisigConfig`
+/// reconfigures, shared by the instructions that queue one kind of change. Changes that target a pool go into the queue of `pool`, which has to be that pool.
+pub(crate) fn queue_config_change(
+    amm: &mut Amm,
+    pool: Option<&mut Account<AmmPool>>,
+    authority: &AccountInfo,
+    remaining_accounts: &[AccountInfo],
+    change: ConfigChange,
+) -> Result<()> {
+    require_keys_eq!(
+        authority.key(),
+        change.authority(amm),
+        AmmError::Unauthorized
+    );
+    authorize(authority, remaining_accounts)?;
+
+    match change {
+        ConfigChange::Fee { fee } => require!(fee < MAX_FEE_BPS, AmmError::InvalidFee),
+        ConfigChange::ProtocolFee { protocol_fee_bps } => {
+            require!(protocol_fee_bps <= MAX_FEE_BPS, AmmError::InvalidFee)
+        }
+        ConfigChange::PoolFee {
+            fee_bps: Some(fee_bps),
+            ..
+        } => require!(fee_bps < MAX_FEE_BPS, AmmError::InvalidFee),
+        ConfigChange::TimelockDelay { timelock_delay } => require!(
+            (0..=MAX_TIMELOCK_DELAY).contains(&timelock_delay),
+            AmmError::InvalidTimelockDelay
+        ),
+        _ => {}
+    }
+
+    if let Some(target) = change.pool() {
+        let pool = pool.as_ref().ok_or(AmmError::PoolMismatch)?;
+        require_keys_eq!(pool.key(), target, AmmError::PoolMismatch);
+    }
+
+    let effective_at = Clock::get()?
+        .unix_timestamp
+        .checked_add(amm.timelock_delay)
+        .ok_or(AmmError::MathOverflow)?;
+    let queue = amm.config_change_queue(pool.map(|pool| &mut **pool), change.kind())?;
+    require!(
+        PendingConfigChange::position(queue, change.kind()).is_err(),
+        AmmError::ConfigChangeAlreadyQueued
+    );
+    queue.push(PendingConfigChange {
+        change,
+        effective_at,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateAmmConfig<'info> {
+    #[account(
+        mut,
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    /// Only needed for changes that target a pool
+    #[account(
+        mut,
+        seeds = [AMM_POOL_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
+        bump,
+        has_one = amm,
+    )]
+    pub pool: Option<Account<'info, AmmPool>>,
+
+    /// CHECK: Wallet or `Multisig` holding the role of the change, its approval is checked by `authorize`
+    pub authority: UncheckedAccount<'info>,
+}
@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+use crate::states::{AmmPool, Dca, DCA_SEED};
+use crate::utils::transfer_checked_with_hook;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
+
+/// Sends the proceeds collected so far to the owner, the DCA keeps running. Accounts of a transfer hook of the output
+/// mint go into `remaining_accounts`.
+pub fn withdraw_dca<'info>(ctx: Context<'_, '_, 'info, 'info, WithdrawDca<'info>>) -> Result<()> {
+    let dca = &ctx.accounts.dca;
+    let dca_signer_seeds: &[&[&[u8]]] = &[&[
+        DCA_SEED.as_bytes(),
+        dca.pool.as_ref(),
+        dca.owner.as_ref(),
+        &dca.id.to_le_bytes(),
+        &[ctx.bumps.dca],
+    ]];
+
+    let output_mint = &ctx.accounts.output_mint;
+    let cpi_accounts = TransferChecked {
+        mint: output_mint.to_account_info(),
+        from: ctx.accounts.output_vault.to_account_info(),
+        to: ctx.accounts.owner_output_account.to_account_info(),
+        authority: dca.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.output_token_program.to_account_info();
+    let cpi_context = CpiContext::new(cpi_program, cpi_accounts)
+        .with_signer(dca_signer_seeds)
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+    transfer_checked_with_hook(
+        cpi_context,
+        ctx.accounts.output_vault.amount,
+        output_mint.decimals,
+    )
+}
+
+#[derive(Accounts)]
+pub struct WithdrawDca<'info> {
+    #[account(
+        seeds = [DCA_SEED.as_bytes(), dca.pool.as_ref(), owner.key().as_ref(), dca.id.to_le_bytes().as_ref()],
+        bump,
+        has_one = pool,
+        has_one = owner,
+    )]
+    pub dca: Box<Account<'info, Dca>>,
+
+    pub pool: Box<Account<'info, AmmPool>>,
+
+    /// `pool.mint_b` when the DCA sells A, `pool.mint_a` otherwise
+    #[account(
+        constraint = output_mint.key() == if dca.is_swap_a { pool.mint_b } else { pool.mint_a } @ AmmError::InvalidDcaMint,
+        mint::token_program = output_token_program,
+    )]
+    pub output_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = output_mint,
+        associated_token::token_program = output_token_program,
+        associated_token::authority = dca,
+    )]
+    pub output_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = output_mint,
+        associated_token::token_program = output_token_program,
+        associated_token::authority = owner,
+    )]
+    pub owner_output_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub output_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
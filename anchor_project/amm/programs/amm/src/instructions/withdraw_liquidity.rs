@@ -1,12 +1,31 @@
-use crate::states::{AmmPool, AMM_MINT_LIQUIDITY_SEED, AMM_POOL_AUTHORITY_SEED, AMM_POOL_SEED};
+use crate::states::{
+    Amm, AmmPool, Observations, AMM_MINT_LIQUIDITY_SEED, AMM_POOL_AUTHORITY_SEED, AMM_POOL_SEED,
+    AMM_SEED, OBSERVATIONS_SEED, WSOL_SEED,
+};
+use crate::twamm::execute_virtual_orders;
+use crate::utils::{check_deadline, native_side, transfer_checked_with_hook, unwrap_sol};
 use anchor_lang::prelude::*;
 
 use crate::errors::AmmError;
 use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token::{burn, transfer_checked, Burn, Mint, Token, TokenAccount, TransferChecked};
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{
+    burn, Burn, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
 
-pub fn withdraw_liquidity(ctx: Context<WithdrawLiquidity>, lp_amount_to_burn: u64) -> Result<()> {
+/// Burns LP tokens for a share of both reserves, hooked mints take their hook accounts from `remaining_accounts`
+pub fn withdraw_liquidity<'info>(
+    ctx: Context<'_, '_, 'info, 'info, WithdrawLiquidity<'info>>,
+    lp_amount_to_burn: u64,
+    deadline: Option<i64>,
+    native_sol: bool,
+) -> Result<()> {
+    check_deadline(deadline)?;
     require!(lp_amount_to_burn > 0, AmmError::AmountIsZero);
+    require!(
+        !ctx.accounts.pool.flash_loan_active,
+        AmmError::FlashLoanActive
+    );
     let total_lp = ctx.accounts.mint_liquidity.supply;
     require!(total_lp > 0, AmmError::LpSupplyIsZero);
 
@@ -18,13 +37,25 @@ pub fn withdraw_liquidity(ctx: Context<WithdrawLiquidity>, lp_amount_to_burn: u6
         AmmError::InsufficientLpBalance
     );
 
+    let now = Clock::get()?.unix_timestamp;
+    execute_virtual_orders(
+        &mut ctx.accounts.pool,
+        &ctx.accounts.amm,
+        ctx.accounts.pool_account_a.amount,
+        ctx.accounts.pool_account_b.amount,
+        now,
+    )?;
+
     let pool_a = &ctx.accounts.pool_account_a;
     let pool_b = &ctx.accounts.pool_account_b;
 
-    let reserve_a = pool_a.amount;
-    let reserve_b = pool_b.amount;
+    let (reserve_a, reserve_b) = ctx.accounts.pool.reserves(pool_a.amount, pool_b.amount)?;
+    ctx.accounts
+        .pool
+        .update_price_accumulators(reserve_a, reserve_b, now);
+    ctx.accounts.observations.record(&ctx.accounts.pool);
 
-    require!(reserve_b > 0 && reserve_b > 0, AmmError::InvalidPoolState);
+    require!(reserve_a > 0 && reserve_b > 0, AmmError::InvalidPoolState);
     let (amount_a_out, amount_b_out) =
         calculate_out_amounts(lp_amount_to_burn, total_lp, reserve_a, reserve_b)?;
 
@@ -43,41 +74,79 @@ pub fn withdraw_liquidity(ctx: Context<WithdrawLiquidity>, lp_amount_to_burn: u6
 
     let authority = &ctx.accounts.authority;
     let authority_signer_seeds: &[&[&[u8]]] = &[&[
-        &AMM_POOL_AUTHORITY_SEED.as_bytes(),
+        AMM_POOL_AUTHORITY_SEED.as_bytes(),
         &ctx.accounts.pool.amm.to_bytes(),
         &ctx.accounts.mint_a.key().to_bytes(),
         &ctx.accounts.mint_b.key().to_bytes(),
+        &ctx.accounts.pool.fee_tier.to_le_bytes(),
         &[ctx.bumps.authority],
     ]];
 
-    // withdraw amount_a_out
+    // withdraw amount_a_out, a transfer fee is taken from what the recipient gets
     let mint_a = &ctx.accounts.mint_a;
     let cpi_accounts = TransferChecked {
         mint: mint_a.to_account_info(),
         from: pool_a.to_account_info(),
-        to: ctx.accounts.depositor_account_a.to_account_info(),
+        to: ctx.accounts.recipient_account_a.to_account_info(),
         authority: authority.to_account_info(),
     };
-    let cpi_context = CpiContext::new(cpi_program.to_account_info(), cpi_accounts)
-        .with_signer(authority_signer_seeds);
-    transfer_checked(cpi_context, amount_a_out, mint_a.decimals)?;
+    let cpi_context = CpiContext::new(ctx.accounts.token_program_a.to_account_info(), cpi_accounts)
+        .with_signer(authority_signer_seeds)
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+    transfer_checked_with_hook(cpi_context, amount_a_out, mint_a.decimals)?;
 
     // withdraw amount_b_out
     let mint_b = &ctx.accounts.mint_b;
     let cpi_accounts = TransferChecked {
         mint: mint_b.to_account_info(),
         from: pool_b.to_account_info(),
-        to: ctx.accounts.depositor_account_b.to_account_info(),
+        to: ctx.accounts.recipient_account_b.to_account_info(),
         authority: authority.to_account_info(),
     };
-    let cpi_context = CpiContext::new(cpi_program.to_account_info(), cpi_accounts)
-        .with_signer(authority_signer_seeds);
-    transfer_checked(cpi_context, amount_b_out, mint_b.decimals)?;
+    let cpi_context = CpiContext::new(ctx.accounts.token_program_b.to_account_info(), cpi_accounts)
+        .with_signer(authority_signer_seeds)
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+    transfer_checked_with_hook(cpi_context, amount_b_out, mint_b.decimals)?;
+
+    // only the withdrawn native side is unwrapped, the depositor has to sign for it leaving its account
+    if native_sol {
+        require_keys_eq!(
+            ctx.accounts.recipient.key(),
+            depositor.key(),
+            AmmError::InvalidNativeSolRecipient
+        );
+        let (recipient_native, native_mint, amount_out, token_program) =
+            if native_side(mint_a.key(), mint_b.key())? {
+                (
+                    &ctx.accounts.recipient_account_a,
+                    mint_a,
+                    amount_a_out,
+                    &ctx.accounts.token_program_a,
+                )
+            } else {
+                (
+                    &ctx.accounts.recipient_account_b,
+                    mint_b,
+                    amount_b_out,
+                    &ctx.accounts.token_program_b,
+                )
+            };
+        unwrap_sol(
+            depositor.to_account_info(),
+            recipient_native.to_account_info(),
+            ctx.accounts.temporary_wsol_account.to_account_info(),
+            ctx.bumps.temporary_wsol_account,
+            native_mint.to_account_info(),
+            amount_out,
+            ctx.accounts.system_program.to_account_info(),
+            token_program.to_account_info(),
+        )?;
+    }
 
     Ok(())
 }
 
-fn calculate_out_amounts(
+pub(crate) fn calculate_out_amounts(
     lp_to_burn: u64,
     total_lp: u64,
     reserve_a: u64,
@@ -99,76 +168,113 @@ fn calculate_out_amounts(
 #[derive(Accounts)]
 pub struct WithdrawLiquidity<'info> {
     #[account(
-        seeds = [AMM_POOL_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref()],
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub amm: Box<Account<'info, Amm>>,
+
+    #[account(
+        mut,
+        seeds = [AMM_POOL_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
         bump,
         has_one = mint_a,
         has_one = mint_b,
+        has_one = amm,
+        has_one = token_program_a,
+        has_one = token_program_b,
     )]
     pub pool: Account<'info, AmmPool>,
 
+    #[account(
+        mut,
+        seeds = [OBSERVATIONS_SEED.as_bytes(), pool.key().as_ref()],
+        bump,
+        has_one = pool,
+    )]
+    pub observations: Box<Account<'info, Observations>>,
+
     /// CHECK readonly
     #[account(
-        seeds=[AMM_POOL_AUTHORITY_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref()],
+        seeds=[AMM_POOL_AUTHORITY_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
         bump,
     )]
     pub authority: AccountInfo<'info>,
 
+    /// Receives the lamports when `native_sol` is set
+    #[account(mut)]
     pub depositor: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [AMM_MINT_LIQUIDITY_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref()],
+        seeds = [AMM_MINT_LIQUIDITY_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
         bump,
     )]
-    pub mint_liquidity: Box<Account<'info, Mint>>,
+    pub mint_liquidity: Box<InterfaceAccount<'info, Mint>>,
 
-    #[account(mut)]
-    pub mint_a: Box<Account<'info, Mint>>,
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
 
-    #[account(mut)]
-    pub mint_b: Box<Account<'info, Mint>>,
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
 
     #[account(
         mut,
         associated_token::mint = mint_a,
+        associated_token::token_program = token_program_a,
         associated_token::authority = authority
     )]
-    pub pool_account_a: Box<Account<'info, TokenAccount>>,
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
         associated_token::mint = mint_b,
+        associated_token::token_program = token_program_b,
         associated_token::authority = authority
     )]
-    pub pool_account_b: Box<Account<'info, TokenAccount>>,
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
         associated_token::mint = mint_liquidity,
         associated_token::authority = depositor,
     )]
-    pub depositor_account_liquidity: Box<Account<'info, TokenAccount>>,
+    pub depositor_account_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: only used as the owner of the accounts that receive the withdrawn tokens
+    pub recipient: UncheckedAccount<'info>,
 
     #[account(
         init_if_needed,
         payer = payer,
         associated_token::mint = mint_a,
-        associated_token::authority = depositor,
+        associated_token::token_program = token_program_a,
+        associated_token::authority = recipient,
     )]
-    pub depositor_account_a: Box<Account<'info, TokenAccount>>,
+    pub recipient_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         init_if_needed,
         payer = payer,
         associated_token::mint = mint_b,
-        associated_token::authority = depositor,
+        associated_token::token_program = token_program_b,
+        associated_token::authority = recipient,
+    )]
+    pub recipient_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Token account at the `["WSOL", depositor]` PDA, created and closed within the instruction to pay the
+    /// native side out as lamports when `native_sol` is set
+    #[account(
+        mut,
+        seeds = [WSOL_SEED.as_bytes(), depositor.key().as_ref()],
+        bump,
     )]
-    pub depositor_account_b: Box<Account<'info, TokenAccount>>,
+    pub temporary_wsol_account: UncheckedAccount<'info>,
 
     #[account(mut)]
     pub payer: Signer<'info>,
 
+    /// Burns the LP token
     pub token_program: Program<'info, Token>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
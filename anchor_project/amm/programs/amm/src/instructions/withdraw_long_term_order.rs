@@ -0,0 +1,246 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+use crate::states::{
+    Amm, AmmPool, LongTermOrder, TwammExpirySnapshot, AMM_POOL_AUTHORITY_SEED, AMM_POOL_SEED,
+    AMM_SEED, LONG_TERM_ORDER_SEED, TWAMM_EXPIRY_SEED,
+};
+use crate::twamm::{execute_virtual_orders, order_earnings, order_refund};
+use crate::utils::transfer_checked_with_hook;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
+
+/// Pays out what a long-term order bought so far and refunds the input it could not sell while the pool had no
+/// liquidity. Once the order expired it is closed. Accounts of transfer hooks of the mints go into
+/// `remaining_accounts`.
+pub fn withdraw_long_term_order<'info>(
+    ctx: Context<'_, '_, 'info, 'info, UpdateLongTermOrder<'info>>,
+) -> Result<()> {
+    let now = ctx.accounts.execute()?;
+    let (proceeds, refund) = ctx.accounts.take_proceeds(now)?;
+    let is_sell_a = ctx.accounts.order.is_sell_a;
+    let bump = ctx.bumps.authority;
+    let hook_accounts = ctx.remaining_accounts;
+    ctx.accounts
+        .pay(!is_sell_a, proceeds, bump, hook_accounts)?;
+    ctx.accounts.pay(is_sell_a, refund, bump, hook_accounts)?;
+
+    if now >= ctx.accounts.order.expiry {
+        ctx.accounts.close()?;
+    }
+    Ok(())
+}
+
+impl<'info> UpdateLongTermOrder<'info> {
+    /// Executes the orders of the pool up to now, so the order and its pool agree on what was sold, and
+    /// returns the current timestamp
+    pub(crate) fn execute(&mut self) -> Result<i64> {
+        require!(!self.pool.flash_loan_active, AmmError::FlashLoanActive);
+        let now = Clock::get()?.unix_timestamp;
+        execute_virtual_orders(
+            &mut self.pool,
+            &self.amm,
+            self.pool_account_a.amount,
+            self.pool_account_b.amount,
+            now,
+        )?;
+        Ok(now)
+    }
+
+    /// Marks the output earned and the input left idle since the last payout as paid and returns both, an expired
+    /// order counts up to its expiry, read from the snapshot once the expiry was pruned
+    pub(crate) fn take_proceeds(&mut self, now: i64) -> Result<(u64, u64)> {
+        let order = &mut self.order;
+        let twamm = &mut self.pool.twamm;
+        let (earned_x64, idle_seconds) = if now >= order.expiry {
+            match (twamm.expiry(order.expiry), &self.expiry_snapshot) {
+                (Some(expiry), _) => (
+                    expiry.earnings_per_rate(order.is_sell_a),
+                    expiry.idle_seconds,
+                ),
+                (None, Some(snapshot)) => (
+                    snapshot.earnings_per_rate(order.is_sell_a),
+                    snapshot.idle_seconds,
+                ),
+                (None, None) => return err!(AmmError::TwammExpiryNotFound),
+            }
+        } else {
+            (twamm.earnings_per_rate(order.is_sell_a), twamm.idle_seconds)
+        };
+        let proceeds = order_earnings(order.sell_rate, order.earnings_per_rate_x64, earned_x64)?;
+        order.earnings_per_rate_x64 = earned_x64;
+        let refund = order_refund(order.sell_rate, order.idle_seconds, idle_seconds)?;
+        order.idle_seconds = idle_seconds;
+
+        let balance = twamm.balance(!order.is_sell_a);
+        *balance = balance
+            .checked_sub(proceeds)
+            .ok_or(AmmError::MathOverflow)?;
+        let balance = twamm.balance(order.is_sell_a);
+        *balance = balance.checked_sub(refund).ok_or(AmmError::MathOverflow)?;
+        Ok((proceeds, refund))
+    }
+
+    /// Sends `amount` of token A or B from the pool to the owner, who bears a transfer fee of the mint. A transfer
+    /// hook of the mint runs with `hook_accounts`.
+    pub(crate) fn pay(
+        &self,
+        is_a: bool,
+        amount: u64,
+        authority_bump: u8,
+        hook_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        let (mint, from, to, token_program) = if is_a {
+            (
+                &self.mint_a,
+                &self.pool_account_a,
+                &self.owner_account_a,
+                &self.token_program_a,
+            )
+        } else {
+            (
+                &self.mint_b,
+                &self.pool_account_b,
+                &self.owner_account_b,
+                &self.token_program_b,
+            )
+        };
+        let authority_signer_seeds: &[&[&[u8]]] = &[&[
+            AMM_POOL_AUTHORITY_SEED.as_bytes(),
+            &self.pool.amm.to_bytes(),
+            &self.mint_a.key().to_bytes(),
+            &self.mint_b.key().to_bytes(),
+            &self.pool.fee_tier.to_le_bytes(),
+            &[authority_bump],
+        ]];
+        let cpi_accounts = TransferChecked {
+            mint: mint.to_account_info(),
+            from: from.to_account_info(),
+            to: to.to_account_info(),
+            authority: self.authority.to_account_info(),
+        };
+        let cpi_context = CpiContext::new(token_program.to_account_info(), cpi_accounts)
+            .with_signer(authority_signer_seeds)
+            .with_remaining_accounts(hook_accounts.to_vec());
+        transfer_checked_with_hook(cpi_context, amount, mint.decimals)
+    }
+
+    /// Closes the order, its rent goes back to the owner. The snapshot of a pruned expiry is closed with its last
+    /// order.
+    pub(crate) fn close(&mut self) -> Result<()> {
+        let expiry = self.order.expiry;
+        if self.pool.twamm.expiry(expiry).is_some() {
+            self.pool.twamm.close_order(expiry);
+        } else if let Some(snapshot) = self.expiry_snapshot.as_mut() {
+            snapshot.orders -= 1;
+            if snapshot.orders == 0 {
+                let payer = self
+                    .snapshot_payer
+                    .as_ref()
+                    .filter(|payer| payer.key() == snapshot.payer)
+                    .ok_or(AmmError::InvalidSnapshotPayer)?;
+                snapshot.close(payer.to_account_info())?;
+            }
+        }
+        self.order.close(self.owner.to_account_info())
+    }
+}
+
+/// Accounts of `withdraw_long_term_order` and `cancel_long_term_order`
+#[derive(Accounts)]
+pub struct UpdateLongTermOrder<'info> {
+    #[account(
+        seeds = [AMM_SEED.as_bytes(), amm.index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub amm: Box<Account<'info, Amm>>,
+
+    #[account(
+        mut,
+        seeds = [AMM_POOL_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
+        bump,
+        has_one = mint_a,
+        has_one = mint_b,
+        has_one = amm,
+        has_one = token_program_a,
+        has_one = token_program_b,
+    )]
+    pub pool: Box<Account<'info, AmmPool>>,
+
+    /// CHECK: readonly
+    #[account(
+        seeds=[AMM_POOL_AUTHORITY_SEED.as_bytes(), pool.amm.as_ref(), pool.mint_a.key().as_ref(), pool.mint_b.key().as_ref(), pool.fee_tier.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub authority: AccountInfo<'info>,
+
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::token_program = token_program_a,
+        associated_token::authority = authority,
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::token_program = token_program_b,
+        associated_token::authority = authority,
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [LONG_TERM_ORDER_SEED.as_bytes(), order.pool.as_ref(), owner.key().as_ref(), order.id.to_le_bytes().as_ref()],
+        bump,
+        has_one = pool,
+        has_one = owner,
+    )]
+    pub order: Box<Account<'info, LongTermOrder>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_a,
+        associated_token::token_program = token_program_a,
+        associated_token::authority = owner,
+    )]
+    pub owner_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_b,
+        associated_token::token_program = token_program_b,
+        associated_token::authority = owner,
+    )]
+    pub owner_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Earnings of the order expiry once `prune_twamm_expiry` moved it out of the pool
+    #[account(
+        mut,
+        seeds = [TWAMM_EXPIRY_SEED.as_bytes(), pool.key().as_ref(), order.expiry.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub expiry_snapshot: Option<Box<Account<'info, TwammExpirySnapshot>>>,
+
+    /// CHECK: Must be `expiry_snapshot.payer`, only needed when the last order of a pruned expiry is closed
+    #[account(mut)]
+    pub snapshot_payer: Option<UncheckedAccount<'info>>,
+
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
@@ -1,8 +1,13 @@
 use anchor_lang::prelude::*;
 
+mod auth;
 mod errors;
 pub mod instructions;
 pub mod states;
+mod twamm;
+mod utils;
+
+use states::{ConfigChange, ConfigChangeKind, MintPolicy, Role};
 
 declare_id!("264uMZcS5Mcpe5EzAP6P2SoGQE4j7KtpSe6U8mSQZeAN");
 
@@ -11,28 +16,319 @@ pub mod amm {
     pub use super::instructions::*;
     use super::*;
 
-    pub fn create_amm(ctx: Context<CreateAmm>, fee: u16, index: u16) -> Result<()> {
-        instructions::create_amm(ctx, fee, index)
+    pub fn create_amm(
+        ctx: Context<CreateAmm>,
+        fee: u16,
+        index: u16,
+        timelock_delay: i64,
+    ) -> Result<()> {
+        instructions::create_amm(ctx, fee, index, timelock_delay)
+    }
+
+    pub fn create_pool(
+        ctx: Context<CreatePool>,
+        fee_tier: u16,
+        fee_bps: Option<u16>,
+    ) -> Result<()> {
+        instructions::create_pool(ctx, fee_tier, fee_bps)
     }
 
-    pub fn create_pool(ctx: Context<CreatePool>) -> Result<()> {
-        instructions::create_pool(ctx)
+    pub fn add_liquidity<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AddLiquidity<'info>>,
+        amount_a: u64,
+        amount_b: u64,
+        deadline: Option<i64>,
+        native_sol: bool,
+    ) -> Result<()> {
+        instructions::add_liquidity(ctx, amount_a, amount_b, deadline, native_sol)
     }
 
-    pub fn add_liquidity(ctx: Context<AddLiquidity>, amount_a: u64, amount_b: u64) -> Result<()> {
-        instructions::add_liquidity(ctx, amount_a, amount_b)
+    pub fn withdraw_liquidity<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WithdrawLiquidity<'info>>,
+        amount: u64,
+        deadline: Option<i64>,
+        native_sol: bool,
+    ) -> Result<()> {
+        instructions::withdraw_liquidity(ctx, amount, deadline, native_sol)
     }
 
-    pub fn withdraw_liquidity(ctx: Context<WithdrawLiquidity>, amount: u64) -> Result<()> {
-        instructions::withdraw_liquidity(ctx, amount)
+    pub fn swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Swap<'info>>,
+        is_swap_a: bool,
+        amount: u64,
+        min_out_amount: u64,
+        deadline: Option<i64>,
+        native_sol: bool,
+    ) -> Result<()> {
+        instructions::swap(ctx, is_swap_a, amount, min_out_amount, deadline, native_sol)
     }
 
-    pub fn swap(
-        ctx: Context<Swap>,
+    pub fn swap_exact_out<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Swap<'info>>,
         is_swap_a: bool,
+        amount_out: u64,
+        max_amount_in: u64,
+        deadline: Option<i64>,
+        native_sol: bool,
+    ) -> Result<()> {
+        instructions::swap_exact_out(
+            ctx,
+            is_swap_a,
+            amount_out,
+            max_amount_in,
+            deadline,
+            native_sol,
+        )
+    }
+
+    pub fn swap_route<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SwapRoute<'info>>,
         amount: u64,
         min_out_amount: u64,
+        hops: u8,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        instructions::swap_route(ctx, amount, min_out_amount, hops, deadline)
+    }
+
+    pub fn flash_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FlashSwap<'info>>,
+        amount_a_out: u64,
+        amount_b_out: u64,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::flash_swap(ctx, amount_a_out, amount_b_out, data)
+    }
+
+    pub fn flash_borrow<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FlashLoan<'info>>,
+        is_a: bool,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::flash_borrow(ctx, is_a, amount)
+    }
+
+    pub fn flash_repay<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FlashLoan<'info>>,
+        is_a: bool,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::flash_repay(ctx, is_a, amount)
+    }
+
+    pub fn place_order<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PlaceOrder<'info>>,
+        id: u64,
+        params: OrderParams,
+    ) -> Result<()> {
+        instructions::place_order(ctx, id, params)
+    }
+
+    pub fn fill_order<'info>(ctx: Context<'_, '_, 'info, 'info, FillOrder<'info>>) -> Result<()> {
+        instructions::fill_order(ctx)
+    }
+
+    pub fn cancel_order<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CancelOrder<'info>>,
+    ) -> Result<()> {
+        instructions::cancel_order(ctx)
+    }
+
+    pub fn create_dca<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreateDca<'info>>,
+        id: u64,
+        params: DcaParams,
+    ) -> Result<()> {
+        instructions::create_dca(ctx, id, params)
+    }
+
+    pub fn execute_dca<'info>(ctx: Context<'_, '_, 'info, 'info, ExecuteDca<'info>>) -> Result<()> {
+        instructions::execute_dca(ctx)
+    }
+
+    pub fn withdraw_dca<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WithdrawDca<'info>>,
+    ) -> Result<()> {
+        instructions::withdraw_dca(ctx)
+    }
+
+    pub fn close_dca<'info>(ctx: Context<'_, '_, 'info, 'info, CloseDca<'info>>) -> Result<()> {
+        instructions::close_dca(ctx)
+    }
+
+    pub fn place_long_term_order<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PlaceLongTermOrder<'info>>,
+        id: u64,
+        is_sell_a: bool,
+        amount: u64,
+        intervals: u32,
+    ) -> Result<()> {
+        instructions::place_long_term_order(ctx, id, is_sell_a, amount, intervals)
+    }
+
+    pub fn withdraw_long_term_order<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UpdateLongTermOrder<'info>>,
+    ) -> Result<()> {
+        instructions::withdraw_long_term_order(ctx)
+    }
+
+    pub fn cancel_long_term_order<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UpdateLongTermOrder<'info>>,
+    ) -> Result<()> {
+        instructions::cancel_long_term_order(ctx)
+    }
+
+    pub fn prune_twamm_expiry(ctx: Context<PruneTwammExpiry>, timestamp: i64) -> Result<()> {
+        instructions::prune_twamm_expiry(ctx, timestamp)
+    }
+
+    pub fn quote_swap(ctx: Context<QuotePool>, is_swap_a: bool, amount: u64) -> Result<SwapQuote> {
+        instructions::quote_swap(ctx, is_swap_a, amount)
+    }
+
+    pub fn quote_swap_exact_out(
+        ctx: Context<QuotePool>,
+        is_swap_a: bool,
+        amount_out: u64,
+    ) -> Result<SwapQuote> {
+        instructions::quote_swap_exact_out(ctx, is_swap_a, amount_out)
+    }
+
+    pub fn preview_add_liquidity(
+        ctx: Context<QuotePool>,
+        amount_a: u64,
+        amount_b: u64,
+    ) -> Result<AddLiquidityPreview> {
+        instructions::preview_add_liquidity(ctx, amount_a, amount_b)
+    }
+
+    pub fn preview_withdraw(ctx: Context<QuotePool>, lp_amount: u64) -> Result<WithdrawPreview> {
+        instructions::preview_withdraw(ctx, lp_amount)
+    }
+
+    pub fn consult(ctx: Context<Consult>, window_seconds: u32) -> Result<TwapPrice> {
+        instructions::consult(ctx, window_seconds)
+    }
+
+    pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, change: ConfigChange) -> Result<()> {
+        instructions::update_amm_config(ctx, change)
+    }
+
+    pub fn execute_config_change(
+        ctx: Context<ExecuteConfigChange>,
+        kind: ConfigChangeKind,
+    ) -> Result<()> {
+        instructions::execute_config_change(ctx, kind)
+    }
+
+    pub fn cancel_config_change(
+        ctx: Context<CancelConfigChange>,
+        kind: ConfigChangeKind,
+    ) -> Result<()> {
+        instructions::cancel_config_change(ctx, kind)
+    }
+
+    pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+        instructions::propose_admin(ctx, new_admin)
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        instructions::accept_admin(ctx)
+    }
+
+    pub fn cancel_admin_transfer(ctx: Context<CancelAdminTransfer>) -> Result<()> {
+        instructions::cancel_admin_transfer(ctx)
+    }
+
+    pub fn pause(ctx: Context<Pause>) -> Result<()> {
+        instructions::pause(ctx)
+    }
+
+    pub fn pause_pool(ctx: Context<PausePool>) -> Result<()> {
+        instructions::pause_pool(ctx)
+    }
+
+    pub fn set_pool_fee(ctx: Context<SetPoolFee>, fee_bps: Option<u16>) -> Result<()> {
+        instructions::set_pool_fee(ctx, fee_bps)
+    }
+
+    pub fn add_fee_tier(ctx: Context<AddFeeTier>, fee_tier: u16) -> Result<()> {
+        instructions::add_fee_tier(ctx, fee_tier)
+    }
+
+    pub fn remove_fee_tier(ctx: Context<RemoveFeeTier>, fee_tier: u16) -> Result<()> {
+        instructions::remove_fee_tier(ctx, fee_tier)
+    }
+
+    pub fn set_permissioned_pool_creation(
+        ctx: Context<SetPermissionedPoolCreation>,
+        permissioned: bool,
+    ) -> Result<()> {
+        instructions::set_permissioned_pool_creation(ctx, permissioned)
+    }
+
+    pub fn add_pool_creator(ctx: Context<AddPoolCreator>, creator: Pubkey) -> Result<()> {
+        instructions::add_pool_creator(ctx, creator)
+    }
+
+    pub fn remove_pool_creator(ctx: Context<RemovePoolCreator>) -> Result<()> {
+        instructions::remove_pool_creator(ctx)
+    }
+
+    pub fn set_mint_policy(ctx: Context<SetMintPolicy>, mint_policy: MintPolicy) -> Result<()> {
+        instructions::set_mint_policy(ctx, mint_policy)
+    }
+
+    pub fn set_mint_entry(ctx: Context<SetMintEntry>, allowed: bool) -> Result<()> {
+        instructions::set_mint_entry(ctx, allowed)
+    }
+
+    pub fn remove_mint_entry(ctx: Context<RemoveMintEntry>) -> Result<()> {
+        instructions::remove_mint_entry(ctx)
+    }
+
+    pub fn add_transfer_hook_program(
+        ctx: Context<AddTransferHookProgram>,
+        program_id: Pubkey,
+    ) -> Result<()> {
+        instructions::add_transfer_hook_program(ctx, program_id)
+    }
+
+    pub fn remove_transfer_hook_program(
+        ctx: Context<RemoveTransferHookProgram>,
+        program_id: Pubkey,
+    ) -> Result<()> {
+        instructions::remove_transfer_hook_program(ctx, program_id)
+    }
+
+    pub fn set_role(ctx: Context<SetRole>, role: Role, key: Pubkey) -> Result<()> {
+        instructions::set_role(ctx, role, key)
+    }
+
+    pub fn set_protocol_fee(ctx: Context<SetProtocolFee>, protocol_fee_bps: u16) -> Result<()> {
+        instructions::set_protocol_fee(ctx, protocol_fee_bps)
+    }
+
+    pub fn collect_protocol_fees<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CollectProtocolFees<'info>>,
+    ) -> Result<()> {
+        instructions::collect_protocol_fees(ctx)
+    }
+    pub fn create_multisig(
+        ctx: Context<CreateMultisig>,
+        create_key: Pubkey,
+        amm: Pubkey,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        instructions::create_multisig(ctx, create_key, amm, signers, threshold)
+    }
+
+    pub fn set_multisig_config(
+        ctx: Context<SetMultisigConfig>,
+        signers: Vec<Pubkey>,
+        threshold: u8,
     ) -> Result<()> {
-        instructions::swap(ctx, is_swap_a, amount, min_out_amount)
+        instructions::set_multisig_config(ctx, signers, threshold)
     }
 }
@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 
+use crate::errors::AmmError;
+
 pub const AMM_SEED: &str = "AMM";
 
 pub const AMM_POOL_SEED: &str = "AMM_POOL";
@@ -7,8 +9,53 @@ pub const AMM_POOL_SEED: &str = "AMM_POOL";
 pub const AMM_POOL_AUTHORITY_SEED: &str = "AMM_POOL_AUTHORITY";
 pub const AMM_MINT_LIQUIDITY_SEED: &str = "AMM_MINT_LIQUIDITY";
 
+pub const POOL_CREATOR_SEED: &str = "POOL_CREATOR";
+pub const MINT_ENTRY_SEED: &str = "MINT_ENTRY";
+pub const MULTISIG_SEED: &str = "MULTISIG";
+pub const OBSERVATIONS_SEED: &str = "OBSERVATIONS";
+pub const ORDER_SEED: &str = "ORDER";
+pub const DCA_SEED: &str = "DCA";
+pub const LONG_TERM_ORDER_SEED: &str = "LONG_TERM_ORDER";
+pub const TWAMM_EXPIRY_SEED: &str = "TWAMM_EXPIRY";
+pub const WSOL_SEED: &str = "WSOL";
+
 pub const MAX_FEE_BPS: u16 = 10_000;
 
+/// Fee tier of pools that charge `Amm.fee` (or their own override) instead of a registered tier
+pub const DEFAULT_FEE_TIER: u16 = 0;
+pub const MAX_FEE_TIERS: usize = 8;
+
+pub const MAX_TRANSFER_HOOK_PROGRAMS: usize = 8;
+
+pub const MAX_MULTISIG_SIGNERS: usize = 10;
+
+/// Upper bound of `Amm.timelock_delay`, 30 days
+pub const MAX_TIMELOCK_DELAY: i64 = 30 * 24 * 60 * 60;
+/// Number of `ConfigChangeKind`s of the whole AMM, each one has its own pending slot on the `Amm`
+pub const AMM_CONFIG_CHANGE_KINDS: usize = 6;
+/// Number of `ConfigChangeKind`s that target a pool, each one has its own pending slot on the `AmmPool`
+pub const POOL_CONFIG_CHANGE_KINDS: usize = 2;
+
+/// Size of the observation ring buffer of a pool
+pub const MAX_OBSERVATIONS: usize = 64;
+/// Minimum seconds between two observations of a pool, the buffer covers `MAX_OBSERVATIONS - 1` periods or more
+/// however often the pool is used
+pub const OBSERVATION_PERIOD: i64 = 60;
+/// Longest TWAP window the observation buffer of a traded pool always reaches back to
+pub const MAX_TWAP_WINDOW: u32 = (MAX_OBSERVATIONS as u32 - 1) * OBSERVATION_PERIOD as u32;
+/// Upper bound of the share of each DCA slice paid to the keeper, 1%
+pub const MAX_DCA_KEEPER_FEE_BPS: u16 = 100;
+
+/// Long-term orders expire on multiples of this many seconds, so orders share expiries
+pub const TWAMM_INTERVAL: i64 = 60 * 60;
+/// Long-term orders expire within this many intervals, so a pool never has more expiries ahead of it
+pub const MAX_TWAMM_INTERVALS: u32 = 24;
+/// Upper bound of distinct expiries with open orders on a pool, the slots beyond `MAX_TWAMM_INTERVALS` hold crossed
+/// expiries until their orders are closed or `prune_twamm_expiry` moves them out
+pub const MAX_TWAMM_EXPIRIES: usize = 32;
+/// Smallest share of the input reserve, in bps, a long-term order has to sell per interval
+pub const MIN_TWAMM_ORDER_BPS: u64 = 10;
+
 #[account]
 #[derive(InitSpace)]
 pub struct Amm {
@@ -17,6 +64,117 @@ pub struct Amm {
     pub index: u16,
 
     pub fee: u16,
+
+    pub pending_admin: Option<Pubkey>,
+
+    pub paused: bool,
+
+    /// Share of the swap fee, in bps of the fee, that is set aside for the protocol
+    pub protocol_fee_bps: u16,
+
+    /// Receives the protocol fees
+    pub treasury: Pubkey,
+
+    /// Queues fee changes and manages fee tiers
+    pub fee_manager: Pubkey,
+
+    /// Pauses the AMM and pools, and queues pause lifts
+    pub pauser: Pubkey,
+
+    /// Manages pool creators and the mint registry
+    pub pool_curator: Pubkey,
+
+    /// Fee tiers, in bps, that pools of this AMM can be created with
+    #[max_len(MAX_FEE_TIERS)]
+    pub fee_tiers: Vec<u16>,
+
+    /// When set, only the admin and allowlisted creators can call `create_pool`
+    pub permissioned_pool_creation: bool,
+
+    pub mint_policy: MintPolicy,
+
+    /// Seconds between queueing a config change and being able to execute it
+    pub timelock_delay: i64,
+
+    /// Queued config changes, at most one per `ConfigChangeKind` so that a change of one role never holds up
+    /// another. Changes that target a pool are queued on the pool.
+    #[max_len(AMM_CONFIG_CHANGE_KINDS)]
+    pub pending_config_changes: Vec<PendingConfigChange>,
+
+    /// Transfer hook programs that Token-2022 mints of new pools may use
+    #[max_len(MAX_TRANSFER_HOOK_PROGRAMS)]
+    pub transfer_hook_programs: Vec<Pubkey>,
+}
+
+/// Keys an `Amm` delegates part of the admin powers to
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Admin,
+    FeeManager,
+    Pauser,
+    Treasury,
+    PoolCurator,
+}
+
+/// Parameter change that only takes effect after the timelock delay of the AMM
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum ConfigChange {
+    Fee {
+        fee: u16,
+    },
+    ProtocolFee {
+        protocol_fee_bps: u16,
+    },
+    PoolFee {
+        pool: Pubkey,
+        fee_bps: Option<u16>,
+    },
+    /// Sets `Amm.pending_admin`, the new admin still has to accept
+    Admin {
+        new_admin: Pubkey,
+    },
+    Unpause,
+    UnpausePool {
+        pool: Pubkey,
+    },
+    TimelockDelay {
+        timelock_delay: i64,
+    },
+    /// Replaces the signer set of `multisig` with the one it holds in `pending_signers` and `pending_threshold`
+    MultisigConfig {
+        multisig: Pubkey,
+    },
+}
+
+/// Variant of a `ConfigChange` without its value, picks the pending slot to execute or cancel
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ConfigChangeKind {
+    Fee,
+    ProtocolFee,
+    PoolFee,
+    Admin,
+    Unpause,
+    UnpausePool,
+    TimelockDelay,
+    MultisigConfig,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct PendingConfigChange {
+    pub change: ConfigChange,
+    /// Unix timestamp from which the change can be executed
+    pub effective_at: i64,
+}
+
+/// Which mints `create_pool` accepts, based on the `MintEntry` registry of the AMM
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum MintPolicy {
+    /// Any mint
+    Open,
+    /// Any mint that is not marked as denied
+    Denylist,
+    /// Only mints marked as allowed
+    Allowlist,
 }
 
 #[account]
@@ -25,4 +183,728 @@ pub struct AmmPool {
     pub amm: Pubkey,
     pub mint_a: Pubkey,
     pub mint_b: Pubkey,
+    /// Token program of each mint, either SPL Token or Token-2022
+    pub token_program_a: Pubkey,
+    pub token_program_b: Pubkey,
+    pub fee_tier: u16,
+
+    pub paused: bool,
+
+    /// Overrides `Amm.fee` for this pool when set
+    pub fee_bps: Option<u16>,
+
+    /// Protocol fees held in the pool accounts that are not collected yet
+    pub protocol_fees_a: u64,
+    pub protocol_fees_b: u64,
+
+    /// Set by `flash_borrow` until the matching `flash_repay`, the reserves are short in between
+    pub flash_loan_active: bool,
+
+    /// Sums of the Q64.64 price of A in B and of B in A times the seconds it held, wrapping on overflow
+    pub price_a_cumulative: u128,
+    pub price_b_cumulative: u128,
+    /// Unix timestamp the accumulators were last brought up to
+    pub last_update_timestamp: i64,
+
+    pub twamm: Twamm,
+
+    /// Queued `PoolFee` and `UnpausePool` changes of this pool, at most one per kind, so that changes of different
+    /// pools never hold up each other
+    #[max_len(POOL_CONFIG_CHANGE_KINDS)]
+    pub pending_config_changes: Vec<PendingConfigChange>,
+}
+
+/// Long-term orders of a pool. Their input is sold at a constant rate every second and executed lazily,
+/// the next time an instruction touches the pool.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, InitSpace)]
+pub struct Twamm {
+    /// Tokens sold per second by all open orders
+    pub sell_rate_a: u64,
+    pub sell_rate_b: u64,
+    /// Output earned per unit of sell rate since the pool was created, Q64.64
+    pub earnings_per_rate_a_x64: u128,
+    pub earnings_per_rate_b_x64: u128,
+    /// Tokens in the pool accounts that belong to orders, unsold input and unclaimed output
+    pub balance_a: u64,
+    pub balance_b: u64,
+    /// Unix timestamp the orders were last executed up to
+    pub last_execution: i64,
+    /// Seconds the orders sold nothing because the pool had no liquidity, their input of that time is refunded
+    pub idle_seconds: u64,
+    /// Sorted by timestamp, kept until every order expiring there is closed
+    #[max_len(MAX_TWAMM_EXPIRIES)]
+    pub expiries: Vec<TwammExpiry>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, InitSpace)]
+pub struct TwammExpiry {
+    pub timestamp: i64,
+    /// Sell rates that end at `timestamp`
+    pub sell_rate_a: u64,
+    pub sell_rate_b: u64,
+    /// Earnings per rate and idle seconds when the expiry was crossed
+    pub earnings_per_rate_a_x64: u128,
+    pub earnings_per_rate_b_x64: u128,
+    pub idle_seconds: u64,
+    /// Open orders that expire at `timestamp`
+    pub orders: u32,
+}
+
+/// Ring buffer of price accumulator snapshots of a pool, read by `consult`
+#[account]
+#[derive(InitSpace)]
+pub struct Observations {
+    pub pool: Pubkey,
+    /// Slot overwritten by the next observation once the buffer is full
+    pub next_index: u16,
+    #[max_len(MAX_OBSERVATIONS)]
+    pub observations: Vec<Observation>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, InitSpace)]
+pub struct Observation {
+    pub timestamp: i64,
+    pub price_a_cumulative: u128,
+    pub price_b_cumulative: u128,
+}
+
+/// Allowlist entry of a key that can create pools on a permissioned AMM
+#[account]
+#[derive(InitSpace)]
+pub struct PoolCreator {
+    pub amm: Pubkey,
+    pub creator: Pubkey,
+}
+
+/// Registry entry that marks a mint as allowed or denied on an AMM
+#[account]
+#[derive(InitSpace)]
+pub struct MintEntry {
+    pub amm: Pubkey,
+    pub mint: Pubkey,
+    pub allowed: bool,
+}
+
+/// M-of-N signer set that can be used as `Amm.admin`
+#[account]
+#[derive(InitSpace)]
+pub struct Multisig {
+    /// Key the PDA is derived from
+    pub create_key: Pubkey,
+
+    /// AMM whose timelock delays changes of the signer set
+    pub amm: Pubkey,
+
+    #[max_len(MAX_MULTISIG_SIGNERS)]
+    pub signers: Vec<Pubkey>,
+
+    pub threshold: u8,
+
+    /// Signer set queued by `set_multisig_config`, applied by the `ConfigChange::MultisigConfig` of the multisig
+    #[max_len(MAX_MULTISIG_SIGNERS)]
+    pub pending_signers: Vec<Pubkey>,
+
+    pub pending_threshold: u8,
+}
+
+/// Swap of escrowed tokens through one pool, executed by anyone once its price condition holds
+#[account]
+#[derive(InitSpace)]
+pub struct Order {
+    pub owner: Pubkey,
+    pub pool: Pubkey,
+    /// Chosen by the owner so one owner can keep several orders on a pool
+    pub id: u64,
+    pub kind: OrderKind,
+    /// Sells token A for token B when set, token B for token A otherwise
+    pub is_swap_a: bool,
+    /// Input sold by the order, the escrow also holds the keeper bounty
+    pub amount: u64,
+    /// Q64.64 price of the input token in the output token the order waits for
+    pub trigger_price_x64: u128,
+    /// Minimum seconds the pool TWAP compared with `trigger_price_x64` averages over
+    pub twap_window: u32,
+    pub min_amount_out: u64,
+    /// Input tokens paid to whoever fills the order
+    pub keeper_bounty: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum OrderKind {
+    /// Fills at the trigger price or better, `min_amount_out` covers at least `amount` at that price
+    Limit,
+    /// Fills once the pool TWAP rises to the trigger price
+    TakeProfit,
+    /// Fills once the pool TWAP falls to the trigger price
+    StopLoss,
+}
+
+/// Deposit sold through one pool in equal slices, one per interval, by whoever calls `execute_dca`
+#[account]
+#[derive(InitSpace)]
+pub struct Dca {
+    pub owner: Pubkey,
+    pub pool: Pubkey,
+    /// Chosen by the owner so one owner can keep several DCAs on a pool
+    pub id: u64,
+    /// Sells token A for token B when set, token B for token A otherwise
+    pub is_swap_a: bool,
+    pub amount_per_period: u64,
+    /// Seconds between two executions
+    pub interval: i64,
+    pub total_periods: u32,
+    pub periods_executed: u32,
+    /// Unix timestamp from which the next slice can be executed
+    pub next_execution: i64,
+    /// Window of the pool TWAP that bounds the output of a slice
+    pub twap_window: u32,
+    /// Largest shortfall of a slice against the TWAP price
+    pub max_slippage_bps: u16,
+    /// Share of each slice paid in the input token to whoever executes it
+    pub keeper_fee_bps: u16,
+}
+
+/// Sells `sell_rate` tokens per second through the pool until `expiry`
+#[account]
+#[derive(InitSpace)]
+pub struct LongTermOrder {
+    pub owner: Pubkey,
+    pub pool: Pubkey,
+    /// Chosen by the owner so one owner can keep several orders on a pool
+    pub id: u64,
+    /// Sells token A for token B when set, token B for token A otherwise
+    pub is_sell_a: bool,
+    pub sell_rate: u64,
+    pub expiry: i64,
+    /// `Twamm` earnings per rate up to which the output was paid out
+    pub earnings_per_rate_x64: u128,
+    /// `Twamm` idle seconds up to which the input was refunded
+    pub idle_seconds: u64,
+}
+
+/// Earnings of a crossed expiry that `prune_twamm_expiry` moved out of its pool
+#[account]
+#[derive(InitSpace)]
+pub struct TwammExpirySnapshot {
+    pub pool: Pubkey,
+    pub timestamp: i64,
+    pub earnings_per_rate_a_x64: u128,
+    pub earnings_per_rate_b_x64: u128,
+    pub idle_seconds: u64,
+    /// Open orders that expired at `timestamp`, the snapshot is closed with the last one
+    pub orders: u32,
+    /// Paid the rent and gets it back once the snapshot is closed
+    pub payer: Pubkey,
+}
+
+impl Amm {
+    pub fn role_key(&self, role: Role) -> Pubkey {
+        match role {
+            Role::Admin => self.admin,
+            Role::FeeManager => self.fee_manager,
+            Role::Pauser => self.pauser,
+            Role::Treasury => self.treasury,
+            Role::PoolCurator => self.pool_curator,
+        }
+    }
+
+    /// Returns the queue that holds changes of `kind`, the one of `pool` for changes that target a pool and the one
+    /// of the AMM otherwise
+    pub fn config_change_queue<'a>(
+        &'a mut self,
+        pool: Option<&'a mut AmmPool>,
+        kind: ConfigChangeKind,
+    ) -> Result<&'a mut Vec<PendingConfigChange>> {
+        if kind.targets_pool() {
+            let pool = pool.ok_or(AmmError::PoolMismatch)?;
+            Ok(&mut pool.pending_config_changes)
+        } else {
+            Ok(&mut self.pending_config_changes)
+        }
+    }
+}
+
+impl PendingConfigChange {
+    /// Returns the position of the queued change of `kind` in `queue`
+    pub fn position(queue: &[PendingConfigChange], kind: ConfigChangeKind) -> Result<usize> {
+        queue
+            .iter()
+            .position(|pending| pending.change.kind() == kind)
+            .ok_or(AmmError::NoPendingConfigChange.into())
+    }
+}
+
+impl ConfigChange {
+    pub fn kind(&self) -> ConfigChangeKind {
+        match self {
+            ConfigChange::Fee { .. } => ConfigChangeKind::Fee,
+            ConfigChange::ProtocolFee { .. } => ConfigChangeKind::ProtocolFee,
+            ConfigChange::PoolFee { .. } => ConfigChangeKind::PoolFee,
+            ConfigChange::Admin { .. } => ConfigChangeKind::Admin,
+            ConfigChange::Unpause => ConfigChangeKind::Unpause,
+            ConfigChange::UnpausePool { .. } => ConfigChangeKind::UnpausePool,
+            ConfigChange::TimelockDelay { .. } => ConfigChangeKind::TimelockDelay,
+            ConfigChange::MultisigConfig { .. } => ConfigChangeKind::MultisigConfig,
+        }
+    }
+
+    /// Returns the key that can queue and cancel the change, the role it belongs to or the multisig it reconfigures
+    pub fn authority(&self, amm: &Amm) -> Pubkey {
+        match self {
+            ConfigChange::Fee { .. }
+            | ConfigChange::ProtocolFee { .. }
+            | ConfigChange::PoolFee { .. } => amm.role_key(Role::FeeManager),
+            ConfigChange::Unpause | ConfigChange::UnpausePool { .. } => amm.role_key(Role::Pauser),
+            ConfigChange::Admin { .. } | ConfigChange::TimelockDelay { .. } => {
+                amm.role_key(Role::Admin)
+            }
+            ConfigChange::MultisigConfig { multisig } => *multisig,
+        }
+    }
+
+    /// Returns the pool the change targets, `None` for changes of the whole AMM
+    pub fn pool(&self) -> Option<Pubkey> {
+        match self {
+            ConfigChange::PoolFee { pool, .. } | ConfigChange::UnpausePool { pool } => Some(*pool),
+            _ => None,
+        }
+    }
+}
+
+impl ConfigChangeKind {
+    /// Whether changes of this kind are queued on their pool instead of the AMM
+    pub fn targets_pool(&self) -> bool {
+        matches!(
+            self,
+            ConfigChangeKind::PoolFee | ConfigChangeKind::UnpausePool
+        )
+    }
+}
+
+impl AmmPool {
+    /// Returns the swap fee of the pool, falling back to the AMM fee
+    pub fn effective_fee(&self, amm: &Amm) -> u16 {
+        self.fee_bps.unwrap_or(amm.fee)
+    }
+
+    /// Returns the reserves owned by the LPs, leaving out uncollected protocol fees and the tokens of
+    /// long-term orders
+    pub fn reserves(&self, pool_amount_a: u64, pool_amount_b: u64) -> Result<(u64, u64)> {
+        let reserve_a = pool_amount_a
+            .checked_sub(self.protocol_fees_a)
+            .and_then(|amount| amount.checked_sub(self.twamm.balance_a))
+            .ok_or(AmmError::MathOverflow)?;
+        let reserve_b = pool_amount_b
+            .checked_sub(self.protocol_fees_b)
+            .and_then(|amount| amount.checked_sub(self.twamm.balance_b))
+            .ok_or(AmmError::MathOverflow)?;
+        Ok((reserve_a, reserve_b))
+    }
+
+    /// Returns the accumulators as they would be at `now`, the price since the last update holds until then
+    pub fn cumulative_prices(&self, reserve_a: u64, reserve_b: u64, now: i64) -> (u128, u128) {
+        let elapsed = now.saturating_sub(self.last_update_timestamp);
+        if elapsed <= 0 || reserve_a == 0 || reserve_b == 0 {
+            return (self.price_a_cumulative, self.price_b_cumulative);
+        }
+
+        // Q64.64, a u64 reserve shifted by 64 bits always fits
+        let price_a = ((reserve_b as u128) << 64) / reserve_a as u128;
+        let price_b = ((reserve_a as u128) << 64) / reserve_b as u128;
+        (
+            self.price_a_cumulative
+                .wrapping_add(price_a.wrapping_mul(elapsed as u128)),
+            self.price_b_cumulative
+                .wrapping_add(price_b.wrapping_mul(elapsed as u128)),
+        )
+    }
+
+    /// Brings the accumulators up to `now`, has to run before the reserves change
+    pub fn update_price_accumulators(&mut self, reserve_a: u64, reserve_b: u64, now: i64) {
+        (self.price_a_cumulative, self.price_b_cumulative) =
+            self.cumulative_prices(reserve_a, reserve_b, now);
+        self.last_update_timestamp = self.last_update_timestamp.max(now);
+    }
+
+    /// Books a protocol fee taken in token A or token B
+    pub fn add_protocol_fee(&mut self, is_a: bool, protocol_fee: u64) -> Result<()> {
+        let protocol_fees = if is_a {
+            &mut self.protocol_fees_a
+        } else {
+            &mut self.protocol_fees_b
+        };
+        *protocol_fees = protocol_fees
+            .checked_add(protocol_fee)
+            .ok_or(AmmError::MathOverflow)?;
+        Ok(())
+    }
+}
+
+impl Twamm {
+    pub fn sell_rate(&mut self, is_a: bool) -> &mut u64 {
+        if is_a {
+            &mut self.sell_rate_a
+        } else {
+            &mut self.sell_rate_b
+        }
+    }
+
+    pub fn balance(&mut self, is_a: bool) -> &mut u64 {
+        if is_a {
+            &mut self.balance_a
+        } else {
+            &mut self.balance_b
+        }
+    }
+
+    pub fn earnings_per_rate(&self, is_sell_a: bool) -> u128 {
+        if is_sell_a {
+            self.earnings_per_rate_a_x64
+        } else {
+            self.earnings_per_rate_b_x64
+        }
+    }
+
+    /// Returns the first expiry after `timestamp`
+    pub fn next_expiry(&self, timestamp: i64) -> Option<i64> {
+        self.expiries
+            .iter()
+            .map(|expiry| expiry.timestamp)
+            .find(|&expiry| expiry > timestamp)
+    }
+
+    pub fn expiry(&self, timestamp: i64) -> Option<&TwammExpiry> {
+        self.expiries
+            .iter()
+            .find(|expiry| expiry.timestamp == timestamp)
+    }
+
+    /// Ends the sell rates that expire at `timestamp` and keeps the earnings their orders are paid from
+    pub fn cross_expiry(&mut self, timestamp: i64) -> Result<()> {
+        let Some(expiry) = self
+            .expiries
+            .iter_mut()
+            .find(|expiry| expiry.timestamp == timestamp)
+        else {
+            return Ok(());
+        };
+        expiry.earnings_per_rate_a_x64 = self.earnings_per_rate_a_x64;
+        expiry.earnings_per_rate_b_x64 = self.earnings_per_rate_b_x64;
+        expiry.idle_seconds = self.idle_seconds;
+        self.sell_rate_a = self
+            .sell_rate_a
+            .checked_sub(expiry.sell_rate_a)
+            .ok_or(AmmError::MathOverflow)?;
+        self.sell_rate_b = self
+            .sell_rate_b
+            .checked_sub(expiry.sell_rate_b)
+            .ok_or(AmmError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Starts selling `sell_rate` per second until `timestamp`
+    pub fn open_order(&mut self, is_sell_a: bool, sell_rate: u64, timestamp: i64) -> Result<()> {
+        let index = match self
+            .expiries
+            .binary_search_by_key(&timestamp, |expiry| expiry.timestamp)
+        {
+            Ok(index) => index,
+            Err(index) => {
+                require!(
+                    self.expiries.len() < MAX_TWAMM_EXPIRIES,
+                    AmmError::TwammExpiriesFull
+                );
+                self.expiries.insert(
+                    index,
+                    TwammExpiry {
+                        timestamp,
+                        sell_rate_a: 0,
+                        sell_rate_b: 0,
+                        earnings_per_rate_a_x64: 0,
+                        earnings_per_rate_b_x64: 0,
+                        idle_seconds: 0,
+                        orders: 0,
+                    },
+                );
+                index
+            }
+        };
+
+        let expiry = &mut self.expiries[index];
+        let expiry_rate = if is_sell_a {
+            &mut expiry.sell_rate_a
+        } else {
+            &mut expiry.sell_rate_b
+        };
+        *expiry_rate = expiry_rate
+            .checked_add(sell_rate)
+            .ok_or(AmmError::MathOverflow)?;
+        expiry.orders += 1;
+
+        let rate = self.sell_rate(is_sell_a);
+        *rate = rate.checked_add(sell_rate).ok_or(AmmError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Stops the sell rate of an order before its expiry, `close_order` still has to follow
+    pub fn cancel_order(&mut self, is_sell_a: bool, sell_rate: u64, timestamp: i64) -> Result<()> {
+        let rate = self.sell_rate(is_sell_a);
+        *rate = rate.checked_sub(sell_rate).ok_or(AmmError::MathOverflow)?;
+
+        if let Some(expiry) = self
+            .expiries
+            .iter_mut()
+            .find(|expiry| expiry.timestamp == timestamp)
+        {
+            let expiry_rate = if is_sell_a {
+                &mut expiry.sell_rate_a
+            } else {
+                &mut expiry.sell_rate_b
+            };
+            *expiry_rate = expiry_rate
+                .checked_sub(sell_rate)
+                .ok_or(AmmError::MathOverflow)?;
+        }
+        Ok(())
+    }
+
+    /// Removes an expiry the orders were already executed past and returns it
+    pub fn prune_expiry(&mut self, timestamp: i64) -> Result<TwammExpiry> {
+        require!(
+            timestamp <= self.last_execution,
+            AmmError::TwammExpiryNotCrossed
+        );
+        let index = self
+            .expiries
+            .iter()
+            .position(|expiry| expiry.timestamp == timestamp)
+            .ok_or(AmmError::TwammExpiryNotFound)?;
+        Ok(self.expiries.remove(index))
+    }
+
+    /// Drops the expiry once the last order expiring there is closed
+    pub fn close_order(&mut self, timestamp: i64) {
+        if let Some(index) = self
+            .expiries
+            .iter()
+            .position(|expiry| expiry.timestamp == timestamp)
+        {
+            self.expiries[index].orders -= 1;
+            if self.expiries[index].orders == 0 {
+                self.expiries.remove(index);
+            }
+        }
+    }
+}
+
+impl TwammExpiry {
+    pub fn earnings_per_rate(&self, is_sell_a: bool) -> u128 {
+        if is_sell_a {
+            self.earnings_per_rate_a_x64
+        } else {
+            self.earnings_per_rate_b_x64
+        }
+    }
+}
+
+impl TwammExpirySnapshot {
+    pub fn earnings_per_rate(&self, is_sell_a: bool) -> u128 {
+        if is_sell_a {
+            self.earnings_per_rate_a_x64
+        } else {
+            self.earnings_per_rate_b_x64
+        }
+    }
+}
+
+impl Observations {
+    /// Stores the current accumulators of `pool`, once per `OBSERVATION_PERIOD` at most
+    pub fn record(&mut self, pool: &AmmPool) {
+        self.write(Observation {
+            timestamp: pool.last_update_timestamp,
+            price_a_cumulative: pool.price_a_cumulative,
+            price_b_cumulative: pool.price_b_cumulative,
+        });
+    }
+
+    /// Overwrites the oldest slot with `observation` unless the latest one is less than `OBSERVATION_PERIOD` old
+    fn write(&mut self, observation: Observation) {
+        if self
+            .latest()
+            .is_some_and(|latest| observation.timestamp - latest.timestamp < OBSERVATION_PERIOD)
+        {
+            return;
+        }
+
+        let index = self.next_index as usize;
+        if index < self.observations.len() {
+            self.observations[index] = observation;
+        } else {
+            self.observations.push(observation);
+        }
+        self.next_index = ((index + 1) % MAX_OBSERVATIONS) as u16;
+    }
+
+    fn latest(&self) -> Option<&Observation> {
+        let index = (self.next_index as usize + MAX_OBSERVATIONS - 1) % MAX_OBSERVATIONS;
+        self.observations.get(index)
+    }
+
+    /// Returns the newest observation taken at or before `timestamp`
+    pub fn at_or_before(&self, timestamp: i64) -> Option<&Observation> {
+        self.observations
+            .iter()
+            .filter(|observation| observation.timestamp <= timestamp)
+            .max_by_key(|observation| observation.timestamp)
+    }
+}
+
+impl Order {
+    /// Returns whether the pool TWAP, as input in output, meets the order condition
+    pub fn is_triggered(&self, twap_price_x64: u128) -> bool {
+        match self.kind {
+            OrderKind::Limit | OrderKind::TakeProfit => twap_price_x64 >= self.trigger_price_x64,
+            OrderKind::StopLoss => twap_price_x64 <= self.trigger_price_x64,
+        }
+    }
+}
+
+impl Multisig {
+    pub fn validate(signers: &[Pubkey], threshold: u8) -> Result<()> {
+        require!(
+            !signers.is_empty() && signers.len() <= MAX_MULTISIG_SIGNERS,
+            AmmError::InvalidMultisig
+        );
+        require!(
+            threshold > 0 && threshold as usize <= signers.len(),
+            AmmError::InvalidMultisig
+        );
+        for (i, signer) in signers.iter().enumerate() {
+            require!(!signers[..i].contains(signer), AmmError::InvalidMultisig);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn observations() -> Observations {
+        Observations {
+            pool: Pubkey::default(),
+            next_index: 0,
+            observations: Vec::new(),
+        }
+    }
+
+    fn observation(timestamp: i64) -> Observation {
+        Observation {
+            timestamp,
+            price_a_cumulative: timestamp as u128,
+            price_b_cumulative: u128::MAX - timestamp as u128,
+        }
+    }
+
+    #[test]
+    fn writes_once_per_period() {
+        let mut buffer = observations();
+        for timestamp in 0..OBSERVATION_PERIOD * 3 {
+            buffer.write(observation(timestamp));
+        }
+        let timestamps: Vec<i64> = buffer.observations.iter().map(|o| o.timestamp).collect();
+        assert_eq!(timestamps, [0, OBSERVATION_PERIOD, 2 * OBSERVATION_PERIOD]);
+
+        // an older or equal timestamp never goes in
+        buffer.write(observation(OBSERVATION_PERIOD));
+        buffer.write(observation(2 * OBSERVATION_PERIOD));
+        assert_eq!(buffer.observations.len(), 3);
+    }
+
+    #[test]
+    fn frequent_writes_keep_a_window_longer_than_the_buffer() {
+        let mut buffer = observations();
+        // a write every second for a day
+        let day = 24 * 60 * 60;
+        for timestamp in 0..=day {
+            buffer.write(observation(timestamp));
+        }
+        assert_eq!(buffer.observations.len(), MAX_OBSERVATIONS);
+
+        let history = (MAX_OBSERVATIONS as i64 - 1) * OBSERVATION_PERIOD;
+        let oldest = buffer.at_or_before(day - history).unwrap();
+        assert!(day - oldest.timestamp >= history);
+        assert!(buffer
+            .at_or_before(day - history - OBSERVATION_PERIOD)
+            .is_none());
+    }
+
+    #[test]
+    fn at_or_before_follows_the_ring_across_wraparound() {
+        let mut buffer = observations();
+        let writes = MAX_OBSERVATIONS as i64 + 5;
+        for i in 0..writes {
+            buffer.write(observation(1_000 + i * OBSERVATION_PERIOD));
+        }
+        assert_eq!(buffer.next_index, 5);
+        assert_eq!(
+            buffer.latest().unwrap().timestamp,
+            1_000 + (writes - 1) * OBSERVATION_PERIOD
+        );
+
+        // the first five were overwritten
+        let oldest = 1_000 + 5 * OBSERVATION_PERIOD;
+        assert!(buffer.at_or_before(oldest - 1).is_none());
+        assert_eq!(buffer.at_or_before(oldest).unwrap().timestamp, oldest);
+        // between two observations the older one is returned
+        let between = oldest + 10 * OBSERVATION_PERIOD + OBSERVATION_PERIOD / 2;
+        assert_eq!(
+            buffer.at_or_before(between).unwrap().timestamp,
+            oldest + 10 * OBSERVATION_PERIOD
+        );
+        assert_eq!(
+            buffer.at_or_before(i64::MAX).unwrap().timestamp,
+            buffer.latest().unwrap().timestamp
+        );
+    }
+
+    #[test]
+    fn at_or_before_on_an_empty_buffer() {
+        let buffer = observations();
+        assert!(buffer.latest().is_none());
+        assert!(buffer.at_or_before(i64::MAX).is_none());
+    }
+
+    #[test]
+    fn prunes_only_crossed_expiries() {
+        let mut twamm = Twamm::default();
+        twamm.open_order(true, 5, TWAMM_INTERVAL).unwrap();
+        twamm.open_order(false, 7, TWAMM_INTERVAL).unwrap();
+        twamm.open_order(true, 3, 2 * TWAMM_INTERVAL).unwrap();
+
+        assert_eq!(
+            twamm.prune_expiry(TWAMM_INTERVAL).unwrap_err(),
+            AmmError::TwammExpiryNotCrossed.into()
+        );
+
+        twamm.earnings_per_rate_a_x64 = 11;
+        twamm.earnings_per_rate_b_x64 = 13;
+        twamm.last_execution = TWAMM_INTERVAL;
+        twamm.cross_expiry(TWAMM_INTERVAL).unwrap();
+        assert_eq!((twamm.sell_rate_a, twamm.sell_rate_b), (3, 0));
+
+        let pruned = twamm.prune_expiry(TWAMM_INTERVAL).unwrap();
+        assert_eq!(pruned.orders, 2);
+        assert_eq!(pruned.earnings_per_rate(true), 11);
+        assert_eq!(pruned.earnings_per_rate(false), 13);
+        assert_eq!(twamm.expiries.len(), 1);
+        assert_eq!(
+            twamm.prune_expiry(TWAMM_INTERVAL).unwrap_err(),
+            AmmError::TwammExpiryNotFound.into()
+        );
+        assert_eq!(
+            twamm.prune_expiry(2 * TWAMM_INTERVAL).unwrap_err(),
+            AmmError::TwammExpiryNotCrossed.into()
+        );
+    }
 }
@@ -0,0 +1,361 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+use crate::instructions::quote_exact_in;
+use crate::states::{Amm, AmmPool, Twamm, TWAMM_INTERVAL};
+
+/// Executes the long-term orders of `pool` from their last execution up to `now`. Time is split at every expiry
+/// on the way, the sell rates are constant in between, and at every `TWAMM_INTERVAL` boundary while orders sell so
+/// that no segment is settled at reserves older than an interval. Tokens do not move, the sold input and the earned
+/// output only change hands between the reserves and the order balances of the pool accounts.
+pub fn execute_virtual_orders(
+    pool: &mut AmmPool,
+    amm: &Amm,
+    pool_amount_a: u64,
+    pool_amount_b: u64,
+    now: i64,
+) -> Result<()> {
+    let fee_bps = pool.effective_fee(amm);
+
+    while pool.twamm.last_execution < now {
+        let start = pool.twamm.last_execution;
+        let (end, expiry) = next_segment(&pool.twamm, start, now);
+
+        // the price before the orders trade holds over the segment
+        let (reserve_a, reserve_b) = pool.reserves(pool_amount_a, pool_amount_b)?;
+        pool.update_price_accumulators(reserve_a, reserve_b, end);
+        execute_segment(
+            pool,
+            reserve_a,
+            reserve_b,
+            end - start,
+            fee_bps,
+            amm.protocol_fee_bps,
+        )?;
+
+        pool.twamm.last_execution = end;
+        if let Some(expiry) = expiry {
+            pool.twamm.cross_expiry(expiry)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns where the segment starting at `start` ends and the expiry it crosses there, if any. Without sell rates
+/// nothing trades and the segment runs to the next expiry or `now`.
+fn next_segment(twamm: &Twamm, start: i64, now: i64) -> (i64, Option<i64>) {
+    let expiry = twamm.next_expiry(start).filter(|&expiry| expiry <= now);
+    let mut end = expiry.unwrap_or(now);
+    if twamm.sell_rate_a > 0 || twamm.sell_rate_b > 0 {
+        end = end.min((start.div_euclid(TWAMM_INTERVAL) + 1) * TWAMM_INTERVAL);
+    }
+    (end, expiry.filter(|&expiry| expiry == end))
+}
+
+/// Settles `elapsed` seconds of both sell rates as one trade. The opposite flows are matched against each other
+/// at the spot price, only what is left of the larger flow is swapped through the curve and pays the swap fee.
+/// Without liquidity there is no price, the orders sell nothing and their input of the segment is refunded.
+fn execute_segment(
+    pool: &mut AmmPool,
+    reserve_a: u64,
+    reserve_b: u64,
+    elapsed: i64,
+    fee_bps: u16,
+    protocol_fee_bps: u16,
+) -> Result<()> {
+    let twamm = &pool.twamm;
+    let sell_a = twamm
+        .sell_rate_a
+        .checked_mul(elapsed as u64)
+        .ok_or(AmmError::MathOverflow)?;
+    let sell_b = twamm
+        .sell_rate_b
+        .checked_mul(elapsed as u64)
+        .ok_or(AmmError::MathOverflow)?;
+    if sell_a == 0 && sell_b == 0 {
+        return Ok(());
+    }
+    if reserve_a == 0 || reserve_b == 0 {
+        let twamm = &mut pool.twamm;
+        twamm.idle_seconds = twamm
+            .idle_seconds
+            .checked_add(elapsed as u64)
+            .ok_or(AmmError::MathOverflow)?;
+        return Ok(());
+    }
+
+    // compare the value of both flows at the spot price, a u64 product always fits in u128
+    let (out_a, out_b) = if sell_a as u128 * reserve_b as u128 >= sell_b as u128 * reserve_a as u128
+    {
+        let matched_a = (sell_b as u128 * reserve_a as u128 / reserve_b as u128) as u64;
+        let quote = quote_exact_in(
+            reserve_a,
+            reserve_b,
+            sell_a - matched_a,
+            fee_bps,
+            protocol_fee_bps,
+        )?;
+        pool.add_protocol_fee(true, quote.protocol_fee)?;
+        (matched_a, sell_b + quote.amount_out)
+    } else {
+        let matched_b = (sell_a as u128 * reserve_b as u128 / reserve_a as u128) as u64;
+        let quote = quote_exact_in(
+            reserve_b,
+            reserve_a,
+            sell_b - matched_b,
+            fee_bps,
+            protocol_fee_bps,
+        )?;
+        pool.add_protocol_fee(false, quote.protocol_fee)?;
+        (sell_a + quote.amount_out, matched_b)
+    };
+
+    let twamm = &mut pool.twamm;
+    twamm.balance_a = twamm
+        .balance_a
+        .checked_sub(sell_a)
+        .and_then(|balance| balance.checked_add(out_a))
+        .ok_or(AmmError::MathOverflow)?;
+    twamm.balance_b = twamm
+        .balance_b
+        .checked_sub(sell_b)
+        .and_then(|balance| balance.checked_add(out_b))
+        .ok_or(AmmError::MathOverflow)?;
+
+    // Q64.64, a u64 amount shifted by 64 bits always fits
+    if twamm.sell_rate_a > 0 {
+        twamm.earnings_per_rate_a_x64 = twamm
+            .earnings_per_rate_a_x64
+            .checked_add(((out_b as u128) << 64) / twamm.sell_rate_a as u128)
+            .ok_or(AmmError::MathOverflow)?;
+    }
+    if twamm.sell_rate_b > 0 {
+        twamm.earnings_per_rate_b_x64 = twamm
+            .earnings_per_rate_b_x64
+            .checked_add(((out_a as u128) << 64) / twamm.sell_rate_b as u128)
+            .ok_or(AmmError::MathOverflow)?;
+    }
+    Ok(())
+}
+
+/// Returns the output of a long-term order earned between the earnings per rate `paid_x64` and `earned_x64`
+pub fn order_earnings(sell_rate: u64, paid_x64: u128, earned_x64: u128) -> Result<u64> {
+    let earnings = earned_x64
+        .checked_sub(paid_x64)
+        .and_then(|delta| delta.checked_mul(sell_rate as u128))
+        .ok_or(AmmError::MathOverflow)?
+        >> 64;
+    earnings
+        .try_into()
+        .map_err(|_| AmmError::MathOverflow.into())
+}
+
+/// Returns the input of a long-term order left unsold between the idle seconds `refunded` and `idle`
+pub fn order_refund(sell_rate: u64, refunded: u64, idle: u64) -> Result<u64> {
+    idle.checked_sub(refunded)
+        .and_then(|seconds| seconds.checked_mul(sell_rate))
+        .ok_or(AmmError::MathOverflow.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::states::TwammExpiry;
+
+    const FEE_BPS: u16 = 30;
+
+    // order balances hold a day of each sell rate
+    fn pool(sell_rate_a: u64, sell_rate_b: u64) -> AmmPool {
+        AmmPool {
+            amm: Pubkey::default(),
+            mint_a: Pubkey::default(),
+            mint_b: Pubkey::default(),
+            token_program_a: Pubkey::default(),
+            token_program_b: Pubkey::default(),
+            fee_tier: 0,
+            paused: false,
+            fee_bps: None,
+            protocol_fees_a: 0,
+            protocol_fees_b: 0,
+            flash_loan_active: false,
+            price_a_cumulative: 0,
+            price_b_cumulative: 0,
+            last_update_timestamp: 0,
+            twamm: Twamm {
+                sell_rate_a,
+                sell_rate_b,
+                balance_a: sell_rate_a * 86_400,
+                balance_b: sell_rate_b * 86_400,
+                ..Twamm::default()
+            },
+            pending_config_changes: Vec::new(),
+        }
+    }
+
+    fn expiry(timestamp: i64) -> TwammExpiry {
+        TwammExpiry {
+            timestamp,
+            sell_rate_a: 0,
+            sell_rate_b: 0,
+            earnings_per_rate_a_x64: 0,
+            earnings_per_rate_b_x64: 0,
+            idle_seconds: 0,
+            orders: 1,
+        }
+    }
+
+    #[test]
+    fn one_sided_flow_is_swapped_through_the_curve() {
+        let mut pool = pool(10, 0);
+        execute_segment(&mut pool, 1_000_000, 3_000_000, 100, FEE_BPS, 0).unwrap();
+
+        let quote = quote_exact_in(1_000_000, 3_000_000, 1_000, FEE_BPS, 0).unwrap();
+        assert_eq!(pool.twamm.balance_a, 10 * 86_400 - 1_000);
+        assert_eq!(pool.twamm.balance_b, quote.amount_out);
+        assert_eq!(
+            pool.twamm.earnings_per_rate_a_x64,
+            ((quote.amount_out as u128) << 64) / 10
+        );
+        assert_eq!(pool.twamm.earnings_per_rate_b_x64, 0);
+
+        // the order is paid what the flow bought, at most one unit less from rounding
+        let earnings = order_earnings(10, 0, pool.twamm.earnings_per_rate_a_x64).unwrap();
+        assert!(earnings <= quote.amount_out && quote.amount_out - earnings <= 1);
+    }
+
+    #[test]
+    fn opposite_flows_of_equal_value_match_without_a_fee() {
+        // 1 A against 3 B per second at a price of 3 B per A
+        let mut pool = pool(1, 3);
+        execute_segment(&mut pool, 1_000_000, 3_000_000, 100, FEE_BPS, 0).unwrap();
+
+        assert_eq!(pool.twamm.balance_a, 86_400 - 100 + 100);
+        assert_eq!(pool.twamm.balance_b, 3 * 86_400 - 300 + 300);
+        assert_eq!(
+            order_earnings(1, 0, pool.twamm.earnings_per_rate_a_x64).unwrap(),
+            300
+        );
+        // 100 / 3 per unit of rate rounds down, the dust stays in the order balance
+        assert_eq!(
+            order_earnings(3, 0, pool.twamm.earnings_per_rate_b_x64).unwrap(),
+            99
+        );
+    }
+
+    #[test]
+    fn only_the_unmatched_rest_pays_the_fee() {
+        let mut pool = pool(2, 3);
+        execute_segment(&mut pool, 1_000_000, 3_000_000, 100, FEE_BPS, 5_000).unwrap();
+
+        // 300 B match 100 of the 200 A, the other 100 A are swapped
+        let quote = quote_exact_in(1_000_000, 3_000_000, 100, FEE_BPS, 5_000).unwrap();
+        assert_eq!(pool.protocol_fees_a, quote.protocol_fee);
+        assert_eq!(
+            pool.twamm.balance_b,
+            3 * 86_400 - 300 + 300 + quote.amount_out
+        );
+        assert_eq!(pool.twamm.balance_a, 2 * 86_400 - 200 + 100);
+    }
+
+    #[test]
+    fn nothing_trades_without_sell_rates_or_reserves() {
+        let mut idle = pool(0, 0);
+        execute_segment(&mut idle, 1_000_000, 3_000_000, 3_600, FEE_BPS, 0).unwrap();
+        assert_eq!(idle.twamm.earnings_per_rate_a_x64, 0);
+
+        let mut empty = pool(10, 0);
+        execute_segment(&mut empty, 0, 3_000_000, 3_600, FEE_BPS, 0).unwrap();
+        assert_eq!(empty.twamm.balance_a, 10 * 86_400);
+        assert_eq!(empty.twamm.balance_b, 0);
+        assert_eq!(empty.twamm.idle_seconds, 3_600);
+    }
+
+    #[test]
+    fn a_segment_longer_than_the_balance_overflows() {
+        let mut pool = pool(10, 0);
+        assert_eq!(
+            execute_segment(&mut pool, 1_000_000, 3_000_000, 86_401, FEE_BPS, 0).unwrap_err(),
+            AmmError::MathOverflow.into()
+        );
+    }
+
+    #[test]
+    fn segments_end_at_interval_boundaries_while_orders_sell() {
+        let mut twamm = pool(10, 0).twamm;
+        let now = 10 * TWAMM_INTERVAL + 5;
+        assert_eq!(next_segment(&twamm, 100, now), (TWAMM_INTERVAL, None));
+        assert_eq!(
+            next_segment(&twamm, 9 * TWAMM_INTERVAL, now),
+            (10 * TWAMM_INTERVAL, None)
+        );
+        assert_eq!(next_segment(&twamm, 10 * TWAMM_INTERVAL, now), (now, None));
+
+        twamm.expiries.push(expiry(2 * TWAMM_INTERVAL));
+        assert_eq!(
+            next_segment(&twamm, TWAMM_INTERVAL + 7, now),
+            (2 * TWAMM_INTERVAL, Some(2 * TWAMM_INTERVAL))
+        );
+        // an expiry after `now` is not crossed
+        assert_eq!(
+            next_segment(&twamm, TWAMM_INTERVAL, TWAMM_INTERVAL + 5),
+            (TWAMM_INTERVAL + 5, None)
+        );
+    }
+
+    #[test]
+    fn idle_pools_skip_to_the_next_expiry() {
+        let mut twamm = pool(0, 0).twamm;
+        let now = 30 * TWAMM_INTERVAL;
+        assert_eq!(next_segment(&twamm, 100, now), (now, None));
+
+        // expiries of cancelled orders still get their earnings snapshot
+        twamm.expiries.push(expiry(3 * TWAMM_INTERVAL));
+        assert_eq!(
+            next_segment(&twamm, 100, now),
+            (3 * TWAMM_INTERVAL, Some(3 * TWAMM_INTERVAL))
+        );
+    }
+
+    #[test]
+    fn orders_are_refunded_the_input_of_idle_segments() {
+        let mut traded = pool(10, 4);
+        execute_segment(&mut traded, 1_000_000, 3_000_000, 100, FEE_BPS, 0).unwrap();
+
+        let mut pool = pool(10, 4);
+        execute_segment(&mut pool, 0, 0, 600, FEE_BPS, 0).unwrap();
+        execute_segment(&mut pool, 1_000_000, 3_000_000, 100, FEE_BPS, 0).unwrap();
+        execute_segment(&mut pool, 0, 0, 300, FEE_BPS, 0).unwrap();
+        assert_eq!(pool.twamm.idle_seconds, 900);
+
+        // an order opened after the first idle segment is refunded the last one only
+        assert_eq!(
+            order_refund(10, 600, pool.twamm.idle_seconds).unwrap(),
+            3_000
+        );
+        assert_eq!(order_refund(4, 0, pool.twamm.idle_seconds).unwrap(), 3_600);
+        // the idle input stays in the order balances to be refunded from
+        assert_eq!(pool.twamm.balance_a, traded.twamm.balance_a);
+        assert_eq!(pool.twamm.balance_b, traded.twamm.balance_b);
+        assert_eq!(
+            order_refund(1, 900, 600).unwrap_err(),
+            AmmError::MathOverflow.into()
+        );
+    }
+
+    #[test]
+    fn order_earnings_round_down_and_reject_a_reversed_range() {
+        let one_x64 = 1u128 << 64;
+        assert_eq!(order_earnings(7, one_x64, 3 * one_x64).unwrap(), 14);
+        assert_eq!(order_earnings(3, 0, one_x64 / 2).unwrap(), 1);
+        assert_eq!(order_earnings(0, 0, u128::MAX).unwrap(), 0);
+        assert_eq!(
+            order_earnings(1, one_x64, 0).unwrap_err(),
+            AmmError::MathOverflow.into()
+        );
+        // more than a u64 of output
+        assert_eq!(
+            order_earnings(u64::MAX, 0, 2 * one_x64).unwrap_err(),
+            AmmError::MathOverflow.into()
+        );
+    }
+}
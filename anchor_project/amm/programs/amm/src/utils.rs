@@ -0,0 +1,270 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{
+    allocate, assign, create_account, transfer, Allocate, Assign, CreateAccount, Transfer,
+};
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::token::TokenAccount;
+use anchor_spl::token_2022::spl_token_2022;
+use anchor_spl::token_2022::spl_token_2022::extension::transfer_fee::TransferFeeConfig;
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    transfer_hook, BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+};
+use anchor_spl::token_2022::spl_token_2022::onchain::invoke_transfer_checked;
+use anchor_spl::token_2022_extensions::transfer_fee::{
+    harvest_withheld_tokens_to_mint, HarvestWithheldTokensToMint,
+};
+use anchor_spl::token_interface::{
+    close_account, initialize_account3, sync_native, transfer_checked, CloseAccount,
+    InitializeAccount3, SyncNative, TransferChecked,
+};
+
+use crate::errors::AmmError;
+use crate::states::WSOL_SEED;
+
+/// Fails once the unix timestamp `deadline` has passed, no deadline never expires
+pub fn check_deadline(deadline: Option<i64>) -> Result<()> {
+    if let Some(deadline) = deadline {
+        require!(
+            Clock::get()?.unix_timestamp <= deadline,
+            AmmError::DeadlineExceeded
+        );
+    }
+    Ok(())
+}
+
+/// Returns whether token A is the native mint of the pool, fails when neither side is
+pub fn native_side(mint_a: Pubkey, mint_b: Pubkey) -> Result<bool> {
+    if mint_a == native_mint::ID {
+        Ok(true)
+    } else if mint_b == native_mint::ID {
+        Ok(false)
+    } else {
+        err!(AmmError::NotNativeMintPool)
+    }
+}
+
+/// Moves `amount` lamports of `owner` into its WSOL `account` and syncs the token balance
+pub fn wrap_sol<'info>(
+    owner: AccountInfo<'info>,
+    account: AccountInfo<'info>,
+    amount: u64,
+    system_program: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+) -> Result<()> {
+    let cpi_accounts = Transfer {
+        from: owner,
+        to: account.clone(),
+    };
+    transfer(CpiContext::new(system_program, cpi_accounts), amount)?;
+    sync_native(CpiContext::new(token_program, SyncNative { account }))
+}
+
+/// Pays `amount` of the WSOL `account` of `owner` out as lamports. The amount moves into `temporary`, a token
+/// account at the `["WSOL", owner]` PDA that is created here and closed right away, so whatever else `account`
+/// holds stays wrapped.
+#[allow(clippy::too_many_arguments)]
+pub fn unwrap_sol<'info>(
+    owner: AccountInfo<'info>,
+    account: AccountInfo<'info>,
+    temporary: AccountInfo<'info>,
+    temporary_bump: u8,
+    mint: AccountInfo<'info>,
+    amount: u64,
+    system_program: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let signer_seeds: &[&[&[u8]]] =
+        &[&[WSOL_SEED.as_bytes(), owner.key.as_ref(), &[temporary_bump]]];
+    let space = TokenAccount::LEN as u64;
+    let rent = Rent::get()?.minimum_balance(TokenAccount::LEN);
+
+    // lamports sent to the address ahead of time cannot block the account from being created
+    let lamports = temporary.lamports();
+    if lamports == 0 {
+        let cpi_accounts = CreateAccount {
+            from: owner.clone(),
+            to: temporary.clone(),
+        };
+        let cpi_context = CpiContext::new(system_program, cpi_accounts).with_signer(signer_seeds);
+        create_account(cpi_context, rent, space, token_program.key)?;
+    } else {
+        if lamports < rent {
+            let cpi_accounts = Transfer {
+                from: owner.clone(),
+                to: temporary.clone(),
+            };
+            transfer(
+                CpiContext::new(system_program.clone(), cpi_accounts),
+                rent - lamports,
+            )?;
+        }
+        let cpi_accounts = Allocate {
+            account_to_allocate: temporary.clone(),
+        };
+        let cpi_context =
+            CpiContext::new(system_program.clone(), cpi_accounts).with_signer(signer_seeds);
+        allocate(cpi_context, space)?;
+        let cpi_accounts = Assign {
+            account_to_assign: temporary.clone(),
+        };
+        let cpi_context = CpiContext::new(system_program, cpi_accounts).with_signer(signer_seeds);
+        assign(cpi_context, token_program.key)?;
+    }
+
+    let cpi_accounts = InitializeAccount3 {
+        account: temporary.clone(),
+        mint: mint.clone(),
+        authority: owner.clone(),
+    };
+    initialize_account3(CpiContext::new(token_program.clone(), cpi_accounts))?;
+
+    let cpi_accounts = TransferChecked {
+        mint,
+        from: account,
+        to: temporary.clone(),
+        authority: owner.clone(),
+    };
+    transfer_checked(
+        CpiContext::new(token_program.clone(), cpi_accounts),
+        amount,
+        native_mint::DECIMALS,
+    )?;
+
+    let cpi_accounts = CloseAccount {
+        account: temporary,
+        destination: owner.clone(),
+        authority: owner,
+    };
+    close_account(CpiContext::new(token_program, cpi_accounts))
+}
+
+/// Returns the fee the token program withholds when `amount` of `mint` is transferred, zero for mints without
+/// the transfer fee extension
+pub fn transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    let Some(config) = transfer_fee_config(mint)? else {
+        return Ok(0);
+    };
+    config
+        .calculate_epoch_fee(Clock::get()?.epoch, amount)
+        .ok_or(AmmError::MathOverflow.into())
+}
+
+/// Returns what has to be transferred of `mint` for `amount` to arrive after the transfer fee
+pub fn amount_with_transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    let Some(config) = transfer_fee_config(mint)? else {
+        return Ok(amount);
+    };
+    config
+        .get_epoch_fee(Clock::get()?.epoch)
+        .calculate_pre_fee_amount(amount)
+        .ok_or(AmmError::MathOverflow.into())
+}
+
+/// Closes the token `account` of the PDA `authority`, signed with `signer_seeds`. Transfer fees withheld in the
+/// account would block the close, they are harvested to the `mint`, which then has to be writable.
+pub fn close_token_account<'info>(
+    account: AccountInfo<'info>,
+    destination: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    if transfer_fee_config(&mint)?.is_some() {
+        let cpi_accounts = HarvestWithheldTokensToMint {
+            token_program_id: token_program.clone(),
+            mint,
+        };
+        harvest_withheld_tokens_to_mint(
+            CpiContext::new(token_program.clone(), cpi_accounts),
+            vec![account.clone()],
+        )?;
+    }
+    let cpi_accounts = CloseAccount {
+        account,
+        destination,
+        authority,
+    };
+    close_account(CpiContext::new(token_program, cpi_accounts).with_signer(signer_seeds))
+}
+
+/// `transfer_checked` that also runs the transfer hook of a Token-2022 mint. The hook program, its validation account
+/// and the extra accounts it lists are looked up in the remaining accounts of `ctx`, mints without a hook ignore them.
+pub fn transfer_checked_with_hook<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+    amount: u64,
+    decimals: u8,
+) -> Result<()> {
+    invoke_transfer_checked(
+        ctx.program.key,
+        ctx.accounts.from,
+        ctx.accounts.mint,
+        ctx.accounts.to,
+        ctx.accounts.authority,
+        &ctx.remaining_accounts,
+        amount,
+        decimals,
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+/// Token-2022 mint extensions a pool accepts. The others let someone besides the pool move or freeze its tokens
+/// (permanent delegate, frozen default state, confidential transfers), stop transfers altogether or close the mint.
+const SUPPORTED_MINT_EXTENSIONS: [ExtensionType; 4] = [
+    ExtensionType::TransferFeeConfig,
+    ExtensionType::TransferHook,
+    ExtensionType::MetadataPointer,
+    ExtensionType::TokenMetadata,
+];
+
+/// Fails with `UnsupportedMintExtension` when `mint` has an extension outside `SUPPORTED_MINT_EXTENSIONS`
+pub fn check_mint_extensions(mint: &AccountInfo) -> Result<()> {
+    if mint.owner != &spl_token_2022::ID {
+        return Ok(());
+    }
+    let data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    for extension in mint.get_extension_types()? {
+        require!(
+            SUPPORTED_MINT_EXTENSIONS.contains(&extension),
+            AmmError::UnsupportedMintExtension
+        );
+    }
+    Ok(())
+}
+
+/// Returns the program of the transfer hook extension of `mint`, `None` for mints without one
+pub fn transfer_hook_program(mint: &AccountInfo) -> Result<Option<Pubkey>> {
+    if mint.owner != &spl_token_2022::ID {
+        return Ok(None);
+    }
+    let data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    Ok(transfer_hook::get_program_id(&mint))
+}
+
+/// Returns the authority that can change the transfer hook program of `mint`, `None` when nobody can
+pub fn transfer_hook_authority(mint: &AccountInfo) -> Result<Option<Pubkey>> {
+    if mint.owner != &spl_token_2022::ID {
+        return Ok(None);
+    }
+    let data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    Ok(mint
+        .get_extension::<transfer_hook::TransferHook>()
+        .ok()
+        .and_then(|extension| Option::<Pubkey>::from(extension.authority)))
+}
+
+fn transfer_fee_config(mint: &AccountInfo) -> Result<Option<TransferFeeConfig>> {
+    if mint.owner != &spl_token_2022::ID {
+        return Ok(None);
+    }
+    let data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    Ok(mint.get_extension::<TransferFeeConfig>().ok().copied())
+}
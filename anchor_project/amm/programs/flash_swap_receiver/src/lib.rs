@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer_checked, Mint, Token, TokenAccount, TransferChecked};
+
+declare_id!("BXZuCCDyKJogXt4uGSR1jG5HCrD4pETitgn7dwAxZSSd");
+
+/// Test borrower for `amm::flash_swap`, it pays back the amount encoded in `data` from the borrower's account
+#[program]
+pub mod flash_swap_receiver {
+    use super::*;
+
+    pub fn flash_swap_callback(
+        ctx: Context<FlashSwapCallback>,
+        _amount_a_out: u64,
+        _amount_b_out: u64,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        let repay_amount = u64::try_from_slice(&data)?;
+
+        let cpi_accounts = TransferChecked {
+            mint: ctx.accounts.mint.to_account_info(),
+            from: ctx.accounts.borrower_account.to_account_info(),
+            to: ctx.accounts.pool_account.to_account_info(),
+            authority: ctx.accounts.borrower.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_context = CpiContext::new(cpi_program, cpi_accounts);
+        transfer_checked(cpi_context, repay_amount, ctx.accounts.mint.decimals)
+    }
+}
+
+#[derive(Accounts)]
+pub struct FlashSwapCallback<'info> {
+    pub borrower: Signer<'info>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = borrower,
+    )]
+    pub borrower_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+    )]
+    pub pool_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
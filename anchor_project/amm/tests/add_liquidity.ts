@@ -174,7 +174,7 @@ describe("add_liquidity", () => {
         const depositorAccountLiquidityBefore = getAssociatedTokenAddressSync(mintLiquidityPda, user.publicKey, false);
         const lpAccount1Before = await getAccount(connection, depositorAccountLiquidityBefore);
 
-        await program.methods.addLiquidity(amountA2, amountB2).accounts({
+        await program.methods.addLiquidity(amountA2, amountB2, null, false).accounts({
             pool: poolPda,
             mintA: mintA.publicKey,
             mintB: mintB.publicKey,
@@ -239,7 +239,7 @@ describe("add_liquidity", () => {
         await mintTo(connection, user, mintB.publicKey, depositorAccountB, user, amountB.toNumber());
 
         try {
-            await program.methods.addLiquidity(amountA, amountB).accounts({
+            await program.methods.addLiquidity(amountA, amountB, null, false).accounts({
                 pool: poolPda,
                 mintA: mintA.publicKey,
                 mintB: mintB.publicKey,
@@ -289,7 +289,7 @@ describe("add_liquidity", () => {
         await mintTo(connection, user, mintA.publicKey, depositorAccountA, user, amountA.toNumber());
 
         try {
-            await program.methods.addLiquidity(amountA, amountB).accounts({
+            await program.methods.addLiquidity(amountA, amountB, null, false).accounts({
                 pool: poolPda,
                 mintA: mintA.publicKey,
                 mintB: mintB.publicKey,
@@ -359,7 +359,7 @@ describe("add_liquidity", () => {
         assert.isTrue(finalBalanceA.lt(amountA), `Account A should have insufficient balance: ${finalBalanceA.toString()} < ${amountA.toString()}`);
 
         try {
-            await program.methods.addLiquidity(amountA, amountB).accounts({
+            await program.methods.addLiquidity(amountA, amountB, null, false).accounts({
                 pool: poolPda,
                 mintA: mintA.publicKey,
                 mintB: mintB.publicKey,
@@ -429,7 +429,7 @@ describe("add_liquidity", () => {
         assert.isTrue(finalBalanceB.lt(amountB), `Account B should have insufficient balance: ${finalBalanceB.toString()} < ${amountB.toString()}`);
 
         try {
-            await program.methods.addLiquidity(amountA, amountB).accounts({
+            await program.methods.addLiquidity(amountA, amountB, null, false).accounts({
                 pool: poolPda,
                 mintA: mintA.publicKey,
                 mintB: mintB.publicKey,
@@ -486,7 +486,7 @@ describe("add_liquidity", () => {
         await mintTo(connection, user, mintA.publicKey, depositorAccountA, user, amountA2.toNumber());
         await mintTo(connection, user, mintB.publicKey, depositorAccountB, user, amountB2.toNumber());
 
-        await program.methods.addLiquidity(amountA2, amountB2).accounts({
+        await program.methods.addLiquidity(amountA2, amountB2, null, false).accounts({
             pool: poolPda,
             mintA: mintA.publicKey,
             mintB: mintB.publicKey,
@@ -547,7 +547,7 @@ describe("add_liquidity", () => {
         await mintTo(connection, user, mintA.publicKey, depositorAccountA, user, amountA2.toNumber());
         await mintTo(connection, user, mintB.publicKey, depositorAccountB, user, amountB2.toNumber());
 
-        await program.methods.addLiquidity(amountA2, amountB2).accounts({
+        await program.methods.addLiquidity(amountA2, amountB2, null, false).accounts({
             pool: poolPda,
             mintA: mintA.publicKey,
             mintB: mintB.publicKey,
@@ -608,7 +608,7 @@ describe("add_liquidity", () => {
         await mintTo(connection, user, mintA.publicKey, depositorAccountA, user, amountA2.toNumber());
         await mintTo(connection, user, mintB.publicKey, depositorAccountB, user, amountB2.toNumber());
 
-        await program.methods.addLiquidity(amountA2, amountB2).accounts({
+        await program.methods.addLiquidity(amountA2, amountB2, null, false).accounts({
             pool: poolPda,
             mintA: mintA.publicKey,
             mintB: mintB.publicKey,
@@ -0,0 +1,175 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { Amm } from "../target/types/amm";
+import { Keypair, Connection } from "@solana/web3.js";
+import { assert } from "chai";
+import {
+  acceptAdmin,
+  airdrop,
+  applyConfigChange,
+  cancelAdminTransfer,
+  checkAmm,
+  ConfigChange,
+  ConfigChangeKind,
+  createAmm,
+  executeConfigChange,
+  proposeAdmin
+} from "./helper";
+
+describe("amm_config", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  let connection: Connection = anchor.getProvider().connection;
+
+  const program = anchor.workspace.amm as Program<Amm>;
+
+  describe("update_amm_config", async () => {
+    it("Admin can change the fee", async () => {
+      const signer = Keypair.generate();
+      const admin = Keypair.generate();
+      const index = 500;
+
+      await airdrop(connection, signer.publicKey);
+      await airdrop(connection, admin.publicKey);
+
+      const { ammPda } = await createAmm(program, signer, admin.publicKey, 100, index);
+      await applyConfigChange(program, admin, ammPda, ConfigChange.fee(250));
+      await checkAmm(program, ammPda, admin.publicKey, index, 250);
+    });
+
+    it("Cannot change the fee without the admin signature", async () => {
+      const signer = Keypair.generate();
+      const admin = Keypair.generate();
+      const index = 501;
+
+      await airdrop(connection, signer.publicKey);
+      await airdrop(connection, admin.publicKey);
+
+      const { ammPda } = await createAmm(program, signer, admin.publicKey, 100, index);
+
+      try {
+        await applyConfigChange(program, signer, ammPda, ConfigChange.fee(250));
+        assert.fail("Expected transaction to fail");
+      } catch (err) {
+        assert.isTrue(err.toString().includes("Unauthorized"), `Expected Unauthorized error, got: ${err.toString()}`);
+      }
+      await checkAmm(program, ammPda, admin.publicKey, index, 100);
+    });
+
+    it("Cannot set fee equal to MAX_FEE_BPS (10000)", async () => {
+      const signer = Keypair.generate();
+      const admin = Keypair.generate();
+      const index = 502;
+
+      await airdrop(connection, signer.publicKey);
+      await airdrop(connection, admin.publicKey);
+
+      const { ammPda } = await createAmm(program, signer, admin.publicKey, 100, index);
+
+      try {
+        await applyConfigChange(program, admin, ammPda, ConfigChange.fee(10000));
+        assert.fail("Expected transaction to fail");
+      } catch (err) {
+        assert.isTrue(err.toString().includes("InvalidFee"), `Expected InvalidFee error, got: ${err.toString()}`);
+      }
+    });
+  });
+
+  describe("admin transfer", async () => {
+    it("New admin takes over after accepting", async () => {
+      const signer = Keypair.generate();
+      const admin = Keypair.generate();
+      const newAdmin = Keypair.generate();
+      const index = 510;
+
+      await airdrop(connection, signer.publicKey);
+      await airdrop(connection, admin.publicKey);
+      await airdrop(connection, newAdmin.publicKey);
+
+      const { ammPda } = await createAmm(program, signer, admin.publicKey, 100, index);
+      await applyConfigChange(program, admin, ammPda, ConfigChange.admin(newAdmin.publicKey));
+
+      let ammData = await program.account.amm.fetch(ammPda);
+      assert.strictEqual(ammData.pendingAdmin.toBase58(), newAdmin.publicKey.toBase58());
+      await checkAmm(program, ammPda, admin.publicKey, index, 100);
+
+      await acceptAdmin(program, newAdmin, ammPda);
+      await checkAmm(program, ammPda, newAdmin.publicKey, index, 100);
+      ammData = await program.account.amm.fetch(ammPda);
+      assert.isNull(ammData.pendingAdmin);
+
+      // the old admin lost its rights, the new one can change the fee
+      try {
+        await applyConfigChange(program, admin, ammPda, ConfigChange.fee(200));
+        assert.fail("Expected transaction to fail");
+      } catch (err) {
+        assert.isTrue(err.toString().includes("Unauthorized"), `Expected Unauthorized error, got: ${err.toString()}`);
+      }
+      await applyConfigChange(program, newAdmin, ammPda, ConfigChange.fee(200));
+      await checkAmm(program, ammPda, newAdmin.publicKey, index, 200);
+    });
+
+    it("Only the proposed key can accept", async () => {
+      const signer = Keypair.generate();
+      const admin = Keypair.generate();
+      const newAdmin = Keypair.generate();
+      const index = 511;
+
+      await airdrop(connection, signer.publicKey);
+      await airdrop(connection, admin.publicKey);
+
+      const { ammPda } = await createAmm(program, signer, admin.publicKey, 100, index);
+      await proposeAdmin(program, admin, ammPda, newAdmin.publicKey);
+      await executeConfigChange(program, ammPda, ConfigChangeKind.admin);
+
+      try {
+        await acceptAdmin(program, signer, ammPda);
+        assert.fail("Expected transaction to fail");
+      } catch (err) {
+        assert.isTrue(err.toString().includes("Unauthorized"), `Expected Unauthorized error, got: ${err.toString()}`);
+      }
+      await checkAmm(program, ammPda, admin.publicKey, index, 100);
+    });
+
+    it("Admin can cancel a pending transfer", async () => {
+      const signer = Keypair.generate();
+      const admin = Keypair.generate();
+      const newAdmin = Keypair.generate();
+      const index = 512;
+
+      await airdrop(connection, signer.publicKey);
+      await airdrop(connection, admin.publicKey);
+      await airdrop(connection, newAdmin.publicKey);
+
+      const { ammPda } = await createAmm(program, signer, admin.publicKey, 100, index);
+      await proposeAdmin(program, admin, ammPda, newAdmin.publicKey);
+      await executeConfigChange(program, ammPda, ConfigChangeKind.admin);
+      await cancelAdminTransfer(program, admin, ammPda);
+
+      try {
+        await acceptAdmin(program, newAdmin, ammPda);
+        assert.fail("Expected transaction to fail");
+      } catch (err) {
+        assert.isTrue(err.toString().includes("NoPendingAdmin"), `Expected NoPendingAdmin error, got: ${err.toString()}`);
+      }
+      await checkAmm(program, ammPda, admin.publicKey, index, 100);
+    });
+
+    it("Only the admin can propose a new admin", async () => {
+      const signer = Keypair.generate();
+      const admin = Keypair.generate();
+      const index = 513;
+
+      await airdrop(connection, signer.publicKey);
+      await airdrop(connection, admin.publicKey);
+
+      const { ammPda } = await createAmm(program, signer, admin.publicKey, 100, index);
+
+      try {
+        await proposeAdmin(program, signer, ammPda, signer.publicKey);
+        assert.fail("Expected transaction to fail");
+      } catch (err) {
+        assert.isTrue(err.toString().includes("Unauthorized"), `Expected Unauthorized error, got: ${err.toString()}`);
+      }
+    });
+  });
+});
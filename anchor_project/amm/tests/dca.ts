@@ -0,0 +1,206 @@
+import * as anchor from "@coral-xyz/anchor";
+import {Program} from "@coral-xyz/anchor";
+import {Amm} from "../target/types/amm";
+import {Keypair, Connection, PublicKey} from "@solana/web3.js";
+import {assert} from "chai";
+import {getAccount, getAssociatedTokenAddressSync} from "@solana/spl-token";
+import {
+    addLiquidity,
+    airdrop,
+    closeDca,
+    createAmm,
+    createDca,
+    createMintSafe,
+    createPool,
+    executeDca,
+    withdrawDca
+} from "./helper";
+
+describe("dca", () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    let connection: Connection = anchor.getProvider().connection;
+
+    const program = anchor.workspace.amm as Program<Amm>;
+    const DECIMALS = new anchor.BN(10).pow(new anchor.BN(9));
+
+    // pool at 1000 A / 3000 B with a first observation old enough for a one second TWAP
+    async function setupPool(ammIndex: number) {
+        const provider = Keypair.generate();
+        const owner = Keypair.generate();
+        const mintA = Keypair.generate();
+        const mintB = Keypair.generate();
+
+        await airdrop(connection, provider.publicKey);
+        await airdrop(connection, owner.publicKey);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintA);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintB);
+
+        const {ammPda} = await createAmm(program, provider, provider.publicKey, 30, ammIndex);
+        const pool = await createPool(program, provider, ammPda, mintA.publicKey, mintB.publicKey);
+        await addLiquidity(program, connection, provider, provider, pool.poolPda, mintA.publicKey, mintB.publicKey, pool.mintLiquidityPda, new anchor.BN(1000).mul(DECIMALS), new anchor.BN(3000).mul(DECIMALS));
+        await sleep(2000);
+
+        return {provider, owner, mintA, mintB, ammPda, ...pool};
+    }
+
+    function sleep(ms: number) {
+        return new Promise((resolve) => setTimeout(resolve, ms));
+    }
+
+    function assertError(err: any, name: string) {
+        assert.isTrue(err.toString().includes(name), `Expected ${name} error, got: ${err.toString()}`);
+    }
+
+    async function balance(account: PublicKey) {
+        return new anchor.BN((await getAccount(connection, account)).amount.toString());
+    }
+
+    it("Keepers execute one slice per interval and the owner withdraws the proceeds", async () => {
+        const ctx = await setupPool(2400);
+        const amountPerPeriod = new anchor.BN(10).mul(DECIMALS);
+        const keeper = Keypair.generate();
+        await airdrop(connection, keeper.publicKey);
+        const execute = () => executeDca(program, keeper, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, dca);
+
+        const dca = await createDca(program, connection, ctx.owner, ctx.provider, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, 0, {
+            isSwapA: true,
+            amountPerPeriod,
+            interval: new anchor.BN(2),
+            totalPeriods: 2,
+            twapWindow: 1,
+            maxSlippageBps: 300,
+            keeperFeeBps: 50,
+        });
+        const inputVault = getAssociatedTokenAddressSync(ctx.mintA.publicKey, dca, true);
+        const outputVault = getAssociatedTokenAddressSync(ctx.mintB.publicKey, dca, true);
+        assert.isTrue((await balance(inputVault)).eq(amountPerPeriod.muln(2)));
+
+        // the keeper gets 0.5% of the slice, the rest is swapped
+        const keeperFee = amountPerPeriod.muln(50).divn(10_000);
+        const keeperAccount = getAssociatedTokenAddressSync(ctx.mintA.publicKey, keeper.publicKey, false);
+        await execute();
+        assert.isTrue((await balance(inputVault)).eq(amountPerPeriod));
+        assert.isTrue((await balance(keeperAccount)).eq(keeperFee));
+        const firstSlice = await balance(outputVault);
+        assert.isTrue(firstSlice.gtn(0));
+
+        try {
+            await execute();
+            assert.fail("Expected execution to fail");
+        } catch (err) {
+            assertError(err, "DcaNotDue");
+        }
+
+        await sleep(3000);
+        await execute();
+        assert.isTrue((await balance(inputVault)).isZero());
+        assert.isTrue((await balance(keeperAccount)).eq(keeperFee.muln(2)));
+        const proceeds = await balance(outputVault);
+        assert.isTrue(proceeds.gt(firstSlice));
+        assert.equal((await program.account.dca.fetch(dca)).periodsExecuted, 2);
+
+        await sleep(3000);
+        try {
+            await execute();
+            assert.fail("Expected execution to fail");
+        } catch (err) {
+            assertError(err, "DcaCompleted");
+        }
+
+        await withdrawDca(program, ctx.owner, ctx.poolPda, ctx.mintB.publicKey, dca);
+        assert.isTrue((await balance(getAssociatedTokenAddressSync(ctx.mintB.publicKey, ctx.owner.publicKey, false))).eq(proceeds));
+        assert.isTrue((await balance(outputVault)).isZero());
+    });
+
+    it("A slice fails when its output falls short of the TWAP bound", async () => {
+        const ctx = await setupPool(2401);
+
+        // 100 A moves the price by about 10%, more than the 1% allowed
+        const dca = await createDca(program, connection, ctx.owner, ctx.provider, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, 0, {
+            isSwapA: true,
+            amountPerPeriod: new anchor.BN(100).mul(DECIMALS),
+            interval: new anchor.BN(60),
+            totalPeriods: 1,
+            twapWindow: 1,
+            maxSlippageBps: 100,
+            keeperFeeBps: 0,
+        });
+
+        try {
+            await executeDca(program, ctx.provider, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, dca);
+            assert.fail("Expected execution to fail");
+        } catch (err) {
+            assertError(err, "OutputAmountTooLow");
+        }
+    });
+
+    it("Owner closes a DCA and gets the unsold deposit back", async () => {
+        const ctx = await setupPool(2402);
+        const amountPerPeriod = new anchor.BN(10).mul(DECIMALS);
+
+        const dca = await createDca(program, connection, ctx.owner, ctx.provider, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, 3, {
+            isSwapA: false,
+            amountPerPeriod,
+            interval: new anchor.BN(60),
+            totalPeriods: 3,
+            twapWindow: 1,
+            maxSlippageBps: 300,
+            keeperFeeBps: 0,
+        });
+        await executeDca(program, ctx.provider, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, dca);
+        const proceeds = await balance(getAssociatedTokenAddressSync(ctx.mintA.publicKey, dca, true));
+
+        await closeDca(program, ctx.owner, ctx.poolPda, ctx.mintB.publicKey, ctx.mintA.publicKey, dca);
+        assert.isTrue((await balance(getAssociatedTokenAddressSync(ctx.mintB.publicKey, ctx.owner.publicKey, false))).eq(amountPerPeriod.muln(2)));
+        assert.isTrue((await balance(getAssociatedTokenAddressSync(ctx.mintA.publicKey, ctx.owner.publicKey, false))).eq(proceeds));
+        assert.isNull(await connection.getAccountInfo(dca));
+    });
+
+    it("Rejects a TWAP window longer than the observation history and an unbounded keeper fee", async () => {
+        const ctx = await setupPool(2403);
+        const params = {
+            isSwapA: true,
+            amountPerPeriod: new anchor.BN(10).mul(DECIMALS),
+            interval: new anchor.BN(60),
+            totalPeriods: 1,
+            twapWindow: 1,
+            maxSlippageBps: 300,
+            keeperFeeBps: 0,
+        };
+
+        // 63 periods of 60 seconds between the oldest and the latest of 64 observations
+        for (const [id, invalid] of [{twapWindow: 63 * 60 + 1}, {keeperFeeBps: 101}].entries()) {
+            try {
+                await createDca(program, connection, ctx.owner, ctx.provider, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, id, {...params, ...invalid});
+                assert.fail("Expected creation to fail");
+            } catch (err) {
+                assertError(err, "InvalidDcaParams");
+            }
+        }
+
+        await createDca(program, connection, ctx.owner, ctx.provider, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, 2, {...params, twapWindow: 63 * 60, keeperFeeBps: 100});
+    });
+
+    it("Rejects a slippage bound the pool fee and the keeper fee already use up", async () => {
+        const ctx = await setupPool(2404);
+        const params = {
+            isSwapA: true,
+            amountPerPeriod: new anchor.BN(10).mul(DECIMALS),
+            interval: new anchor.BN(60),
+            totalPeriods: 1,
+            twapWindow: 1,
+            maxSlippageBps: 80,
+            keeperFeeBps: 50,
+        };
+
+        // 30 bps pool fee and 50 bps keeper fee
+        try {
+            await createDca(program, connection, ctx.owner, ctx.provider, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, 0, params);
+            assert.fail("Expected creation to fail");
+        } catch (err) {
+            assertError(err, "DcaSlippageBelowFees");
+        }
+
+        await createDca(program, connection, ctx.owner, ctx.provider, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, 1, {...params, maxSlippageBps: 81});
+    });
+});
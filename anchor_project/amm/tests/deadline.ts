@@ -0,0 +1,83 @@
+import * as anchor from "@coral-xyz/anchor";
+import {Program} from "@coral-xyz/anchor";
+import {Amm} from "../target/types/amm";
+import {Keypair, Connection} from "@solana/web3.js";
+import {assert} from "chai";
+import {
+    addLiquidity,
+    airdrop,
+    createAmm,
+    createMintSafe,
+    createPool,
+    swap,
+    swapExactOut,
+    withdrawLiquidity
+} from "./helper";
+
+describe("deadline", () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    let connection: Connection = anchor.getProvider().connection;
+
+    const program = anchor.workspace.amm as Program<Amm>;
+    const DECIMALS = new anchor.BN(10).pow(new anchor.BN(9));
+    const LIQUIDITY = new anchor.BN(1000).mul(DECIMALS);
+
+    async function setupPool(ammIndex: number) {
+        const provider = Keypair.generate();
+        const trader = Keypair.generate();
+        const mintA = Keypair.generate();
+        const mintB = Keypair.generate();
+
+        await airdrop(connection, provider.publicKey);
+        await airdrop(connection, trader.publicKey);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintA);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintB);
+
+        const {ammPda} = await createAmm(program, provider, provider.publicKey, 30, ammIndex);
+        const pool = await createPool(program, provider, ammPda, mintA.publicKey, mintB.publicKey);
+        const {lpAmount} = await addLiquidity(program, connection, provider, provider, pool.poolPda, mintA.publicKey, mintB.publicKey, pool.mintLiquidityPda, LIQUIDITY, LIQUIDITY);
+
+        return {provider, trader, mintA, mintB, ammPda, lpAmount, ...pool};
+    }
+
+    async function now() {
+        const slot = await connection.getSlot("confirmed");
+        return new anchor.BN(await connection.getBlockTime(slot));
+    }
+
+    function assertDeadlineExceeded(err: any) {
+        assert.isTrue(err.toString().includes("DeadlineExceeded"), `Expected DeadlineExceeded error, got: ${err.toString()}`);
+    }
+
+    it("Instructions go through before the deadline", async () => {
+        const ctx = await setupPool(1600);
+        const deadline = (await now()).addn(60);
+        const amount = new anchor.BN(10).mul(DECIMALS);
+
+        await swap(program, connection, ctx.trader, ctx.provider, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, true, amount, new anchor.BN(0), deadline);
+        await swapExactOut(program, connection, ctx.trader, ctx.provider, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, true, amount, amount.muln(2), deadline);
+        await addLiquidity(program, connection, ctx.provider, ctx.provider, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, ctx.mintLiquidityPda, amount, amount, deadline);
+        await withdrawLiquidity(program, connection, ctx.provider, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, ctx.mintLiquidityPda, ctx.authorityPda, ctx.lpAmount, deadline);
+    });
+
+    it("Instructions fail once the deadline has passed", async () => {
+        const ctx = await setupPool(1601);
+        const deadline = (await now()).subn(60);
+        const amount = new anchor.BN(10).mul(DECIMALS);
+
+        const calls = [
+            () => swap(program, connection, ctx.trader, ctx.provider, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, true, amount, new anchor.BN(0), deadline),
+            () => swapExactOut(program, connection, ctx.trader, ctx.provider, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, true, amount, amount.muln(2), deadline),
+            () => addLiquidity(program, connection, ctx.provider, ctx.provider, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, ctx.mintLiquidityPda, amount, amount, deadline),
+            () => withdrawLiquidity(program, connection, ctx.provider, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, ctx.mintLiquidityPda, ctx.authorityPda, ctx.lpAmount, deadline),
+        ];
+        for (const call of calls) {
+            try {
+                await call();
+                assert.fail("Expected transaction to fail");
+            } catch (err) {
+                assertDeadlineExceeded(err);
+            }
+        }
+    });
+});
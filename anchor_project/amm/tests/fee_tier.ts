@@ -0,0 +1,139 @@
+import * as anchor from "@coral-xyz/anchor";
+import {Program} from "@coral-xyz/anchor";
+import {Amm} from "../target/types/amm";
+import {Keypair, Connection} from "@solana/web3.js";
+import {assert} from "chai";
+import {getAccount, getAssociatedTokenAddressSync} from "@solana/spl-token";
+import {
+    addFeeTier,
+    addLiquidity,
+    airdrop,
+    createAmm,
+    createMintSafe,
+    createPool,
+    removeFeeTier,
+    swap
+} from "./helper";
+
+describe("fee_tier", () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    let connection: Connection = anchor.getProvider().connection;
+
+    const program = anchor.workspace.amm as Program<Amm>;
+    const DECIMALS = new anchor.BN(10).pow(new anchor.BN(9));
+    const LIQUIDITY = new anchor.BN(1000).mul(DECIMALS);
+
+    function expectedOut(amount: anchor.BN, feeBps: number): anchor.BN {
+        const amountEff = amount.mul(new anchor.BN(10000 - feeBps)).div(new anchor.BN(10000));
+        return LIQUIDITY.sub(LIQUIDITY.mul(LIQUIDITY).div(LIQUIDITY.add(amountEff)));
+    }
+
+    it("Same pair can have one pool per fee tier", async () => {
+        const provider = Keypair.generate();
+        const trader1 = Keypair.generate();
+        const trader2 = Keypair.generate();
+        const admin = Keypair.generate();
+        const mintA = Keypair.generate();
+        const mintB = Keypair.generate();
+        const ammIndex = 900;
+
+        await airdrop(connection, provider.publicKey);
+        await airdrop(connection, trader1.publicKey);
+        await airdrop(connection, trader2.publicKey);
+        await airdrop(connection, admin.publicKey);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintA);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintB);
+
+        const {ammPda} = await createAmm(program, provider, admin.publicKey, 30, ammIndex);
+        await addFeeTier(program, admin, ammPda, 5);
+        await addFeeTier(program, admin, ammPda, 100);
+
+        const ammData = await program.account.amm.fetch(ammPda);
+        assert.deepEqual(ammData.feeTiers, [5, 100]);
+
+        const pool5 = await createPool(program, provider, ammPda, mintA.publicKey, mintB.publicKey, 5);
+        const pool100 = await createPool(program, provider, ammPda, mintA.publicKey, mintB.publicKey, 100);
+        assert.notStrictEqual(pool5.poolPda.toBase58(), pool100.poolPda.toBase58());
+        assert.notStrictEqual(pool5.mintLiquidityPda.toBase58(), pool100.mintLiquidityPda.toBase58());
+
+        const pool5Data = await program.account.ammPool.fetch(pool5.poolPda);
+        assert.strictEqual(pool5Data.feeTier, 5);
+        assert.strictEqual(pool5Data.feeBps, 5);
+
+        await addLiquidity(program, connection, provider, provider, pool5.poolPda, mintA.publicKey, mintB.publicKey, pool5.mintLiquidityPda, LIQUIDITY, LIQUIDITY);
+        await addLiquidity(program, connection, provider, provider, pool100.poolPda, mintA.publicKey, mintB.publicKey, pool100.mintLiquidityPda, LIQUIDITY, LIQUIDITY);
+
+        const amount = new anchor.BN(10).mul(DECIMALS);
+        await swap(program, connection, trader1, provider, ammPda, pool5.poolPda, mintA.publicKey, mintB.publicKey, true, amount, new anchor.BN(0));
+        await swap(program, connection, trader2, provider, ammPda, pool100.poolPda, mintA.publicKey, mintB.publicKey, true, amount, new anchor.BN(0));
+
+        const output1 = new anchor.BN((await getAccount(connection, getAssociatedTokenAddressSync(mintB.publicKey, trader1.publicKey, false))).amount.toString());
+        const output2 = new anchor.BN((await getAccount(connection, getAssociatedTokenAddressSync(mintB.publicKey, trader2.publicKey, false))).amount.toString());
+        assert.isTrue(output1.eq(expectedOut(amount, 5)), `Trader1 should get ${expectedOut(amount, 5).toString()} B, got ${output1.toString()}`);
+        assert.isTrue(output2.eq(expectedOut(amount, 100)), `Trader2 should get ${expectedOut(amount, 100).toString()} B, got ${output2.toString()}`);
+    });
+
+    it("Cannot create a pool with an unregistered fee tier", async () => {
+        const provider = Keypair.generate();
+        const admin = Keypair.generate();
+        const mintA = Keypair.generate();
+        const mintB = Keypair.generate();
+        const ammIndex = 901;
+
+        await airdrop(connection, provider.publicKey);
+        await airdrop(connection, admin.publicKey);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintA);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintB);
+
+        const {ammPda} = await createAmm(program, provider, admin.publicKey, 30, ammIndex);
+        await addFeeTier(program, admin, ammPda, 5);
+        await removeFeeTier(program, admin, ammPda, 5);
+
+        try {
+            await createPool(program, provider, ammPda, mintA.publicKey, mintB.publicKey, 5);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("FeeTierNotFound"), `Expected FeeTierNotFound error, got: ${err.toString()}`);
+        }
+    });
+
+    it("Fee tier management errors", async () => {
+        const signer = Keypair.generate();
+        const admin = Keypair.generate();
+        const ammIndex = 902;
+
+        await airdrop(connection, signer.publicKey);
+        await airdrop(connection, admin.publicKey);
+
+        const {ammPda} = await createAmm(program, signer, admin.publicKey, 30, ammIndex);
+
+        try {
+            await addFeeTier(program, signer, ammPda, 5);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("Unauthorized"), `Expected Unauthorized error, got: ${err.toString()}`);
+        }
+
+        try {
+            await addFeeTier(program, admin, ammPda, 0);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("InvalidFee"), `Expected InvalidFee error, got: ${err.toString()}`);
+        }
+
+        await addFeeTier(program, admin, ammPda, 5);
+        try {
+            await addFeeTier(program, admin, ammPda, 5);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("FeeTierAlreadyExists"), `Expected FeeTierAlreadyExists error, got: ${err.toString()}`);
+        }
+
+        try {
+            await removeFeeTier(program, admin, ammPda, 30);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("FeeTierNotFound"), `Expected FeeTierNotFound error, got: ${err.toString()}`);
+        }
+    });
+});
@@ -0,0 +1,139 @@
+import * as anchor from "@coral-xyz/anchor";
+import {Program} from "@coral-xyz/anchor";
+import {Amm} from "../target/types/amm";
+import {Keypair, Connection, PublicKey, Transaction, TransactionInstruction} from "@solana/web3.js";
+import {assert} from "chai";
+import {createAssociatedTokenAccount, getAccount, getAssociatedTokenAddressSync, mintTo, TOKEN_PROGRAM_ID} from "@solana/spl-token";
+import {
+    addLiquidity,
+    airdrop,
+    createAmm,
+    createMintSafe,
+    createPool,
+    flashLoanInstructions,
+    getPoolReserves
+} from "./helper";
+
+describe("flash_loan", () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    let connection: Connection = anchor.getProvider().connection;
+
+    const program = anchor.workspace.amm as Program<Amm>;
+    const DECIMALS = new anchor.BN(10).pow(new anchor.BN(9));
+    const LIQUIDITY = new anchor.BN(1000).mul(DECIMALS);
+    const FEE = 30;
+    const FUNDS = new anchor.BN(10).mul(DECIMALS);
+
+    async function setupPool(ammIndex: number) {
+        const provider = Keypair.generate();
+        const borrower = Keypair.generate();
+        const mintA = Keypair.generate();
+        const mintB = Keypair.generate();
+
+        await airdrop(connection, provider.publicKey);
+        await airdrop(connection, borrower.publicKey);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintA);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintB);
+
+        const {ammPda} = await createAmm(program, provider, provider.publicKey, FEE, ammIndex);
+        const pool = await createPool(program, provider, ammPda, mintA.publicKey, mintB.publicKey);
+        await addLiquidity(program, connection, provider, provider, pool.poolPda, mintA.publicKey, mintB.publicKey, pool.mintLiquidityPda, LIQUIDITY, LIQUIDITY);
+
+        // enough to pay the fee, the loan itself pays the principal back
+        const borrowerAccountA = await createAssociatedTokenAccount(connection, borrower, mintA.publicKey, borrower.publicKey);
+        await mintTo(connection, provider, mintA.publicKey, borrowerAccountA, provider, FUNDS.toNumber());
+
+        return {provider, borrower, mintA, mintB, ammPda, borrowerAccountA, ...pool};
+    }
+
+    async function send(borrower: Keypair, instructions: TransactionInstruction[]) {
+        const tx = new Transaction().add(...instructions);
+        await anchor.getProvider().sendAndConfirm(tx, [borrower], {commitment: "confirmed"});
+    }
+
+    function assertError(err: any, name: string) {
+        assert.isTrue(err.toString().includes(name), `Expected ${name} error, got: ${err.toString()}`);
+    }
+
+    async function balance(account: PublicKey) {
+        return new anchor.BN((await getAccount(connection, account)).amount.toString());
+    }
+
+    it("Borrow and repay in one transaction leaves the fee with the LPs", async () => {
+        const ctx = await setupPool(2100);
+        const amount = new anchor.BN(500).mul(DECIMALS);
+        const fee = amount.muln(FEE).divn(10000);
+
+        const {borrowIx, repayIx} = await flashLoanInstructions(program, ctx.borrower.publicKey, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, true, amount);
+        await send(ctx.borrower, [borrowIx, repayIx]);
+
+        const reserves = await getPoolReserves(connection, ctx.mintA.publicKey, ctx.mintB.publicKey, ctx.authorityPda);
+        assert.isTrue(reserves.reserveA.eq(LIQUIDITY.add(fee)));
+        assert.isTrue(reserves.reserveB.eq(LIQUIDITY));
+        assert.isTrue((await balance(ctx.borrowerAccountA)).eq(FUNDS.sub(fee)));
+
+        const poolData = await program.account.ammPool.fetch(ctx.poolPda);
+        assert.isFalse(poolData.flashLoanActive);
+        assert.isTrue(poolData.protocolFeesA.eqn(0));
+    });
+
+    it("Borrow needs a matching repay later in the transaction", async () => {
+        const ctx = await setupPool(2101);
+        const amount = new anchor.BN(100).mul(DECIMALS);
+        const {borrowIx, repayIx} = await flashLoanInstructions(program, ctx.borrower.publicKey, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, true, amount);
+        const smallerRepay = await flashLoanInstructions(program, ctx.borrower.publicKey, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, true, amount, amount.subn(1));
+
+        for (const instructions of [[borrowIx], [borrowIx, smallerRepay.repayIx]]) {
+            try {
+                await send(ctx.borrower, instructions);
+                assert.fail("Expected transaction to fail");
+            } catch (err) {
+                assertError(err, "FlashRepayMissing");
+            }
+        }
+
+        try {
+            await send(ctx.borrower, [repayIx]);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertError(err, "NoFlashLoan");
+        }
+    });
+
+    it("Pool is locked between borrow and repay", async () => {
+        const ctx = await setupPool(2102);
+        const amount = new anchor.BN(100).mul(DECIMALS);
+        const {borrowIx, repayIx} = await flashLoanInstructions(program, ctx.borrower.publicKey, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, true, amount);
+
+        // swap B for the A that is short while the loan is out
+        await createAssociatedTokenAccount(connection, ctx.borrower, ctx.mintB.publicKey, ctx.borrower.publicKey);
+        await mintTo(connection, ctx.provider, ctx.mintB.publicKey, getAssociatedTokenAddressSync(ctx.mintB.publicKey, ctx.borrower.publicKey, false), ctx.provider, amount.toNumber());
+        const swapIx = await program.methods.swap(false, amount, new anchor.BN(0), null, false).accounts({
+            amm: ctx.ammPda,
+            pool: ctx.poolPda,
+            trader: ctx.borrower.publicKey,
+            mintA: ctx.mintA.publicKey,
+            mintB: ctx.mintB.publicKey,
+            outputMint: ctx.mintA.publicKey,
+            recipient: ctx.borrower.publicKey,
+            recipientAccount: ctx.borrowerAccountA,
+            payer: ctx.borrower.publicKey,
+            outputTokenProgram: TOKEN_PROGRAM_ID,
+        }).instruction();
+
+        try {
+            await send(ctx.borrower, [borrowIx, swapIx, repayIx]);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertError(err, "FlashLoanActive");
+        }
+
+        // a second loan of the same pool waits for the first repay
+        try {
+            await send(ctx.borrower, [borrowIx, borrowIx, repayIx, repayIx]);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertError(err, "FlashLoanActive");
+        }
+    });
+});
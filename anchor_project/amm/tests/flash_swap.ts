@@ -0,0 +1,128 @@
+import * as anchor from "@coral-xyz/anchor";
+import {Program} from "@coral-xyz/anchor";
+import {Amm} from "../target/types/amm";
+import {Keypair, Connection, PublicKey} from "@solana/web3.js";
+import {assert} from "chai";
+import {
+    createAssociatedTokenAccount,
+    getAccount,
+    getAssociatedTokenAddressSync,
+    mintTo,
+    TOKEN_PROGRAM_ID
+} from "@solana/spl-token";
+import {
+    addLiquidity,
+    airdrop,
+    createAmm,
+    createMintSafe,
+    createPool,
+    getPoolReserves,
+    quotePoolAccounts
+} from "./helper";
+
+describe("flash_swap", () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    let connection: Connection = anchor.getProvider().connection;
+
+    const program = anchor.workspace.amm as Program<Amm>;
+    const receiver = anchor.workspace.flashSwapReceiver as Program;
+    const DECIMALS = new anchor.BN(10).pow(new anchor.BN(9));
+    const LIQUIDITY = new anchor.BN(1000).mul(DECIMALS);
+    const OBSERVATION_PERIOD = 60;
+
+    async function setupPool(ammIndex: number) {
+        const provider = Keypair.generate();
+        const borrower = Keypair.generate();
+        const mintA = Keypair.generate();
+        const mintB = Keypair.generate();
+
+        await airdrop(connection, provider.publicKey);
+        await airdrop(connection, borrower.publicKey);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintA);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintB);
+
+        const {ammPda} = await createAmm(program, provider, provider.publicKey, 30, ammIndex);
+        const pool = await createPool(program, provider, ammPda, mintA.publicKey, mintB.publicKey);
+        await addLiquidity(program, connection, provider, provider, pool.poolPda, mintA.publicKey, mintB.publicKey, pool.mintLiquidityPda, LIQUIDITY, LIQUIDITY);
+
+        const borrowerAccountA = await createAssociatedTokenAccount(connection, borrower, mintA.publicKey, borrower.publicKey);
+        const borrowerAccountB = await createAssociatedTokenAccount(connection, borrower, mintB.publicKey, borrower.publicKey);
+        await mintTo(connection, provider, mintA.publicKey, borrowerAccountA, provider, new anchor.BN(100).mul(DECIMALS).toNumber());
+
+        return {provider, borrower, mintA, mintB, ammPda, borrowerAccountA, borrowerAccountB, pool, ...pool};
+    }
+
+    // borrows B and lets the receiver program pay `repayA` of token A back
+    async function flashSwapB(ctx: Awaited<ReturnType<typeof setupPool>>, amountBOut: anchor.BN, repayA: anchor.BN) {
+        await program.methods.flashSwap(new anchor.BN(0), amountBOut, repayA.toArrayLike(Buffer, "le", 8)).accounts({
+            amm: ctx.ammPda,
+            pool: ctx.poolPda,
+            observations: ctx.observationsPda,
+            mintA: ctx.mintA.publicKey,
+            mintB: ctx.mintB.publicKey,
+            borrower: ctx.borrower.publicKey,
+            recipientAccountA: ctx.borrowerAccountA,
+            recipientAccountB: ctx.borrowerAccountB,
+            callbackProgram: receiver.programId,
+        }).remainingAccounts([
+            {pubkey: ctx.borrower.publicKey, isSigner: true, isWritable: false},
+            {pubkey: ctx.mintA.publicKey, isSigner: false, isWritable: false},
+            {pubkey: ctx.borrowerAccountA, isSigner: false, isWritable: true},
+            {pubkey: ctx.poolAccountA, isSigner: false, isWritable: true},
+            {pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false},
+        ]).signers([ctx.borrower]).rpc({commitment: "confirmed"});
+    }
+
+    async function balance(account: PublicKey) {
+        return new anchor.BN((await getAccount(connection, account)).amount.toString());
+    }
+
+    it("Borrower gets the output first and repays in the callback", async () => {
+        const ctx = await setupPool(2000);
+        const amountOut = new anchor.BN(10).mul(DECIMALS);
+        const accounts = quotePoolAccounts(ctx.ammPda, ctx.pool, ctx.mintA.publicKey, ctx.mintB.publicKey);
+        const quote = await program.methods.quoteSwapExactOut(true, amountOut).accounts(accounts).view();
+
+        const balanceABefore = await balance(ctx.borrowerAccountA);
+        await flashSwapB(ctx, amountOut, quote.amountIn);
+
+        assert.isTrue((await balance(ctx.borrowerAccountB)).eq(amountOut));
+        assert.isTrue(balanceABefore.sub(await balance(ctx.borrowerAccountA)).eq(quote.amountIn));
+
+        const reserves = await getPoolReserves(connection, ctx.mintA.publicKey, ctx.mintB.publicKey, ctx.authorityPda);
+        assert.isTrue(reserves.reserveA.mul(reserves.reserveB).gte(LIQUIDITY.mul(LIQUIDITY)));
+    });
+
+    it("Fails when the callback does not pay the pool back", async () => {
+        const ctx = await setupPool(2001);
+        const amountOut = new anchor.BN(10).mul(DECIMALS);
+
+        for (const repay of [new anchor.BN(0), amountOut]) {
+            try {
+                await flashSwapB(ctx, amountOut, repay);
+                assert.fail("Expected transaction to fail");
+            } catch (err) {
+                assert.isTrue(err.toString().includes("InvariantViolated"), `Expected InvariantViolated error, got: ${err.toString()}`);
+            }
+        }
+
+        assert.isTrue((await balance(ctx.borrowerAccountB)).eqn(0));
+    });
+
+    it("Records an observation like a swap", async () => {
+        const ctx = await setupPool(2002);
+        const amountOut = new anchor.BN(10).mul(DECIMALS);
+        const accounts = quotePoolAccounts(ctx.ammPda, ctx.pool, ctx.mintA.publicKey, ctx.mintB.publicKey);
+        const quote = await program.methods.quoteSwapExactOut(true, amountOut).accounts(accounts).view();
+
+        // a pool writes one observation per period, the deposit wrote the first one
+        await new Promise((resolve) => setTimeout(resolve, (OBSERVATION_PERIOD + 1) * 1000));
+        await flashSwapB(ctx, amountOut, quote.amountIn);
+
+        const {observations} = await program.account.observations.fetch(ctx.observationsPda);
+        assert.equal(observations.length, 2);
+        const pool = await program.account.ammPool.fetch(ctx.poolPda);
+        assert.isTrue(pool.lastUpdateTimestamp.eq(observations[1].timestamp));
+        assert.isTrue(pool.priceACumulative.eq(observations[1].priceACumulative));
+    });
+});
@@ -1,4 +1,4 @@
-import { Connection, PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
+import { AccountMeta, Connection, PublicKey, Keypair, SystemProgram, SYSVAR_INSTRUCTIONS_PUBKEY } from "@solana/web3.js";
 import * as anchor from "@coral-xyz/anchor";
 import {assert} from "chai";
 import { Amm } from "../target/types/amm";
@@ -16,6 +16,22 @@ export function indexToSeed(index: number) {
   return indexSeed;
 }
 
+// Admin keys are not signers in the IDL so they can be a `Multisig`, a wallet approves through the remaining accounts
+export function signerMetas(signers: Keypair[]) {
+  return signers.map((signer) => ({ pubkey: signer.publicKey, isSigner: true, isWritable: false }));
+}
+
+// SPL Token or Token-2022, whichever program owns `mint`
+export async function mintTokenProgram(connection: Connection, mint: PublicKey) {
+  return (await connection.getAccountInfo(mint)).owner;
+}
+
+export function feeTierToSeed(feeTier: number) {
+  const feeTierSeed = Buffer.alloc(2)
+  feeTierSeed.writeUInt16LE(feeTier)
+  return feeTierSeed;
+}
+
 export async function checkAmm(
     program: anchor.Program<Amm>,
     amm: PublicKey,
@@ -47,19 +63,25 @@ export interface CreateAmmResult {
   ammPda: PublicKey;
 }
 
+export function ammAddress(program: anchor.Program<Amm>, index: number) {
+  const [ammPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("AMM"), indexToSeed(index)],
+    program.programId
+  );
+  return ammPda;
+}
+
 export async function createAmm(
   program: anchor.Program<Amm>,
   signer: Keypair,
   adminAccount: PublicKey,
   fee: number,
-  index: number
+  index: number,
+  timelockDelay = 0
 ): Promise<CreateAmmResult> {
-  const [ammPda] = PublicKey.findProgramAddressSync(
-    [Buffer.from("AMM"), indexToSeed(index)],
-    program.programId
-  );
+  const ammPda = ammAddress(program, index);
 
-  await program.methods.createAmm(fee, index).accounts({
+  await program.methods.createAmm(fee, index, new anchor.BN(timelockDelay)).accounts({
     amm: ammPda,
     adminAccount: adminAccount,
     signer: signer.publicKey,
@@ -69,12 +91,377 @@ export async function createAmm(
   return { ammPda };
 }
 
+export const ConfigChange = {
+  fee: (fee: number) => ({ fee: { fee } }),
+  protocolFee: (protocolFeeBps: number) => ({ protocolFee: { protocolFeeBps } }),
+  poolFee: (pool: PublicKey, feeBps: number | null) => ({ poolFee: { pool, feeBps } }),
+  admin: (newAdmin: PublicKey) => ({ admin: { newAdmin } }),
+  unpause: () => ({ unpause: {} }),
+  unpausePool: (pool: PublicKey) => ({ unpausePool: { pool } }),
+  timelockDelay: (timelockDelay: number) => ({ timelockDelay: { timelockDelay: new anchor.BN(timelockDelay) } }),
+  multisigConfig: (multisig: PublicKey) => ({ multisigConfig: { multisig } }),
+};
+
+export const ConfigChangeKind = {
+  fee: { fee: {} },
+  protocolFee: { protocolFee: {} },
+  poolFee: { poolFee: {} },
+  admin: { admin: {} },
+  unpause: { unpause: {} },
+  unpausePool: { unpausePool: {} },
+  timelockDelay: { timelockDelay: {} },
+  multisigConfig: { multisigConfig: {} },
+};
+
+// Pending slot of a change, e.g. `ConfigChangeKind.fee` for `ConfigChange.fee(...)`
+export function configChangeKind(change: any) {
+  return { [Object.keys(change)[0]]: {} };
+}
+
+// `ammPda` is the AMM whose timelock delays changes of the signer set, it does not have to exist yet
+export async function createMultisig(
+  program: anchor.Program<Amm>,
+  payer: Keypair,
+  ammPda: PublicKey,
+  signers: PublicKey[],
+  threshold: number
+): Promise<PublicKey> {
+  const createKey = Keypair.generate().publicKey;
+  const [multisigPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("MULTISIG"), createKey.toBuffer()],
+    program.programId
+  );
+
+  await program.methods.createMultisig(createKey, ammPda, signers, threshold).accounts({
+    multisig: multisigPda,
+    payer: payer.publicKey,
+  }).signers([payer]).rpc({ commitment: "confirmed" });
+
+  return multisigPda;
+}
+
+// Queues a `ConfigChange::MultisigConfig`, executed like any other change once the timelock delay passed
+export async function setMultisigConfig(
+  program: anchor.Program<Amm>,
+  ammPda: PublicKey,
+  multisigPda: PublicKey,
+  approvers: Keypair[],
+  signers: PublicKey[],
+  threshold: number
+) {
+  await program.methods.setMultisigConfig(signers, threshold).accounts({
+    amm: ammPda,
+    multisig: multisigPda,
+  }).remainingAccounts(signerMetas(approvers)).signers(approvers).rpc({ commitment: "confirmed" });
+}
+
+// `poolPda` is the pool a `poolFee` or `unpausePool` change targets, those are queued on the pool
+export async function updateAmmConfig(
+  program: anchor.Program<Amm>,
+  authority: Keypair,
+  ammPda: PublicKey,
+  change: any,
+  poolPda: PublicKey | null = null
+) {
+  await program.methods.updateAmmConfig(change).accounts({
+    amm: ammPda,
+    pool: poolPda,
+    authority: authority.publicKey,
+  }).remainingAccounts(signerMetas([authority])).signers([authority]).rpc({ commitment: "confirmed" });
+}
+
+export async function executeConfigChange(
+  program: anchor.Program<Amm>,
+  ammPda: PublicKey,
+  kind: any,
+  poolPda: PublicKey | null = null,
+  multisigPda: PublicKey | null = null
+) {
+  await program.methods.executeConfigChange(kind).accounts({
+    amm: ammPda,
+    pool: poolPda,
+    multisig: multisigPda,
+  }).rpc({ commitment: "confirmed" });
+}
+
+export async function cancelConfigChange(
+  program: anchor.Program<Amm>,
+  authority: Keypair,
+  ammPda: PublicKey,
+  kind: any,
+  poolPda: PublicKey | null = null
+) {
+  await program.methods.cancelConfigChange(kind).accounts({
+    amm: ammPda,
+    pool: poolPda,
+    authority: authority.publicKey,
+  }).remainingAccounts(signerMetas([authority])).signers([authority]).rpc({ commitment: "confirmed" });
+}
+
+// Queues and executes a config change right away, only works while the timelock delay is 0
+export async function applyConfigChange(
+  program: anchor.Program<Amm>,
+  authority: Keypair,
+  ammPda: PublicKey,
+  change: any,
+  poolPda: PublicKey | null = null
+) {
+  await updateAmmConfig(program, authority, ammPda, change, poolPda);
+  await executeConfigChange(program, ammPda, configChangeKind(change), poolPda);
+}
+
+// Queues a `ConfigChange::Admin`, executed like any other change once the timelock delay passed
+export async function proposeAdmin(
+  program: anchor.Program<Amm>,
+  admin: Keypair,
+  ammPda: PublicKey,
+  newAdmin: PublicKey
+) {
+  await program.methods.proposeAdmin(newAdmin).accounts({
+    amm: ammPda,
+    admin: admin.publicKey,
+  }).remainingAccounts(signerMetas([admin])).signers([admin]).rpc({ commitment: "confirmed" });
+}
+
+export async function acceptAdmin(
+  program: anchor.Program<Amm>,
+  newAdmin: Keypair,
+  ammPda: PublicKey
+) {
+  await program.methods.acceptAdmin().accounts({
+    amm: ammPda,
+    newAdmin: newAdmin.publicKey,
+  }).remainingAccounts(signerMetas([newAdmin])).signers([newAdmin]).rpc({ commitment: "confirmed" });
+}
+
+export async function cancelAdminTransfer(
+  program: anchor.Program<Amm>,
+  admin: Keypair,
+  ammPda: PublicKey
+) {
+  await program.methods.cancelAdminTransfer().accounts({
+    amm: ammPda,
+    admin: admin.publicKey,
+  }).remainingAccounts(signerMetas([admin])).signers([admin]).rpc({ commitment: "confirmed" });
+}
+
+export async function pause(
+  program: anchor.Program<Amm>,
+  pauser: Keypair,
+  ammPda: PublicKey
+) {
+  await program.methods.pause().accounts({
+    amm: ammPda,
+    pauser: pauser.publicKey,
+  }).remainingAccounts(signerMetas([pauser])).signers([pauser]).rpc({ commitment: "confirmed" });
+}
+
+export async function pausePool(
+  program: anchor.Program<Amm>,
+  pauser: Keypair,
+  ammPda: PublicKey,
+  poolPda: PublicKey
+) {
+  await program.methods.pausePool().accounts({
+    amm: ammPda,
+    pool: poolPda,
+    pauser: pauser.publicKey,
+  }).remainingAccounts(signerMetas([pauser])).signers([pauser]).rpc({ commitment: "confirmed" });
+}
+
+export const Role = {
+  admin: { admin: {} },
+  feeManager: { feeManager: {} },
+  pauser: { pauser: {} },
+  treasury: { treasury: {} },
+  poolCurator: { poolCurator: {} },
+};
+
+export async function setRole(
+  program: anchor.Program<Amm>,
+  admin: Keypair,
+  ammPda: PublicKey,
+  role: any,
+  key: PublicKey
+) {
+  await program.methods.setRole(role, key).accounts({
+    amm: ammPda,
+    admin: admin.publicKey,
+  }).remainingAccounts(signerMetas([admin])).signers([admin]).rpc({ commitment: "confirmed" });
+}
+
+// Queues a `ConfigChange::PoolFee` for the pool, executed once the timelock delay passed
+export async function setPoolFee(
+  program: anchor.Program<Amm>,
+  feeManager: Keypair,
+  ammPda: PublicKey,
+  poolPda: PublicKey,
+  feeBps: number | null
+) {
+  await program.methods.setPoolFee(feeBps).accounts({
+    amm: ammPda,
+    pool: poolPda,
+    feeManager: feeManager.publicKey,
+  }).remainingAccounts(signerMetas([feeManager])).signers([feeManager]).rpc({ commitment: "confirmed" });
+}
+
+export async function addFeeTier(
+  program: anchor.Program<Amm>,
+  feeManager: Keypair,
+  ammPda: PublicKey,
+  feeTier: number
+) {
+  await program.methods.addFeeTier(feeTier).accounts({
+    amm: ammPda,
+    feeManager: feeManager.publicKey,
+  }).remainingAccounts(signerMetas([feeManager])).signers([feeManager]).rpc({ commitment: "confirmed" });
+}
+
+export async function removeFeeTier(
+  program: anchor.Program<Amm>,
+  feeManager: Keypair,
+  ammPda: PublicKey,
+  feeTier: number
+) {
+  await program.methods.removeFeeTier(feeTier).accounts({
+    amm: ammPda,
+    feeManager: feeManager.publicKey,
+  }).remainingAccounts(signerMetas([feeManager])).signers([feeManager]).rpc({ commitment: "confirmed" });
+}
+
+export async function addTransferHookProgram(
+  program: anchor.Program<Amm>,
+  admin: Keypair,
+  ammPda: PublicKey,
+  programId: PublicKey
+) {
+  await program.methods.addTransferHookProgram(programId).accounts({
+    amm: ammPda,
+    admin: admin.publicKey,
+  }).remainingAccounts(signerMetas([admin])).signers([admin]).rpc({ commitment: "confirmed" });
+}
+
+export async function removeTransferHookProgram(
+  program: anchor.Program<Amm>,
+  admin: Keypair,
+  ammPda: PublicKey,
+  programId: PublicKey
+) {
+  await program.methods.removeTransferHookProgram(programId).accounts({
+    amm: ammPda,
+    admin: admin.publicKey,
+  }).remainingAccounts(signerMetas([admin])).signers([admin]).rpc({ commitment: "confirmed" });
+}
+
+export async function setPermissionedPoolCreation(
+  program: anchor.Program<Amm>,
+  poolCurator: Keypair,
+  ammPda: PublicKey,
+  permissioned: boolean
+) {
+  await program.methods.setPermissionedPoolCreation(permissioned).accounts({
+    amm: ammPda,
+    poolCurator: poolCurator.publicKey,
+  }).remainingAccounts(signerMetas([poolCurator])).signers([poolCurator]).rpc({ commitment: "confirmed" });
+}
+
+export async function addPoolCreator(
+  program: anchor.Program<Amm>,
+  poolCurator: Keypair,
+  ammPda: PublicKey,
+  creator: PublicKey
+): Promise<PublicKey> {
+  const [poolCreatorPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("POOL_CREATOR"), ammPda.toBuffer(), creator.toBuffer()],
+    program.programId
+  );
+
+  await program.methods.addPoolCreator(creator).accounts({
+    amm: ammPda,
+    poolCreator: poolCreatorPda,
+    poolCurator: poolCurator.publicKey,
+    payer: poolCurator.publicKey,
+  }).remainingAccounts(signerMetas([poolCurator])).signers([poolCurator]).rpc({ commitment: "confirmed" });
+
+  return poolCreatorPda;
+}
+
+export async function removePoolCreator(
+  program: anchor.Program<Amm>,
+  poolCurator: Keypair,
+  ammPda: PublicKey,
+  poolCreatorPda: PublicKey
+) {
+  await program.methods.removePoolCreator().accounts({
+    amm: ammPda,
+    poolCreator: poolCreatorPda,
+    poolCurator: poolCurator.publicKey,
+  }).remainingAccounts(signerMetas([poolCurator])).signers([poolCurator]).rpc({ commitment: "confirmed" });
+}
+
+export const MintPolicy = {
+  open: { open: {} },
+  denylist: { denylist: {} },
+  allowlist: { allowlist: {} },
+};
+
+export async function setMintPolicy(
+  program: anchor.Program<Amm>,
+  poolCurator: Keypair,
+  ammPda: PublicKey,
+  mintPolicy: any
+) {
+  await program.methods.setMintPolicy(mintPolicy).accounts({
+    amm: ammPda,
+    poolCurator: poolCurator.publicKey,
+  }).remainingAccounts(signerMetas([poolCurator])).signers([poolCurator]).rpc({ commitment: "confirmed" });
+}
+
+export async function setMintEntry(
+  program: anchor.Program<Amm>,
+  poolCurator: Keypair,
+  ammPda: PublicKey,
+  mint: PublicKey,
+  allowed: boolean
+): Promise<PublicKey> {
+  const [mintEntryPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("MINT_ENTRY"), ammPda.toBuffer(), mint.toBuffer()],
+    program.programId
+  );
+
+  await program.methods.setMintEntry(allowed).accounts({
+    amm: ammPda,
+    mint: mint,
+    mintEntry: mintEntryPda,
+    poolCurator: poolCurator.publicKey,
+    payer: poolCurator.publicKey,
+  }).remainingAccounts(signerMetas([poolCurator])).signers([poolCurator]).rpc({ commitment: "confirmed" });
+
+  return mintEntryPda;
+}
+
+export async function removeMintEntry(
+  program: anchor.Program<Amm>,
+  poolCurator: Keypair,
+  ammPda: PublicKey,
+  mintEntryPda: PublicKey
+) {
+  await program.methods.removeMintEntry().accounts({
+    amm: ammPda,
+    mintEntry: mintEntryPda,
+    poolCurator: poolCurator.publicKey,
+  }).remainingAccounts(signerMetas([poolCurator])).signers([poolCurator]).rpc({ commitment: "confirmed" });
+}
+
 export interface CreatePoolResult {
   poolPda: PublicKey;
+  observationsPda: PublicKey;
   mintLiquidityPda: PublicKey;
   authorityPda: PublicKey;
   poolAccountA: PublicKey;
   poolAccountB: PublicKey;
+  tokenProgramA: PublicKey;
+  tokenProgramB: PublicKey;
 }
 
 export async function createPool(
@@ -82,7 +469,12 @@ export async function createPool(
   signer: Keypair,
   ammPda: PublicKey,
   mintA: PublicKey,
-  mintB: PublicKey
+  mintB: PublicKey,
+  feeTier: number = 0,
+  feeBps: number | null = null,
+  poolCreator: PublicKey | null = null,
+  roleAuthority: PublicKey | null = null,
+  approvers: Keypair[] = []
 ): Promise<CreatePoolResult> {
   const [poolPda] = PublicKey.findProgramAddressSync(
     [
@@ -90,16 +482,23 @@ export async function createPool(
       ammPda.toBuffer(),
       mintA.toBuffer(),
       mintB.toBuffer(),
+      feeTierToSeed(feeTier),
     ],
     program.programId
   );
 
+  const [observationsPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("OBSERVATIONS"), poolPda.toBuffer()],
+    program.programId
+  );
+
   const [mintLiquidityPda] = PublicKey.findProgramAddressSync(
     [
       Buffer.from("AMM_MINT_LIQUIDITY"),
       ammPda.toBuffer(),
       mintA.toBuffer(),
       mintB.toBuffer(),
+      feeTierToSeed(feeTier),
     ],
     program.programId
   );
@@ -110,25 +509,33 @@ export async function createPool(
       ammPda.toBuffer(),
       mintA.toBuffer(),
       mintB.toBuffer(),
+      feeTierToSeed(feeTier),
     ],
     program.programId
   );
 
+  const connection = program.provider.connection;
+  const tokenProgramA = await mintTokenProgram(connection, mintA);
+  const tokenProgramB = await mintTokenProgram(connection, mintB);
+
   const poolAccountA = getAssociatedTokenAddressSync(
     mintA,
     authorityPda,
-    true
+    true,
+    tokenProgramA
   );
 
   const poolAccountB = getAssociatedTokenAddressSync(
     mintB,
     authorityPda,
-    true
+    true,
+    tokenProgramB
   );
 
-  await program.methods.createPool().accounts({
+  await program.methods.createPool(feeTier, feeBps).accounts({
     amm: ammPda,
     pool: poolPda,
+    observations: observationsPda,
     mintLiquidity: mintLiquidityPda,
     poolAccountA: poolAccountA,
     poolAccountB: poolAccountB,
@@ -136,17 +543,26 @@ export async function createPool(
     mintA: mintA,
     mintB: mintB,
     signer: signer.publicKey,
+    poolCreator: poolCreator,
+    // pool curator and fee manager, the signer itself unless a multisig holds the role
+    poolCurator: roleAuthority ?? signer.publicKey,
+    feeManager: roleAuthority ?? signer.publicKey,
     systemProgram: SystemProgram.programId,
     tokenProgram: TOKEN_PROGRAM_ID,
+    tokenProgramA: tokenProgramA,
+    tokenProgramB: tokenProgramB,
     associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
-  }).signers([signer]).rpc({ commitment: "confirmed" });
+  }).remainingAccounts(signerMetas(approvers)).signers([signer, ...approvers]).rpc({ commitment: "confirmed" });
 
   return {
     poolPda,
+    observationsPda,
     mintLiquidityPda,
     authorityPda,
     poolAccountA,
     poolAccountB,
+    tokenProgramA,
+    tokenProgramB,
   };
 }
 
@@ -164,27 +580,31 @@ export async function addLiquidity(
   mintB: PublicKey,
   mintLiquidityPda: PublicKey,
   amountA: anchor.BN,
-  amountB: anchor.BN
+  amountB: anchor.BN,
+  deadline: anchor.BN | null = null,
+  hookAccounts: AccountMeta[] = []
 ): Promise<AddLiquidityResult> {
-  const depositorAccountA = getAssociatedTokenAddressSync(mintA, signer.publicKey, false);
-  const depositorAccountB = getAssociatedTokenAddressSync(mintB, signer.publicKey, false);
+  const tokenProgramA = await mintTokenProgram(connection, mintA);
+  const tokenProgramB = await mintTokenProgram(connection, mintB);
+  const depositorAccountA = getAssociatedTokenAddressSync(mintA, signer.publicKey, false, tokenProgramA);
+  const depositorAccountB = getAssociatedTokenAddressSync(mintB, signer.publicKey, false, tokenProgramB);
   const depositorAccountLiquidity = getAssociatedTokenAddressSync(mintLiquidityPda, signer.publicKey, false);
 
   try {
-    await createAssociatedTokenAccount(connection, signer, mintA, signer.publicKey);
+    await createAssociatedTokenAccount(connection, signer, mintA, signer.publicKey, undefined, tokenProgramA);
   } catch (err) {
     // Account might already exist
   }
   try {
-    await createAssociatedTokenAccount(connection, signer, mintB, signer.publicKey);
+    await createAssociatedTokenAccount(connection, signer, mintB, signer.publicKey, undefined, tokenProgramB);
   } catch (err) {
     // Account might already exist
   }
 
-  await mintTo(connection, mintAuthority, mintA, depositorAccountA, mintAuthority, amountA.toNumber());
-  await mintTo(connection, mintAuthority, mintB, depositorAccountB, mintAuthority, amountB.toNumber());
+  await mintTo(connection, mintAuthority, mintA, depositorAccountA, mintAuthority, amountA.toNumber(), [], undefined, tokenProgramA);
+  await mintTo(connection, mintAuthority, mintB, depositorAccountB, mintAuthority, amountB.toNumber(), [], undefined, tokenProgramB);
 
-  await program.methods.addLiquidity(amountA, amountB).accounts({
+  await program.methods.addLiquidity(amountA, amountB, deadline, false).accounts({
     pool: poolPda,
     mintA: mintA,
     mintB: mintB,
@@ -192,7 +612,7 @@ export async function addLiquidity(
     depositorAccountA: depositorAccountA,
     depositorAccountB: depositorAccountB,
     payer: signer.publicKey,
-  }).signers([signer]).rpc({commitment: "confirmed"});
+  }).remainingAccounts(hookAccounts).signers([signer]).rpc({commitment: "confirmed"});
 
   const lpAccount = await getAccount(connection, depositorAccountLiquidity);
   return {lpAmount: new anchor.BN(lpAccount.amount.toString())};
@@ -209,8 +629,10 @@ export async function getPoolReserves(
   mintB: PublicKey,
   authorityPda: PublicKey
 ): Promise<GetPoolReservesResult> {
-  const poolAccountA = await getAccount(connection, getAssociatedTokenAddressSync(mintA, authorityPda, true));
-  const poolAccountB = await getAccount(connection, getAssociatedTokenAddressSync(mintB, authorityPda, true));
+  const tokenProgramA = await mintTokenProgram(connection, mintA);
+  const tokenProgramB = await mintTokenProgram(connection, mintB);
+  const poolAccountA = await getAccount(connection, getAssociatedTokenAddressSync(mintA, authorityPda, true, tokenProgramA), undefined, tokenProgramA);
+  const poolAccountB = await getAccount(connection, getAssociatedTokenAddressSync(mintB, authorityPda, true, tokenProgramB), undefined, tokenProgramB);
 
   return {
     reserveA: new anchor.BN(poolAccountA.amount.toString()),
@@ -232,14 +654,19 @@ export async function withdrawLiquidity(
   mintB: PublicKey,
   mintLiquidityPda: PublicKey,
   authorityPda: PublicKey,
-  lpAmountToBurn: anchor.BN
+  lpAmountToBurn: anchor.BN,
+  deadline: anchor.BN | null = null,
+  recipient: PublicKey = signer.publicKey,
+  hookAccounts: AccountMeta[] = []
 ): Promise<WithdrawLiquidityResult> {
   const depositorAccountLiquidity = getAssociatedTokenAddressSync(mintLiquidityPda, signer.publicKey, false);
-  const depositorAccountA = getAssociatedTokenAddressSync(mintA, signer.publicKey, false);
-  const depositorAccountB = getAssociatedTokenAddressSync(mintB, signer.publicKey, false);
+  const tokenProgramA = await mintTokenProgram(connection, mintA);
+  const tokenProgramB = await mintTokenProgram(connection, mintB);
+  const recipientAccountA = getAssociatedTokenAddressSync(mintA, recipient, false, tokenProgramA);
+  const recipientAccountB = getAssociatedTokenAddressSync(mintB, recipient, false, tokenProgramB);
 
-  const poolAccountA = await getAccount(connection, getAssociatedTokenAddressSync(mintA, authorityPda, true));
-  const poolAccountB = await getAccount(connection, getAssociatedTokenAddressSync(mintB, authorityPda, true));
+  const poolAccountA = await getAccount(connection, getAssociatedTokenAddressSync(mintA, authorityPda, true, tokenProgramA), undefined, tokenProgramA);
+  const poolAccountB = await getAccount(connection, getAssociatedTokenAddressSync(mintB, authorityPda, true, tokenProgramB), undefined, tokenProgramB);
 
   const reserveABefore = new anchor.BN(poolAccountA.amount.toString());
   const reserveBBefore = new anchor.BN(poolAccountB.amount.toString());
@@ -249,17 +676,620 @@ export async function withdrawLiquidity(
   const expectedAmountAOut = lpAmountToBurn.mul(reserveABefore).div(totalLp);
   const expectedAmountBOut = lpAmountToBurn.mul(reserveBBefore).div(totalLp);
 
-  await program.methods.withdrawLiquidity(lpAmountToBurn).accounts({
+  await program.methods.withdrawLiquidity(lpAmountToBurn, deadline, false).accounts({
     pool: poolPda,
     mintA: mintA,
     mintB: mintB,
     mintLiquidity: mintLiquidityPda,
     depositor: signer.publicKey,
+    recipient: recipient,
     depositorAccountLiquidity: depositorAccountLiquidity,
-    depositorAccountA: depositorAccountA,
-    depositorAccountB: depositorAccountB,
+    recipientAccountA: recipientAccountA,
+    recipientAccountB: recipientAccountB,
     payer: signer.publicKey,
-  }).signers([signer]).rpc({commitment: "confirmed"});
+  }).remainingAccounts(hookAccounts).signers([signer]).rpc({commitment: "confirmed"});
 
   return {amountAOut: expectedAmountAOut, amountBOut: expectedAmountBOut};
-}
\ No newline at end of file
+}
+
+export async function swap(
+  program: Program<Amm>,
+  connection: Connection,
+  trader: Keypair,
+  mintAuthority: Keypair,
+  ammPda: PublicKey,
+  poolPda: PublicKey,
+  mintA: PublicKey,
+  mintB: PublicKey,
+  isSwapA: boolean,
+  amount: anchor.BN,
+  minOutAmount: anchor.BN,
+  deadline: anchor.BN | null = null,
+  recipient: PublicKey = trader.publicKey,
+  hookAccounts: AccountMeta[] = []
+) {
+  const [inputMint, outputMint] = isSwapA ? [mintA, mintB] : [mintB, mintA];
+  const inputTokenProgram = await mintTokenProgram(connection, inputMint);
+  const outputTokenProgram = await mintTokenProgram(connection, outputMint);
+  const traderInput = getAssociatedTokenAddressSync(inputMint, trader.publicKey, false, inputTokenProgram);
+  try {
+    await createAssociatedTokenAccount(connection, trader, inputMint, trader.publicKey, undefined, inputTokenProgram);
+  } catch (err) {
+    // Account might already exist
+  }
+  await mintTo(connection, mintAuthority, inputMint, traderInput, mintAuthority, amount.toNumber(), [], undefined, inputTokenProgram);
+
+  await program.methods.swap(isSwapA, amount, minOutAmount, deadline, false).accounts({
+    amm: ammPda,
+    pool: poolPda,
+    trader: trader.publicKey,
+    mintA: mintA,
+    mintB: mintB,
+    outputMint: outputMint,
+    recipient: recipient,
+    recipientAccount: getAssociatedTokenAddressSync(outputMint, recipient, false, outputTokenProgram),
+    payer: trader.publicKey,
+    outputTokenProgram: outputTokenProgram,
+  }).remainingAccounts(hookAccounts).signers([trader]).rpc({commitment: "confirmed"});
+}
+
+export async function swapExactOut(
+  program: Program<Amm>,
+  connection: Connection,
+  trader: Keypair,
+  mintAuthority: Keypair,
+  ammPda: PublicKey,
+  poolPda: PublicKey,
+  mintA: PublicKey,
+  mintB: PublicKey,
+  isSwapA: boolean,
+  amountOut: anchor.BN,
+  maxAmountIn: anchor.BN,
+  deadline: anchor.BN | null = null,
+  recipient: PublicKey = trader.publicKey,
+  hookAccounts: AccountMeta[] = []
+) {
+  const [inputMint, outputMint] = isSwapA ? [mintA, mintB] : [mintB, mintA];
+  const inputTokenProgram = await mintTokenProgram(connection, inputMint);
+  const outputTokenProgram = await mintTokenProgram(connection, outputMint);
+  const traderInput = getAssociatedTokenAddressSync(inputMint, trader.publicKey, false, inputTokenProgram);
+  try {
+    await createAssociatedTokenAccount(connection, trader, inputMint, trader.publicKey, undefined, inputTokenProgram);
+  } catch (err) {
+    // Account might already exist
+  }
+  await mintTo(connection, mintAuthority, inputMint, traderInput, mintAuthority, maxAmountIn.toNumber(), [], undefined, inputTokenProgram);
+
+  await program.methods.swapExactOut(isSwapA, amountOut, maxAmountIn, deadline, false).accounts({
+    amm: ammPda,
+    pool: poolPda,
+    trader: trader.publicKey,
+    mintA: mintA,
+    mintB: mintB,
+    outputMint: outputMint,
+    recipient: recipient,
+    recipientAccount: getAssociatedTokenAddressSync(outputMint, recipient, false, outputTokenProgram),
+    payer: trader.publicKey,
+    outputTokenProgram: outputTokenProgram,
+  }).remainingAccounts(hookAccounts).signers([trader]).rpc({commitment: "confirmed"});
+}
+
+export interface RouteHop {
+  poolPda: PublicKey;
+  authorityPda: PublicKey;
+  poolAccountA: PublicKey;
+  poolAccountB: PublicKey;
+  mintA: PublicKey;
+  mintB: PublicKey;
+  tokenProgramA: PublicKey;
+  tokenProgramB: PublicKey;
+  observationsPda: PublicKey;
+}
+
+// Remaining accounts of `swap_route`, in the order the program reads each hop
+export function routeMetas(hops: RouteHop[]) {
+  return hops.flatMap((hop) => [
+    { pubkey: hop.poolPda, isSigner: false, isWritable: true },
+    { pubkey: hop.authorityPda, isSigner: false, isWritable: false },
+    { pubkey: hop.poolAccountA, isSigner: false, isWritable: true },
+    { pubkey: hop.poolAccountB, isSigner: false, isWritable: true },
+    { pubkey: hop.mintA, isSigner: false, isWritable: false },
+    { pubkey: hop.mintB, isSigner: false, isWritable: false },
+    { pubkey: hop.tokenProgramA, isSigner: false, isWritable: false },
+    { pubkey: hop.tokenProgramB, isSigner: false, isWritable: false },
+    { pubkey: hop.observationsPda, isSigner: false, isWritable: true },
+  ]);
+}
+
+export async function swapRoute(
+  program: Program<Amm>,
+  connection: Connection,
+  trader: Keypair,
+  mintAuthority: Keypair,
+  ammPda: PublicKey,
+  hops: RouteHop[],
+  inputMint: PublicKey,
+  outputMint: PublicKey,
+  amount: anchor.BN,
+  minOutAmount: anchor.BN,
+  deadline: anchor.BN | null = null,
+  recipient: PublicKey = trader.publicKey,
+  hookAccounts: AccountMeta[] = []
+) {
+  const inputTokenProgram = await mintTokenProgram(connection, inputMint);
+  const outputTokenProgram = await mintTokenProgram(connection, outputMint);
+  const traderInput = getAssociatedTokenAddressSync(inputMint, trader.publicKey, false, inputTokenProgram);
+  try {
+    await createAssociatedTokenAccount(connection, trader, inputMint, trader.publicKey, undefined, inputTokenProgram);
+  } catch (err) {
+    // Account might already exist
+  }
+  await mintTo(connection, mintAuthority, inputMint, traderInput, mintAuthority, amount.toNumber(), [], undefined, inputTokenProgram);
+
+  await program.methods.swapRoute(amount, minOutAmount, hops.length, deadline).accounts({
+    amm: ammPda,
+    trader: trader.publicKey,
+    inputMint: inputMint,
+    outputMint: outputMint,
+    recipient: recipient,
+    recipientAccount: getAssociatedTokenAddressSync(outputMint, recipient, false, outputTokenProgram),
+    payer: trader.publicKey,
+    inputTokenProgram: inputTokenProgram,
+    outputTokenProgram: outputTokenProgram,
+  }).remainingAccounts([...routeMetas(hops), ...hookAccounts]).signers([trader]).rpc({commitment: "confirmed"});
+}
+
+// Accounts of the quote and preview instructions, which only read the pool
+export function quotePoolAccounts(ammPda: PublicKey, pool: CreatePoolResult, mintA: PublicKey, mintB: PublicKey) {
+  return {
+    amm: ammPda,
+    pool: pool.poolPda,
+    mintA: mintA,
+    mintB: mintB,
+    authority: pool.authorityPda,
+    mintLiquidity: pool.mintLiquidityPda,
+    poolAccountA: pool.poolAccountA,
+    poolAccountB: pool.poolAccountB,
+    tokenProgramA: pool.tokenProgramA,
+    tokenProgramB: pool.tokenProgramB,
+  };
+}
+
+// `flash_borrow` and `flash_repay` instructions, both have to go into the same transaction
+export async function flashLoanInstructions(
+  program: Program<Amm>,
+  borrower: PublicKey,
+  ammPda: PublicKey,
+  poolPda: PublicKey,
+  mintA: PublicKey,
+  mintB: PublicKey,
+  isA: boolean,
+  amount: anchor.BN,
+  repayAmount: anchor.BN = amount,
+  hookAccounts: AccountMeta[] = []
+) {
+  const tokenProgram = await mintTokenProgram(program.provider.connection, isA ? mintA : mintB);
+  const accounts = {
+    amm: ammPda,
+    pool: poolPda,
+    mintA: mintA,
+    mintB: mintB,
+    borrower: borrower,
+    borrowerAccount: getAssociatedTokenAddressSync(isA ? mintA : mintB, borrower, false, tokenProgram),
+    instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+  };
+  const borrowIx = await program.methods.flashBorrow(isA, amount).accounts(accounts).remainingAccounts(hookAccounts).instruction();
+  const repayIx = await program.methods.flashRepay(isA, repayAmount).accounts(accounts).remainingAccounts(hookAccounts).instruction();
+  return {borrowIx, repayIx};
+}
+
+// Queues a `ConfigChange::ProtocolFee`, executed once the timelock delay passed
+export async function setProtocolFee(
+  program: anchor.Program<Amm>,
+  feeManager: Keypair,
+  ammPda: PublicKey,
+  protocolFeeBps: number
+) {
+  await program.methods.setProtocolFee(protocolFeeBps).accounts({
+    amm: ammPda,
+    feeManager: feeManager.publicKey,
+  }).remainingAccounts(signerMetas([feeManager])).signers([feeManager]).rpc({ commitment: "confirmed" });
+}
+
+export async function collectProtocolFees(
+  program: Program<Amm>,
+  payer: Keypair,
+  ammPda: PublicKey,
+  poolPda: PublicKey,
+  mintA: PublicKey,
+  mintB: PublicKey,
+  treasury: PublicKey,
+  hookAccounts: AccountMeta[] = []
+) {
+  await program.methods.collectProtocolFees().accounts({
+    amm: ammPda,
+    pool: poolPda,
+    mintA: mintA,
+    mintB: mintB,
+    treasury: treasury,
+    payer: payer.publicKey,
+  }).remainingAccounts(hookAccounts).signers([payer]).rpc({commitment: "confirmed"});
+}
+
+export const OrderKind = {
+  limit: { limit: {} },
+  takeProfit: { takeProfit: {} },
+  stopLoss: { stopLoss: {} },
+};
+
+export interface OrderParams {
+  kind: typeof OrderKind[keyof typeof OrderKind];
+  isSwapA: boolean;
+  amount: anchor.BN;
+  triggerPriceX64: anchor.BN;
+  twapWindow: number;
+  minAmountOut: anchor.BN;
+  keeperBounty: anchor.BN;
+}
+
+// Q64.64 price of `numerator / denominator`
+export function priceX64(numerator: number, denominator: number) {
+  return new anchor.BN(numerator).shln(64).divn(denominator);
+}
+
+export function orderPda(program: Program<Amm>, poolPda: PublicKey, owner: PublicKey, id: number) {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("ORDER"), poolPda.toBuffer(), owner.toBuffer(), new anchor.BN(id).toArrayLike(Buffer, "le", 8)],
+    program.programId
+  );
+  return pda;
+}
+
+export async function placeOrder(
+  program: Program<Amm>,
+  connection: Connection,
+  owner: Keypair,
+  mintAuthority: Keypair,
+  poolPda: PublicKey,
+  inputMint: PublicKey,
+  id: number,
+  params: OrderParams,
+  hookAccounts: AccountMeta[] = []
+): Promise<PublicKey> {
+  const inputTokenProgram = await mintTokenProgram(connection, inputMint);
+  const ownerInput = getAssociatedTokenAddressSync(inputMint, owner.publicKey, false, inputTokenProgram);
+  try {
+    await createAssociatedTokenAccount(connection, owner, inputMint, owner.publicKey, undefined, inputTokenProgram);
+  } catch (err) {
+    // Account might already exist
+  }
+  await mintTo(connection, mintAuthority, inputMint, ownerInput, mintAuthority, params.amount.add(params.keeperBounty).toNumber(), [], undefined, inputTokenProgram);
+
+  const order = orderPda(program, poolPda, owner.publicKey, id);
+  await program.methods.placeOrder(new anchor.BN(id), params).accounts({
+    pool: poolPda,
+    order: order,
+    inputMint: inputMint,
+    escrow: getAssociatedTokenAddressSync(inputMint, order, true, inputTokenProgram),
+    owner: owner.publicKey,
+    ownerInputAccount: ownerInput,
+    inputTokenProgram: inputTokenProgram,
+  }).remainingAccounts(hookAccounts).signers([owner]).rpc({commitment: "confirmed"});
+  return order;
+}
+
+export async function fillOrder(
+  program: Program<Amm>,
+  keeper: Keypair,
+  ammPda: PublicKey,
+  poolPda: PublicKey,
+  mintA: PublicKey,
+  mintB: PublicKey,
+  order: PublicKey,
+  hookAccounts: AccountMeta[] = []
+) {
+  const {owner, isSwapA} = await program.account.order.fetch(order);
+  const [inputMint, outputMint] = isSwapA ? [mintA, mintB] : [mintB, mintA];
+  const inputTokenProgram = await mintTokenProgram(program.provider.connection, inputMint);
+  const outputTokenProgram = await mintTokenProgram(program.provider.connection, outputMint);
+  await program.methods.fillOrder().accounts({
+    amm: ammPda,
+    pool: poolPda,
+    mintA: mintA,
+    mintB: mintB,
+    order: order,
+    inputMint: inputMint,
+    outputMint: outputMint,
+    escrow: getAssociatedTokenAddressSync(inputMint, order, true, inputTokenProgram),
+    owner: owner,
+    ownerOutputAccount: getAssociatedTokenAddressSync(outputMint, owner, false, outputTokenProgram),
+    keeper: keeper.publicKey,
+    keeperAccount: getAssociatedTokenAddressSync(inputMint, keeper.publicKey, false, inputTokenProgram),
+    inputTokenProgram: inputTokenProgram,
+    outputTokenProgram: outputTokenProgram,
+  }).remainingAccounts(hookAccounts).signers([keeper]).rpc({commitment: "confirmed"});
+}
+
+export async function cancelOrder(
+  program: Program<Amm>,
+  owner: Keypair,
+  poolPda: PublicKey,
+  inputMint: PublicKey,
+  order: PublicKey,
+  hookAccounts: AccountMeta[] = []
+) {
+  const inputTokenProgram = await mintTokenProgram(program.provider.connection, inputMint);
+  await program.methods.cancelOrder().accounts({
+    order: order,
+    pool: poolPda,
+    inputMint: inputMint,
+    escrow: getAssociatedTokenAddressSync(inputMint, order, true, inputTokenProgram),
+    owner: owner.publicKey,
+    ownerInputAccount: getAssociatedTokenAddressSync(inputMint, owner.publicKey, false, inputTokenProgram),
+    inputTokenProgram: inputTokenProgram,
+  }).remainingAccounts(hookAccounts).signers([owner]).rpc({commitment: "confirmed"});
+}
+
+export interface DcaParams {
+  isSwapA: boolean;
+  amountPerPeriod: anchor.BN;
+  interval: anchor.BN;
+  totalPeriods: number;
+  twapWindow: number;
+  maxSlippageBps: number;
+  keeperFeeBps: number;
+}
+
+export function dcaPda(program: Program<Amm>, poolPda: PublicKey, owner: PublicKey, id: number) {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("DCA"), poolPda.toBuffer(), owner.toBuffer(), new anchor.BN(id).toArrayLike(Buffer, "le", 8)],
+    program.programId
+  );
+  return pda;
+}
+
+export async function createDca(
+  program: Program<Amm>,
+  connection: Connection,
+  owner: Keypair,
+  mintAuthority: Keypair,
+  poolPda: PublicKey,
+  mintA: PublicKey,
+  mintB: PublicKey,
+  id: number,
+  params: DcaParams,
+  hookAccounts: AccountMeta[] = []
+): Promise<PublicKey> {
+  const [inputMint, outputMint] = params.isSwapA ? [mintA, mintB] : [mintB, mintA];
+  const inputTokenProgram = await mintTokenProgram(connection, inputMint);
+  const outputTokenProgram = await mintTokenProgram(connection, outputMint);
+  const ownerInput = getAssociatedTokenAddressSync(inputMint, owner.publicKey, false, inputTokenProgram);
+  try {
+    await createAssociatedTokenAccount(connection, owner, inputMint, owner.publicKey, undefined, inputTokenProgram);
+  } catch (err) {
+    // Account might already exist
+  }
+  await mintTo(connection, mintAuthority, inputMint, ownerInput, mintAuthority, params.amountPerPeriod.muln(params.totalPeriods).toNumber(), [], undefined, inputTokenProgram);
+
+  const pool = await program.account.ammPool.fetch(poolPda);
+  const dca = dcaPda(program, poolPda, owner.publicKey, id);
+  await program.methods.createDca(new anchor.BN(id), params).accounts({
+    amm: pool.amm,
+    pool: poolPda,
+    dca: dca,
+    inputMint: inputMint,
+    outputMint: outputMint,
+    inputVault: getAssociatedTokenAddressSync(inputMint, dca, true, inputTokenProgram),
+    outputVault: getAssociatedTokenAddressSync(outputMint, dca, true, outputTokenProgram),
+    owner: owner.publicKey,
+    ownerInputAccount: ownerInput,
+    inputTokenProgram: inputTokenProgram,
+    outputTokenProgram: outputTokenProgram,
+  }).remainingAccounts(hookAccounts).signers([owner]).rpc({commitment: "confirmed"});
+  return dca;
+}
+
+export async function executeDca(
+  program: Program<Amm>,
+  keeper: Keypair,
+  ammPda: PublicKey,
+  poolPda: PublicKey,
+  mintA: PublicKey,
+  mintB: PublicKey,
+  dca: PublicKey,
+  hookAccounts: AccountMeta[] = []
+) {
+  const {isSwapA} = await program.account.dca.fetch(dca);
+  const [inputMint, outputMint] = isSwapA ? [mintA, mintB] : [mintB, mintA];
+  const inputTokenProgram = await mintTokenProgram(program.provider.connection, inputMint);
+  const outputTokenProgram = await mintTokenProgram(program.provider.connection, outputMint);
+  await program.methods.executeDca().accounts({
+    amm: ammPda,
+    pool: poolPda,
+    mintA: mintA,
+    mintB: mintB,
+    dca: dca,
+    inputMint: inputMint,
+    outputMint: outputMint,
+    inputVault: getAssociatedTokenAddressSync(inputMint, dca, true, inputTokenProgram),
+    outputVault: getAssociatedTokenAddressSync(outputMint, dca, true, outputTokenProgram),
+    keeper: keeper.publicKey,
+    keeperAccount: getAssociatedTokenAddressSync(inputMint, keeper.publicKey, false, inputTokenProgram),
+    inputTokenProgram: inputTokenProgram,
+    outputTokenProgram: outputTokenProgram,
+  }).remainingAccounts(hookAccounts).signers([keeper]).rpc({commitment: "confirmed"});
+}
+
+export async function withdrawDca(
+  program: Program<Amm>,
+  owner: Keypair,
+  poolPda: PublicKey,
+  outputMint: PublicKey,
+  dca: PublicKey,
+  hookAccounts: AccountMeta[] = []
+) {
+  const outputTokenProgram = await mintTokenProgram(program.provider.connection, outputMint);
+  await program.methods.withdrawDca().accounts({
+    dca: dca,
+    pool: poolPda,
+    outputMint: outputMint,
+    outputVault: getAssociatedTokenAddressSync(outputMint, dca, true, outputTokenProgram),
+    owner: owner.publicKey,
+    ownerOutputAccount: getAssociatedTokenAddressSync(outputMint, owner.publicKey, false, outputTokenProgram),
+    outputTokenProgram: outputTokenProgram,
+  }).remainingAccounts(hookAccounts).signers([owner]).rpc({commitment: "confirmed"});
+}
+
+export async function closeDca(
+  program: Program<Amm>,
+  owner: Keypair,
+  poolPda: PublicKey,
+  inputMint: PublicKey,
+  outputMint: PublicKey,
+  dca: PublicKey,
+  hookAccounts: AccountMeta[] = []
+) {
+  const inputTokenProgram = await mintTokenProgram(program.provider.connection, inputMint);
+  const outputTokenProgram = await mintTokenProgram(program.provider.connection, outputMint);
+  await program.methods.closeDca().accounts({
+    dca: dca,
+    pool: poolPda,
+    inputMint: inputMint,
+    outputMint: outputMint,
+    inputVault: getAssociatedTokenAddressSync(inputMint, dca, true, inputTokenProgram),
+    outputVault: getAssociatedTokenAddressSync(outputMint, dca, true, outputTokenProgram),
+    owner: owner.publicKey,
+    ownerInputAccount: getAssociatedTokenAddressSync(inputMint, owner.publicKey, false, inputTokenProgram),
+    ownerOutputAccount: getAssociatedTokenAddressSync(outputMint, owner.publicKey, false, outputTokenProgram),
+    inputTokenProgram: inputTokenProgram,
+    outputTokenProgram: outputTokenProgram,
+  }).remainingAccounts(hookAccounts).signers([owner]).rpc({commitment: "confirmed"});
+}
+
+export function longTermOrderPda(program: Program<Amm>, poolPda: PublicKey, owner: PublicKey, id: number) {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("LONG_TERM_ORDER"), poolPda.toBuffer(), owner.toBuffer(), new anchor.BN(id).toArrayLike(Buffer, "le", 8)],
+    program.programId
+  );
+  return pda;
+}
+
+export async function placeLongTermOrder(
+  program: Program<Amm>,
+  connection: Connection,
+  owner: Keypair,
+  mintAuthority: Keypair,
+  ammPda: PublicKey,
+  poolPda: PublicKey,
+  mintA: PublicKey,
+  mintB: PublicKey,
+  id: number,
+  isSellA: boolean,
+  amount: anchor.BN,
+  intervals: number,
+  hookAccounts: AccountMeta[] = []
+): Promise<PublicKey> {
+  const inputMint = isSellA ? mintA : mintB;
+  const inputTokenProgram = await mintTokenProgram(connection, inputMint);
+  const ownerInput = getAssociatedTokenAddressSync(inputMint, owner.publicKey, false, inputTokenProgram);
+  try {
+    await createAssociatedTokenAccount(connection, owner, inputMint, owner.publicKey, undefined, inputTokenProgram);
+  } catch (err) {
+    // Account might already exist
+  }
+  await mintTo(connection, mintAuthority, inputMint, ownerInput, mintAuthority, amount.toNumber(), [], undefined, inputTokenProgram);
+
+  const order = longTermOrderPda(program, poolPda, owner.publicKey, id);
+  await program.methods.placeLongTermOrder(new anchor.BN(id), isSellA, amount, intervals).accounts({
+    amm: ammPda,
+    pool: poolPda,
+    mintA: mintA,
+    mintB: mintB,
+    order: order,
+    owner: owner.publicKey,
+    ownerInputAccount: ownerInput,
+  }).remainingAccounts(hookAccounts).signers([owner]).rpc({commitment: "confirmed"});
+  return order;
+}
+
+export function twammExpiryPda(program: Program<Amm>, poolPda: PublicKey, timestamp: anchor.BN) {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("TWAMM_EXPIRY"), poolPda.toBuffer(), timestamp.toArrayLike(Buffer, "le", 8)],
+    program.programId
+  );
+  return pda;
+}
+
+export async function pruneTwammExpiry(
+  program: Program<Amm>,
+  payer: Keypair,
+  poolPda: PublicKey,
+  timestamp: anchor.BN
+): Promise<PublicKey> {
+  const snapshot = twammExpiryPda(program, poolPda, timestamp);
+  await program.methods.pruneTwammExpiry(timestamp).accounts({
+    pool: poolPda,
+    snapshot: snapshot,
+    payer: payer.publicKey,
+  }).signers([payer]).rpc({commitment: "confirmed"});
+  return snapshot;
+}
+
+async function updateLongTermOrderAccounts(
+  program: Program<Amm>,
+  owner: Keypair,
+  ammPda: PublicKey,
+  poolPda: PublicKey,
+  mintA: PublicKey,
+  mintB: PublicKey,
+  order: PublicKey
+) {
+  const tokenProgramA = await mintTokenProgram(program.provider.connection, mintA);
+  const tokenProgramB = await mintTokenProgram(program.provider.connection, mintB);
+  // the expiry snapshot only exists once `prune_twamm_expiry` moved the expiry out of the pool
+  const {expiry} = await program.account.longTermOrder.fetch(order);
+  const expirySnapshot = twammExpiryPda(program, poolPda, expiry);
+  const snapshot = await program.account.twammExpirySnapshot.fetchNullable(expirySnapshot);
+  return {
+    amm: ammPda,
+    pool: poolPda,
+    mintA: mintA,
+    mintB: mintB,
+    order: order,
+    owner: owner.publicKey,
+    ownerAccountA: getAssociatedTokenAddressSync(mintA, owner.publicKey, false, tokenProgramA),
+    ownerAccountB: getAssociatedTokenAddressSync(mintB, owner.publicKey, false, tokenProgramB),
+    expirySnapshot: snapshot ? expirySnapshot : null,
+    snapshotPayer: snapshot ? snapshot.payer : null,
+  };
+}
+
+export async function withdrawLongTermOrder(
+  program: Program<Amm>,
+  owner: Keypair,
+  ammPda: PublicKey,
+  poolPda: PublicKey,
+  mintA: PublicKey,
+  mintB: PublicKey,
+  order: PublicKey,
+  hookAccounts: AccountMeta[] = []
+) {
+  await program.methods.withdrawLongTermOrder()
+    .accounts(await updateLongTermOrderAccounts(program, owner, ammPda, poolPda, mintA, mintB, order))
+    .remainingAccounts(hookAccounts).signers([owner]).rpc({commitment: "confirmed"});
+}
+
+export async function cancelLongTermOrder(
+  program: Program<Amm>,
+  owner: Keypair,
+  ammPda: PublicKey,
+  poolPda: PublicKey,
+  mintA: PublicKey,
+  mintB: PublicKey,
+  order: PublicKey,
+  hookAccounts: AccountMeta[] = []
+) {
+  await program.methods.cancelLongTermOrder()
+    .accounts(await updateLongTermOrderAccounts(program, owner, ammPda, poolPda, mintA, mintB, order))
+    .remainingAccounts(hookAccounts).signers([owner]).rpc({commitment: "confirmed"});
+}
@@ -0,0 +1,117 @@
+import * as anchor from "@coral-xyz/anchor";
+import {Program} from "@coral-xyz/anchor";
+import {Amm} from "../target/types/amm";
+import {Keypair, Connection} from "@solana/web3.js";
+import {assert} from "chai";
+import {
+    airdrop,
+    createAmm,
+    createMintSafe,
+    createPool,
+    MintPolicy,
+    removeMintEntry,
+    setMintEntry,
+    setMintPolicy
+} from "./helper";
+
+describe("mint_registry", () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    let connection: Connection = anchor.getProvider().connection;
+
+    const program = anchor.workspace.amm as Program<Amm>;
+
+    async function setup(ammIndex: number) {
+        const signer = Keypair.generate();
+        const admin = Keypair.generate();
+        const mintA = Keypair.generate();
+        const mintB = Keypair.generate();
+        const mintC = Keypair.generate();
+
+        await airdrop(connection, signer.publicKey);
+        await airdrop(connection, admin.publicKey);
+        await createMintSafe(connection, signer, signer.publicKey, 9, mintA);
+        await createMintSafe(connection, signer, signer.publicKey, 9, mintB);
+        await createMintSafe(connection, signer, signer.publicKey, 9, mintC);
+
+        const {ammPda} = await createAmm(program, signer, admin.publicKey, 30, ammIndex);
+        return {signer, admin, mintA, mintB, mintC, ammPda};
+    }
+
+    function assertMintNotAllowed(err: any) {
+        assert.isTrue(err.toString().includes("MintNotAllowed"), `Expected MintNotAllowed error, got: ${err.toString()}`);
+    }
+
+    it("Allowlist AMM only accepts pools where both mints are allowed", async () => {
+        const ctx = await setup(1100);
+        await setMintPolicy(program, ctx.admin, ctx.ammPda, MintPolicy.allowlist);
+        await setMintEntry(program, ctx.admin, ctx.ammPda, ctx.mintA.publicKey, true);
+        await setMintEntry(program, ctx.admin, ctx.ammPda, ctx.mintB.publicKey, true);
+        await setMintEntry(program, ctx.admin, ctx.ammPda, ctx.mintC.publicKey, false);
+
+        await createPool(program, ctx.signer, ctx.ammPda, ctx.mintA.publicKey, ctx.mintB.publicKey);
+
+        // mintC is explicitly denied
+        try {
+            await createPool(program, ctx.signer, ctx.ammPda, ctx.mintA.publicKey, ctx.mintC.publicKey);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertMintNotAllowed(err);
+        }
+    });
+
+    it("Allowlist AMM rejects unknown mints", async () => {
+        const ctx = await setup(1101);
+        await setMintPolicy(program, ctx.admin, ctx.ammPda, MintPolicy.allowlist);
+        await setMintEntry(program, ctx.admin, ctx.ammPda, ctx.mintA.publicKey, true);
+
+        try {
+            await createPool(program, ctx.signer, ctx.ammPda, ctx.mintA.publicKey, ctx.mintB.publicKey);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertMintNotAllowed(err);
+        }
+    });
+
+    it("Denylist AMM accepts unknown mints and rejects denied ones", async () => {
+        const ctx = await setup(1102);
+        await setMintPolicy(program, ctx.admin, ctx.ammPda, MintPolicy.denylist);
+        const mintEntryC = await setMintEntry(program, ctx.admin, ctx.ammPda, ctx.mintC.publicKey, false);
+
+        await createPool(program, ctx.signer, ctx.ammPda, ctx.mintA.publicKey, ctx.mintB.publicKey);
+
+        try {
+            await createPool(program, ctx.signer, ctx.ammPda, ctx.mintC.publicKey, ctx.mintA.publicKey);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertMintNotAllowed(err);
+        }
+
+        await removeMintEntry(program, ctx.admin, ctx.ammPda, mintEntryC);
+        await createPool(program, ctx.signer, ctx.ammPda, ctx.mintC.publicKey, ctx.mintA.publicKey);
+    });
+
+    it("Open AMM ignores the registry", async () => {
+        const ctx = await setup(1103);
+        await setMintEntry(program, ctx.admin, ctx.ammPda, ctx.mintA.publicKey, false);
+
+        await createPool(program, ctx.signer, ctx.ammPda, ctx.mintA.publicKey, ctx.mintB.publicKey);
+    });
+
+    it("Only the admin can manage the registry", async () => {
+        const ctx = await setup(1104);
+
+        try {
+            await setMintPolicy(program, ctx.signer, ctx.ammPda, MintPolicy.allowlist);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("Unauthorized"), `Expected Unauthorized error, got: ${err.toString()}`);
+        }
+
+        try {
+            await setMintEntry(program, ctx.signer, ctx.ammPda, ctx.mintA.publicKey, true);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("Unauthorized"), `Expected Unauthorized error, got: ${err.toString()}`);
+        }
+    });
+});
@@ -0,0 +1,210 @@
+import * as anchor from "@coral-xyz/anchor";
+import {Program} from "@coral-xyz/anchor";
+import {Amm} from "../target/types/amm";
+import {Keypair, Connection, PublicKey} from "@solana/web3.js";
+import {assert} from "chai";
+import {
+    airdrop,
+    ammAddress,
+    applyConfigChange,
+    cancelConfigChange,
+    ConfigChange,
+    ConfigChangeKind,
+    createAmm,
+    createMintSafe,
+    createMultisig,
+    createPool,
+    executeConfigChange,
+    Role,
+    setMultisigConfig,
+    setPermissionedPoolCreation,
+    setRole,
+    signerMetas
+} from "./helper";
+
+describe("multisig", () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    let connection: Connection = anchor.getProvider().connection;
+
+    const program = anchor.workspace.amm as Program<Amm>;
+    const DELAY = 2;
+
+    // multisig whose signer set changes wait for the timelock of the AMM at `ammIndex`
+    async function setupMultisig(threshold: number, ammIndex: number) {
+        const payer = Keypair.generate();
+        const members = [Keypair.generate(), Keypair.generate(), Keypair.generate()];
+
+        await airdrop(connection, payer.publicKey);
+        const multisigPda = await createMultisig(program, payer, ammAddress(program, ammIndex), members.map((member) => member.publicKey), threshold);
+        return {payer, members, multisigPda};
+    }
+
+    async function pauseWith(ammPda: PublicKey, pauser: PublicKey, approvers: Keypair[]) {
+        await program.methods.pause().accounts({
+            amm: ammPda,
+            pauser: pauser,
+        }).remainingAccounts(signerMetas(approvers)).signers(approvers).rpc({commitment: "confirmed"});
+    }
+
+    function assertError(err: any, name: string) {
+        assert.isTrue(err.toString().includes(name), `Expected ${name} error, got: ${err.toString()}`);
+    }
+
+    it("Multisig admin needs threshold approvals", async () => {
+        const {payer, members, multisigPda} = await setupMultisig(2, 1300);
+        const {ammPda} = await createAmm(program, payer, multisigPda, 30, 1300);
+
+        try {
+            await pauseWith(ammPda, multisigPda, [members[0]]);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertError(err, "NotEnoughSigners");
+        }
+
+        try {
+            await pauseWith(ammPda, multisigPda, [members[0], Keypair.generate()]);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertError(err, "NotEnoughSigners");
+        }
+
+        await pauseWith(ammPda, multisigPda, [members[0], members[2]]);
+        assert.isTrue((await program.account.amm.fetch(ammPda)).paused);
+    });
+
+    it("Rejects invalid signer sets", async () => {
+        const payer = Keypair.generate();
+        const member = Keypair.generate().publicKey;
+        await airdrop(connection, payer.publicKey);
+
+        for (const [signers, threshold] of [[[member], 0], [[member], 2], [[member, member], 1], [[], 0]] as [PublicKey[], number][]) {
+            try {
+                await createMultisig(program, payer, PublicKey.default, signers, threshold);
+                assert.fail("Expected transaction to fail");
+            } catch (err) {
+                assertError(err, "InvalidMultisig");
+            }
+        }
+    });
+
+    it("Signer set changes are approved by the current signers and wait for the timelock", async () => {
+        const {payer, members, multisigPda} = await setupMultisig(2, 1302);
+        const {ammPda} = await createAmm(program, payer, multisigPda, 30, 1302, DELAY);
+        const newMember = Keypair.generate();
+        const newSigners = [members[0].publicKey, newMember.publicKey];
+
+        try {
+            await setMultisigConfig(program, ammPda, multisigPda, [members[0]], newSigners, 1);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertError(err, "NotEnoughSigners");
+        }
+
+        await setMultisigConfig(program, ammPda, multisigPda, [members[0], members[1]], newSigners, 1);
+        let multisigData = await program.account.multisig.fetch(multisigPda);
+        assert.strictEqual(multisigData.threshold, 2);
+        assert.strictEqual(multisigData.pendingThreshold, 1);
+
+        try {
+            await executeConfigChange(program, ammPda, ConfigChangeKind.multisigConfig, null, multisigPda);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertError(err, "TimelockNotElapsed");
+        }
+
+        await new Promise((resolve) => setTimeout(resolve, (DELAY + 1) * 1000));
+        await executeConfigChange(program, ammPda, ConfigChangeKind.multisigConfig, null, multisigPda);
+
+        multisigData = await program.account.multisig.fetch(multisigPda);
+        assert.strictEqual(multisigData.threshold, 1);
+        assert.deepEqual(multisigData.signers.map((signer) => signer.toBase58()), newSigners.map((signer) => signer.toBase58()));
+        assert.lengthOf(multisigData.pendingSigners, 0);
+    });
+
+    it("Signer set changes only go through the AMM the multisig belongs to", async () => {
+        const {payer, members, multisigPda} = await setupMultisig(2, 1303);
+        await createAmm(program, payer, multisigPda, 30, 1303, DELAY);
+        // another AMM without a delay that names the same multisig as its admin
+        const {ammPda: otherAmmPda} = await createAmm(program, payer, multisigPda, 30, 1304);
+
+        try {
+            await setMultisigConfig(program, otherAmmPda, multisigPda, [members[0], members[1]], [members[0].publicKey], 1);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertError(err, "MultisigMismatch");
+        }
+
+        // the current signers can drop a queued change
+        const ammPda = ammAddress(program, 1303);
+        await setMultisigConfig(program, ammPda, multisigPda, [members[0], members[1]], [members[0].publicKey], 1);
+        try {
+            await cancelConfigChange(program, members[0], ammPda, ConfigChangeKind.multisigConfig);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertError(err, "Unauthorized");
+        }
+        await program.methods.cancelConfigChange(ConfigChangeKind.multisigConfig).accounts({
+            amm: ammPda,
+            pool: null,
+            authority: multisigPda,
+        }).remainingAccounts(signerMetas([members[1], members[2]])).signers([members[1], members[2]]).rpc({commitment: "confirmed"});
+        assert.lengthOf((await program.account.amm.fetch(ammPda)).pendingConfigChanges, 0);
+    });
+
+    it("Multisig pool curator and fee manager approve pools like any other role", async () => {
+        const {payer, members, multisigPda} = await setupMultisig(2, 1305);
+        const admin = Keypair.generate();
+        const mintA = Keypair.generate();
+        const mintB = Keypair.generate();
+        await airdrop(connection, admin.publicKey);
+        await createMintSafe(connection, payer, payer.publicKey, 9, mintA);
+        await createMintSafe(connection, payer, payer.publicKey, 9, mintB);
+
+        const {ammPda} = await createAmm(program, payer, admin.publicKey, 30, 1305);
+        await setPermissionedPoolCreation(program, admin, ammPda, true);
+        await setRole(program, admin, ammPda, Role.poolCurator, multisigPda);
+        await setRole(program, admin, ammPda, Role.feeManager, multisigPda);
+
+        // the admin wallet no longer holds the roles
+        try {
+            await createPool(program, admin, ammPda, mintA.publicKey, mintB.publicKey);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertError(err, "PoolCreationNotAllowed");
+        }
+
+        try {
+            await createPool(program, payer, ammPda, mintA.publicKey, mintB.publicKey, 0, 100, null, multisigPda, [members[0]]);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertError(err, "NotEnoughSigners");
+        }
+
+        const {poolPda} = await createPool(program, payer, ammPda, mintA.publicKey, mintB.publicKey, 0, 100, null, multisigPda, [members[0], members[1]]);
+        assert.strictEqual((await program.account.ammPool.fetch(poolPda)).feeBps, 100);
+    });
+
+    it("Wallet admin can hand over to a multisig", async () => {
+        const {payer, members, multisigPda} = await setupMultisig(2, 1301);
+        const admin = Keypair.generate();
+        await airdrop(connection, admin.publicKey);
+        const {ammPda} = await createAmm(program, payer, admin.publicKey, 30, 1301);
+
+        await applyConfigChange(program, admin, ammPda, ConfigChange.admin(multisigPda));
+        await program.methods.acceptAdmin().accounts({
+            amm: ammPda,
+            newAdmin: multisigPda,
+        }).remainingAccounts(signerMetas([members[1], members[2]])).signers([members[1], members[2]]).rpc({commitment: "confirmed"});
+
+        const ammData = await program.account.amm.fetch(ammPda);
+        assert.strictEqual(ammData.admin.toBase58(), multisigPda.toBase58());
+        assert.isNull(ammData.pendingAdmin);
+
+        try {
+            await setRole(program, admin, ammPda, Role.pauser, admin.publicKey);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertError(err, "Unauthorized");
+        }
+    });
+});
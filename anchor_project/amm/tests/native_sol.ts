@@ -0,0 +1,201 @@
+import * as anchor from "@coral-xyz/anchor";
+import {Program} from "@coral-xyz/anchor";
+import {Amm} from "../target/types/amm";
+import {Keypair, Connection, LAMPORTS_PER_SOL, PublicKey} from "@solana/web3.js";
+import {assert} from "chai";
+import {
+    createAssociatedTokenAccount,
+    createWrappedNativeAccount,
+    getAccount,
+    getAssociatedTokenAddressSync,
+    mintTo,
+    NATIVE_MINT,
+    TOKEN_PROGRAM_ID
+} from "@solana/spl-token";
+import {addLiquidity, airdrop, createAmm, createMintSafe, createPool} from "./helper";
+
+describe("native_sol", () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    let connection: Connection = anchor.getProvider().connection;
+
+    const program = anchor.workspace.amm as Program<Amm>;
+    const DECIMALS = new anchor.BN(10).pow(new anchor.BN(9));
+
+    // pool of SOL as token A against a new token B, nobody holds WSOL
+    async function setupPool(ammIndex: number) {
+        const provider = Keypair.generate();
+        const trader = Keypair.generate();
+        const mintB = Keypair.generate();
+
+        await airdrop(connection, provider.publicKey, 5 * LAMPORTS_PER_SOL);
+        await airdrop(connection, trader.publicKey, 2 * LAMPORTS_PER_SOL);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintB);
+
+        const {ammPda} = await createAmm(program, provider, provider.publicKey, 30, ammIndex);
+        const pool = await createPool(program, provider, ammPda, NATIVE_MINT, mintB.publicKey);
+
+        const providerAccountB = await createAssociatedTokenAccount(connection, provider, mintB.publicKey, provider.publicKey);
+        await mintTo(connection, provider, mintB.publicKey, providerAccountB, provider, new anchor.BN(3000).mul(DECIMALS).toNumber());
+        await program.methods.addLiquidity(new anchor.BN(LAMPORTS_PER_SOL), new anchor.BN(3000).mul(DECIMALS), null, true).accounts({
+            pool: pool.poolPda,
+            mintA: NATIVE_MINT,
+            mintB: mintB.publicKey,
+            depositor: provider.publicKey,
+            depositorAccountA: wsolAccount(provider.publicKey),
+            depositorAccountB: providerAccountB,
+            payer: provider.publicKey,
+        }).signers([provider]).rpc({commitment: "confirmed"});
+
+        return {provider, trader, mintB, ammPda, ...pool};
+    }
+
+    function wsolAccount(owner: PublicKey) {
+        return getAssociatedTokenAddressSync(NATIVE_MINT, owner, false);
+    }
+
+    // created and closed within each instruction that unwraps
+    function temporaryWsolAccount(owner: PublicKey) {
+        const [pda] = PublicKey.findProgramAddressSync([Buffer.from("WSOL"), owner.toBuffer()], program.programId);
+        return pda;
+    }
+
+    async function wsolBalance(owner: PublicKey) {
+        return (await getAccount(connection, wsolAccount(owner), "confirmed")).amount;
+    }
+
+    function assertError(err: any, name: string) {
+        assert.isTrue(err.toString().includes(name), `Expected ${name} error, got: ${err.toString()}`);
+    }
+
+    async function lamports(owner: PublicKey) {
+        return connection.getBalance(owner, "confirmed");
+    }
+
+    async function nativeSwap(ctx: Awaited<ReturnType<typeof setupPool>>, isSwapA: boolean, amount: anchor.BN, recipient = ctx.trader.publicKey) {
+        const outputMint = isSwapA ? ctx.mintB.publicKey : NATIVE_MINT;
+        await program.methods.swap(isSwapA, amount, new anchor.BN(0), null, true).accounts({
+            amm: ctx.ammPda,
+            pool: ctx.poolPda,
+            trader: ctx.trader.publicKey,
+            mintA: NATIVE_MINT,
+            mintB: ctx.mintB.publicKey,
+            outputMint: outputMint,
+            recipient: recipient,
+            recipientAccount: getAssociatedTokenAddressSync(outputMint, recipient, false),
+            payer: ctx.trader.publicKey,
+            outputTokenProgram: TOKEN_PROGRAM_ID,
+        }).signers([ctx.trader]).rpc({commitment: "confirmed"});
+    }
+
+    async function nativeWithdraw(ctx: Awaited<ReturnType<typeof setupPool>>, lpAmount: anchor.BN, recipient = ctx.provider.publicKey) {
+        await program.methods.withdrawLiquidity(lpAmount, null, true).accounts({
+            pool: ctx.poolPda,
+            mintA: NATIVE_MINT,
+            mintB: ctx.mintB.publicKey,
+            mintLiquidity: ctx.mintLiquidityPda,
+            depositor: ctx.provider.publicKey,
+            recipient: recipient,
+            depositorAccountLiquidity: getAssociatedTokenAddressSync(ctx.mintLiquidityPda, ctx.provider.publicKey, false),
+            recipientAccountA: getAssociatedTokenAddressSync(NATIVE_MINT, recipient, false),
+            recipientAccountB: getAssociatedTokenAddressSync(ctx.mintB.publicKey, recipient, false),
+            payer: ctx.provider.publicKey,
+        }).signers([ctx.provider]).rpc({commitment: "confirmed"});
+    }
+
+    it("Deposits lamports wrapped for exactly the deposit", async () => {
+        const ctx = await setupPool(2600);
+
+        const poolWsol = await getAccount(connection, ctx.poolAccountA);
+        assert.equal(poolWsol.amount.toString(), LAMPORTS_PER_SOL.toString());
+        assert.equal(await wsolBalance(ctx.provider.publicKey), BigInt(0));
+    });
+
+    it("Swaps lamports in and out", async () => {
+        const ctx = await setupPool(2601);
+        const amount = new anchor.BN(LAMPORTS_PER_SOL / 10);
+
+        const before = await lamports(ctx.trader.publicKey);
+        await nativeSwap(ctx, true, amount);
+        assert.isAtMost(await lamports(ctx.trader.publicKey), before - amount.toNumber());
+        assert.equal(await wsolBalance(ctx.trader.publicKey), BigInt(0));
+
+        const traderAccountB = getAssociatedTokenAddressSync(ctx.mintB.publicKey, ctx.trader.publicKey, false);
+        const bought = new anchor.BN((await getAccount(connection, traderAccountB)).amount.toString());
+        assert.isTrue(bought.gtn(0));
+
+        const beforeSell = await lamports(ctx.trader.publicKey);
+        await nativeSwap(ctx, false, bought);
+        assert.isAbove(await lamports(ctx.trader.publicKey), beforeSell);
+        assert.equal(await wsolBalance(ctx.trader.publicKey), BigInt(0));
+        assert.isNull(await connection.getAccountInfo(temporaryWsolAccount(ctx.trader.publicKey)));
+    });
+
+    it("Leaves WSOL the trader already held wrapped", async () => {
+        const ctx = await setupPool(2604);
+        const held = BigInt(LAMPORTS_PER_SOL / 2);
+        await createWrappedNativeAccount(connection, ctx.trader, ctx.trader.publicKey, Number(held));
+
+        await nativeSwap(ctx, true, new anchor.BN(LAMPORTS_PER_SOL / 10));
+        assert.equal(await wsolBalance(ctx.trader.publicKey), held);
+
+        const traderAccountB = getAssociatedTokenAddressSync(ctx.mintB.publicKey, ctx.trader.publicKey, false);
+        const bought = new anchor.BN((await getAccount(connection, traderAccountB)).amount.toString());
+        const before = await lamports(ctx.trader.publicKey);
+        await nativeSwap(ctx, false, bought);
+
+        // only the output was unwrapped
+        assert.isAbove(await lamports(ctx.trader.publicKey), before);
+        assert.equal(await wsolBalance(ctx.trader.publicKey), held);
+        assert.isNull(await connection.getAccountInfo(temporaryWsolAccount(ctx.trader.publicKey)));
+    });
+
+    it("Withdraws the native side as lamports to the signer only", async () => {
+        const ctx = await setupPool(2602);
+        const lpAccount = getAssociatedTokenAddressSync(ctx.mintLiquidityPda, ctx.provider.publicKey, false);
+        const lpAmount = new anchor.BN((await getAccount(connection, lpAccount)).amount.toString()).divn(2);
+
+        try {
+            await nativeWithdraw(ctx, lpAmount, ctx.trader.publicKey);
+            assert.fail("Expected withdraw to fail");
+        } catch (err) {
+            assertError(err, "InvalidNativeSolRecipient");
+        }
+
+        const before = await lamports(ctx.provider.publicKey);
+        await nativeWithdraw(ctx, lpAmount);
+        assert.isAbove(await lamports(ctx.provider.publicKey), before + LAMPORTS_PER_SOL / 4);
+        assert.equal(await wsolBalance(ctx.provider.publicKey), BigInt(0));
+        assert.isNull(await connection.getAccountInfo(temporaryWsolAccount(ctx.provider.publicKey)));
+    });
+
+    it("Rejects native_sol on a pool without the native mint", async () => {
+        const provider = Keypair.generate();
+        const mintA = Keypair.generate();
+        const mintB = Keypair.generate();
+        await airdrop(connection, provider.publicKey);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintA);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintB);
+
+        const {ammPda} = await createAmm(program, provider, provider.publicKey, 30, 2603);
+        const pool = await createPool(program, provider, ammPda, mintA.publicKey, mintB.publicKey);
+        await addLiquidity(program, connection, provider, provider, pool.poolPda, mintA.publicKey, mintB.publicKey, pool.mintLiquidityPda, new anchor.BN(1000).mul(DECIMALS), new anchor.BN(3000).mul(DECIMALS));
+
+        try {
+            await program.methods.swap(true, DECIMALS, new anchor.BN(0), null, true).accounts({
+                amm: ammPda,
+                pool: pool.poolPda,
+                trader: provider.publicKey,
+                mintA: mintA.publicKey,
+                mintB: mintB.publicKey,
+                outputMint: mintB.publicKey,
+                recipient: provider.publicKey,
+                recipientAccount: getAssociatedTokenAddressSync(mintB.publicKey, provider.publicKey, false),
+                payer: provider.publicKey,
+                outputTokenProgram: TOKEN_PROGRAM_ID,
+            }).signers([provider]).rpc({commitment: "confirmed"});
+            assert.fail("Expected swap to fail");
+        } catch (err) {
+            assertError(err, "NotNativeMintPool");
+        }
+    });
+});
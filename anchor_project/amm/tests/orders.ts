@@ -0,0 +1,248 @@
+import * as anchor from "@coral-xyz/anchor";
+import {Program} from "@coral-xyz/anchor";
+import {Amm} from "../target/types/amm";
+import {Keypair, Connection, PublicKey} from "@solana/web3.js";
+import {assert} from "chai";
+import {
+    ASSOCIATED_TOKEN_PROGRAM_ID,
+    createAssociatedTokenAccount,
+    getAccount,
+    getAssociatedTokenAddressSync,
+    TOKEN_PROGRAM_ID
+} from "@solana/spl-token";
+import {
+    addLiquidity,
+    airdrop,
+    cancelOrder,
+    createAmm,
+    createMintSafe,
+    createPool,
+    fillOrder,
+    OrderKind,
+    orderPda,
+    placeOrder,
+    priceX64,
+    swap
+} from "./helper";
+
+describe("orders", () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    let connection: Connection = anchor.getProvider().connection;
+
+    const program = anchor.workspace.amm as Program<Amm>;
+    const DECIMALS = new anchor.BN(10).pow(new anchor.BN(9));
+
+    // pool at 1000 A / 3000 B, A trades at 3 B
+    async function setupPool(ammIndex: number) {
+        const provider = Keypair.generate();
+        const owner = Keypair.generate();
+        const trader = Keypair.generate();
+        const keeper = Keypair.generate();
+        const mintA = Keypair.generate();
+        const mintB = Keypair.generate();
+
+        await airdrop(connection, provider.publicKey);
+        await airdrop(connection, owner.publicKey);
+        await airdrop(connection, trader.publicKey);
+        await airdrop(connection, keeper.publicKey);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintA);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintB);
+
+        const {ammPda} = await createAmm(program, provider, provider.publicKey, 30, ammIndex);
+        const pool = await createPool(program, provider, ammPda, mintA.publicKey, mintB.publicKey);
+        await addLiquidity(program, connection, provider, provider, pool.poolPda, mintA.publicKey, mintB.publicKey, pool.mintLiquidityPda, new anchor.BN(1000).mul(DECIMALS), new anchor.BN(3000).mul(DECIMALS));
+        // the TWAP of the orders needs an observation older than their window
+        await sleep(2000);
+
+        return {provider, owner, trader, keeper, mintA, mintB, ammPda, ...pool};
+    }
+
+    function sleep(ms: number) {
+        return new Promise((resolve) => setTimeout(resolve, ms));
+    }
+
+    // Waits until the price left by the last trade has held twice as long as the price before it since the deposit,
+    // the only observation, so the TWAP has moved two thirds of the way to it
+    async function waitForTwap(ctx: Awaited<ReturnType<typeof setupPool>>) {
+        const {observations} = await program.account.observations.fetch(ctx.observationsPda);
+        const pool = await program.account.ammPool.fetch(ctx.poolPda);
+        const before = pool.lastUpdateTimestamp.sub(observations[0].timestamp).toNumber();
+        await sleep((2 * before + 2) * 1000);
+    }
+
+    function assertError(err: any, name: string) {
+        assert.isTrue(err.toString().includes(name), `Expected ${name} error, got: ${err.toString()}`);
+    }
+
+    async function balance(mint: PublicKey, owner: PublicKey) {
+        return new anchor.BN((await getAccount(connection, getAssociatedTokenAddressSync(mint, owner, false))).amount.toString());
+    }
+
+    async function fill(ctx: Awaited<ReturnType<typeof setupPool>>, order: PublicKey) {
+        await fillOrder(program, ctx.keeper, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, order);
+    }
+
+    it("Limit order fills once the price reaches it and pays the keeper", async () => {
+        const ctx = await setupPool(2300);
+        const amount = new anchor.BN(10).mul(DECIMALS);
+        const bounty = DECIMALS.divn(100);
+
+        // sell 10 A for at least 3.3 B each
+        const order = await placeOrder(program, connection, ctx.owner, ctx.provider, ctx.poolPda, ctx.mintA.publicKey, 0, {
+            kind: OrderKind.limit,
+            isSwapA: true,
+            amount,
+            triggerPriceX64: priceX64(33, 10),
+            twapWindow: 1,
+            minAmountOut: new anchor.BN(0),
+            keeperBounty: bounty,
+        });
+        const stored = await program.account.order.fetch(order);
+        assert.isTrue(stored.minAmountOut.eq(amount.muln(33).divn(10)));
+
+        try {
+            await fill(ctx, order);
+            assert.fail("Expected fill to fail");
+        } catch (err) {
+            assertError(err, "OrderNotTriggered");
+        }
+
+        // buying A pushes its price above 3.3 B
+        await swap(program, connection, ctx.trader, ctx.provider, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, false, new anchor.BN(300).mul(DECIMALS), new anchor.BN(0));
+        // the spot price is past the trigger but the TWAP is not yet
+        try {
+            await fill(ctx, order);
+            assert.fail("Expected fill to fail");
+        } catch (err) {
+            assertError(err, "OrderNotTriggered");
+        }
+        await waitForTwap(ctx);
+        await fill(ctx, order);
+
+        assert.isTrue((await balance(ctx.mintB.publicKey, ctx.owner.publicKey)).gte(stored.minAmountOut));
+        assert.isTrue((await balance(ctx.mintA.publicKey, ctx.keeper.publicKey)).eq(bounty));
+        assert.isNull(await connection.getAccountInfo(order));
+        assert.isNull(await connection.getAccountInfo(getAssociatedTokenAddressSync(ctx.mintA.publicKey, order, true)));
+    });
+
+    it("Stop loss fills once the price falls to the trigger", async () => {
+        const ctx = await setupPool(2301);
+        const amount = new anchor.BN(10).mul(DECIMALS);
+        const minAmountOut = new anchor.BN(15).mul(DECIMALS);
+
+        // sell 10 A once A drops to 2.7 B, for no less than 15 B
+        const order = await placeOrder(program, connection, ctx.owner, ctx.provider, ctx.poolPda, ctx.mintA.publicKey, 0, {
+            kind: OrderKind.stopLoss,
+            isSwapA: true,
+            amount,
+            triggerPriceX64: priceX64(27, 10),
+            twapWindow: 1,
+            minAmountOut,
+            keeperBounty: new anchor.BN(0),
+        });
+
+        try {
+            await fill(ctx, order);
+            assert.fail("Expected fill to fail");
+        } catch (err) {
+            assertError(err, "OrderNotTriggered");
+        }
+
+        await swap(program, connection, ctx.trader, ctx.provider, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, true, new anchor.BN(200).mul(DECIMALS), new anchor.BN(0));
+        await waitForTwap(ctx);
+        await fill(ctx, order);
+
+        assert.isTrue((await balance(ctx.mintB.publicKey, ctx.owner.publicKey)).gte(minAmountOut));
+        assert.isNull(await connection.getAccountInfo(order));
+    });
+
+    it("Take profit does not fill below its minimum output", async () => {
+        const ctx = await setupPool(2302);
+
+        // sell 100 B for A once B is worth 0.3 A, but ask for more than the pool can give
+        const order = await placeOrder(program, connection, ctx.owner, ctx.provider, ctx.poolPda, ctx.mintB.publicKey, 0, {
+            kind: OrderKind.takeProfit,
+            isSwapA: false,
+            amount: new anchor.BN(100).mul(DECIMALS),
+            triggerPriceX64: priceX64(3, 10),
+            twapWindow: 1,
+            minAmountOut: new anchor.BN(40).mul(DECIMALS),
+            keeperBounty: new anchor.BN(0),
+        });
+
+        try {
+            await fill(ctx, order);
+            assert.fail("Expected fill to fail");
+        } catch (err) {
+            assertError(err, "OutputAmountTooLow");
+        }
+    });
+
+    it("Owner cancels an order and gets the escrow and bounty back", async () => {
+        const ctx = await setupPool(2303);
+        const amount = new anchor.BN(10).mul(DECIMALS);
+        const bounty = DECIMALS.divn(100);
+
+        const order = await placeOrder(program, connection, ctx.owner, ctx.provider, ctx.poolPda, ctx.mintA.publicKey, 7, {
+            kind: OrderKind.limit,
+            isSwapA: true,
+            amount,
+            triggerPriceX64: priceX64(4, 1),
+            twapWindow: 1,
+            minAmountOut: new anchor.BN(0),
+            keeperBounty: bounty,
+        });
+        assert.isTrue((await balance(ctx.mintA.publicKey, ctx.owner.publicKey)).isZero());
+
+        try {
+            await cancelOrder(program, ctx.keeper, ctx.poolPda, ctx.mintA.publicKey, order);
+            assert.fail("Expected cancel to fail");
+        } catch (err) {
+            assert.isNotNull(err);
+        }
+
+        await cancelOrder(program, ctx.owner, ctx.poolPda, ctx.mintA.publicKey, order);
+        assert.isTrue((await balance(ctx.mintA.publicKey, ctx.owner.publicKey)).eq(amount.add(bounty)));
+        assert.isNull(await connection.getAccountInfo(order));
+    });
+
+    it("Places the order when its escrow account was created ahead of it", async () => {
+        const ctx = await setupPool(2304);
+        const order = orderPda(program, ctx.poolPda, ctx.owner.publicKey, 0);
+        await createAssociatedTokenAccount(connection, ctx.trader, ctx.mintA.publicKey, order, undefined, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID, true);
+
+        await placeOrder(program, connection, ctx.owner, ctx.provider, ctx.poolPda, ctx.mintA.publicKey, 0, {
+            kind: OrderKind.limit,
+            isSwapA: true,
+            amount: new anchor.BN(10).mul(DECIMALS),
+            triggerPriceX64: priceX64(4, 1),
+            twapWindow: 1,
+            minAmountOut: new anchor.BN(0),
+            keeperBounty: new anchor.BN(0),
+        });
+        const escrow = await getAccount(connection, getAssociatedTokenAddressSync(ctx.mintA.publicKey, order, true));
+        assert.strictEqual(escrow.amount.toString(), new anchor.BN(10).mul(DECIMALS).toString());
+    });
+
+    it("Rejects an order without a TWAP window or with one longer than the observation history", async () => {
+        const ctx = await setupPool(2305);
+
+        // 63 periods of 60 seconds between the oldest and the latest of 64 observations
+        for (const [id, twapWindow] of [0, 63 * 60 + 1].entries()) {
+            try {
+                await placeOrder(program, connection, ctx.owner, ctx.provider, ctx.poolPda, ctx.mintA.publicKey, id, {
+                    kind: OrderKind.limit,
+                    isSwapA: true,
+                    amount: new anchor.BN(10).mul(DECIMALS),
+                    triggerPriceX64: priceX64(4, 1),
+                    twapWindow,
+                    minAmountOut: new anchor.BN(0),
+                    keeperBounty: new anchor.BN(0),
+                });
+                assert.fail("Expected transaction to fail");
+            } catch (err) {
+                assertError(err, "InvalidOrderPrice");
+            }
+        }
+    });
+});
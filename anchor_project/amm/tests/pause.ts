@@ -0,0 +1,122 @@
+import * as anchor from "@coral-xyz/anchor";
+import {Program} from "@coral-xyz/anchor";
+import {Amm} from "../target/types/amm";
+import {Keypair, Connection} from "@solana/web3.js";
+import {assert} from "chai";
+import {
+    addLiquidity,
+    airdrop,
+    applyConfigChange,
+    ConfigChange,
+    createAmm,
+    createMintSafe,
+    createPool,
+    pause,
+    pausePool,
+    swap,
+    withdrawLiquidity
+} from "./helper";
+
+describe("pause", () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    let connection: Connection = anchor.getProvider().connection;
+
+    const program = anchor.workspace.amm as Program<Amm>;
+    const DECIMALS = new anchor.BN(10).pow(new anchor.BN(9));
+
+    async function setupPool(ammIndex: number) {
+        const provider = Keypair.generate();
+        const trader = Keypair.generate();
+        const admin = Keypair.generate();
+        const mintA = Keypair.generate();
+        const mintB = Keypair.generate();
+
+        await airdrop(connection, provider.publicKey);
+        await airdrop(connection, trader.publicKey);
+        await airdrop(connection, admin.publicKey);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintA);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintB);
+
+        const {ammPda} = await createAmm(program, provider, admin.publicKey, 30, ammIndex);
+        const pool = await createPool(program, provider, ammPda, mintA.publicKey, mintB.publicKey);
+
+        const liquidityAmount = new anchor.BN(1000).mul(DECIMALS);
+        const {lpAmount} = await addLiquidity(program, connection, provider, provider, pool.poolPda, mintA.publicKey, mintB.publicKey, pool.mintLiquidityPda, liquidityAmount, liquidityAmount);
+
+        return {provider, trader, admin, mintA, mintB, ammPda, lpAmount, ...pool};
+    }
+
+    function assertPaused(err: any) {
+        assert.isTrue(err.toString().includes("Paused"), `Expected Paused error, got: ${err.toString()}`);
+    }
+
+    it("Paused AMM rejects swap and add_liquidity but allows withdraw", async () => {
+        const ctx = await setupPool(600);
+        await pause(program, ctx.admin, ctx.ammPda);
+
+        const amount = new anchor.BN(10).mul(DECIMALS);
+        try {
+            await swap(program, connection, ctx.trader, ctx.provider, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, true, amount, new anchor.BN(0));
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertPaused(err);
+        }
+
+        try {
+            await addLiquidity(program, connection, ctx.provider, ctx.provider, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, ctx.mintLiquidityPda, amount, amount);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertPaused(err);
+        }
+
+        await withdrawLiquidity(program, connection, ctx.provider, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, ctx.mintLiquidityPda, ctx.authorityPda, ctx.lpAmount);
+
+        // unpausing restores trading
+        await applyConfigChange(program, ctx.admin, ctx.ammPda, ConfigChange.unpause());
+        await addLiquidity(program, connection, ctx.provider, ctx.provider, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, ctx.mintLiquidityPda, amount, amount);
+        await swap(program, connection, ctx.trader, ctx.provider, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, true, new anchor.BN(1).mul(DECIMALS), new anchor.BN(0));
+    });
+
+    it("Paused pool rejects swap and add_liquidity but allows withdraw", async () => {
+        const ctx = await setupPool(601);
+        await pausePool(program, ctx.admin, ctx.ammPda, ctx.poolPda);
+
+        const poolData = await program.account.ammPool.fetch(ctx.poolPda);
+        assert.isTrue(poolData.paused);
+
+        const amount = new anchor.BN(10).mul(DECIMALS);
+        try {
+            await swap(program, connection, ctx.trader, ctx.provider, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, false, amount, new anchor.BN(0));
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertPaused(err);
+        }
+
+        try {
+            await addLiquidity(program, connection, ctx.provider, ctx.provider, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, ctx.mintLiquidityPda, amount, amount);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertPaused(err);
+        }
+
+        await withdrawLiquidity(program, connection, ctx.provider, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, ctx.mintLiquidityPda, ctx.authorityPda, ctx.lpAmount);
+    });
+
+    it("Only the admin can pause", async () => {
+        const ctx = await setupPool(602);
+
+        try {
+            await pause(program, ctx.trader, ctx.ammPda);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("Unauthorized"), `Expected Unauthorized error, got: ${err.toString()}`);
+        }
+
+        try {
+            await pausePool(program, ctx.trader, ctx.ammPda, ctx.poolPda);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("Unauthorized"), `Expected Unauthorized error, got: ${err.toString()}`);
+        }
+    });
+});
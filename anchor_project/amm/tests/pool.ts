@@ -12,7 +12,7 @@ import {
     mintTo,
     TOKEN_PROGRAM_ID
 } from "@solana/spl-token";
-import {airdrop, createAmm, createMintSafe, createPool, indexToSeed} from "./helper";
+import {airdrop, createAmm, createMintSafe, createPool, feeTierToSeed, indexToSeed} from "./helper";
 
 describe("pool", () => {
     anchor.setProvider(anchor.AnchorProvider.env());
@@ -68,6 +68,7 @@ describe("pool", () => {
                     ammPda.toBuffer(),
                     mintC.publicKey.toBuffer(),
                     mintC.publicKey.toBuffer(),
+                    feeTierToSeed(0),
                 ],
                 program.programId
             );
@@ -78,6 +79,7 @@ describe("pool", () => {
                     ammPda.toBuffer(),
                     mintC.publicKey.toBuffer(),
                     mintC.publicKey.toBuffer(),
+                    feeTierToSeed(0),
                 ],
                 program.programId
             );
@@ -88,6 +90,7 @@ describe("pool", () => {
                     ammPda.toBuffer(),
                     mintC.publicKey.toBuffer(),
                     mintC.publicKey.toBuffer(),
+                    feeTierToSeed(0),
                 ],
                 program.programId
             );
@@ -105,7 +108,7 @@ describe("pool", () => {
             );
 
             try {
-                await program.methods.createPool().accounts({
+                await program.methods.createPool(0, null).accounts({
                     amm: ammPda,
                     pool: poolPda,
                     mintLiquidity: mintLiquidityPda,
@@ -115,8 +118,13 @@ describe("pool", () => {
                     mintA: mintC.publicKey,
                     mintB: mintC.publicKey,
                     signer: signer.publicKey,
+                    poolCreator: null,
+                    poolCurator: null,
+                    feeManager: null,
                     systemProgram: SystemProgram.programId,
                     tokenProgram: TOKEN_PROGRAM_ID,
+                    tokenProgramA: TOKEN_PROGRAM_ID,
+                    tokenProgramB: TOKEN_PROGRAM_ID,
                     associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
                 }).signers([signer]).rpc({commitment: "confirmed"});
 
@@ -139,6 +147,7 @@ describe("pool", () => {
                     fakeAmmPda.toBuffer(),
                     mintD.publicKey.toBuffer(),
                     mintA.publicKey.toBuffer(),
+                    feeTierToSeed(0),
                 ],
                 program.programId
             );
@@ -149,6 +158,7 @@ describe("pool", () => {
                     fakeAmmPda.toBuffer(),
                     mintD.publicKey.toBuffer(),
                     mintA.publicKey.toBuffer(),
+                    feeTierToSeed(0),
                 ],
                 program.programId
             );
@@ -159,6 +169,7 @@ describe("pool", () => {
                     fakeAmmPda.toBuffer(),
                     mintD.publicKey.toBuffer(),
                     mintA.publicKey.toBuffer(),
+                    feeTierToSeed(0),
                 ],
                 program.programId
             );
@@ -176,7 +187,7 @@ describe("pool", () => {
             );
 
             try {
-                await program.methods.createPool().accounts({
+                await program.methods.createPool(0, null).accounts({
                     amm: fakeAmmPda,
                     pool: poolPda,
                     mintLiquidity: mintLiquidityPda,
@@ -186,8 +197,13 @@ describe("pool", () => {
                     mintA: mintD.publicKey,
                     mintB: mintA.publicKey,
                     signer: signer.publicKey,
+                    poolCreator: null,
+                    poolCurator: null,
+                    feeManager: null,
                     systemProgram: SystemProgram.programId,
                     tokenProgram: TOKEN_PROGRAM_ID,
+                    tokenProgramA: TOKEN_PROGRAM_ID,
+                    tokenProgramB: TOKEN_PROGRAM_ID,
                     associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
                 }).signers([signer]).rpc({commitment: "confirmed"});
 
@@ -0,0 +1,109 @@
+import * as anchor from "@coral-xyz/anchor";
+import {Program} from "@coral-xyz/anchor";
+import {Amm} from "../target/types/amm";
+import {Keypair, Connection} from "@solana/web3.js";
+import {assert} from "chai";
+import {
+    addPoolCreator,
+    airdrop,
+    createAmm,
+    createMintSafe,
+    createPool,
+    removePoolCreator,
+    setPermissionedPoolCreation
+} from "./helper";
+
+describe("pool_creator", () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    let connection: Connection = anchor.getProvider().connection;
+
+    const program = anchor.workspace.amm as Program<Amm>;
+
+    async function setup(ammIndex: number) {
+        const signer = Keypair.generate();
+        const creator = Keypair.generate();
+        const admin = Keypair.generate();
+        const mintA = Keypair.generate();
+        const mintB = Keypair.generate();
+        const mintC = Keypair.generate();
+
+        await airdrop(connection, signer.publicKey);
+        await airdrop(connection, creator.publicKey);
+        await airdrop(connection, admin.publicKey);
+        await createMintSafe(connection, signer, signer.publicKey, 9, mintA);
+        await createMintSafe(connection, signer, signer.publicKey, 9, mintB);
+        await createMintSafe(connection, signer, signer.publicKey, 9, mintC);
+
+        const {ammPda} = await createAmm(program, signer, admin.publicKey, 30, ammIndex);
+        await setPermissionedPoolCreation(program, admin, ammPda, true);
+
+        return {signer, creator, admin, mintA, mintB, mintC, ammPda};
+    }
+
+    it("Only the admin and allowlisted creators can create pools on a permissioned AMM", async () => {
+        const ctx = await setup(1000);
+
+        try {
+            await createPool(program, ctx.signer, ctx.ammPda, ctx.mintA.publicKey, ctx.mintB.publicKey);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("PoolCreationNotAllowed"), `Expected PoolCreationNotAllowed error, got: ${err.toString()}`);
+        }
+
+        // admin can always create pools
+        await createPool(program, ctx.admin, ctx.ammPda, ctx.mintA.publicKey, ctx.mintB.publicKey);
+
+        const poolCreatorPda = await addPoolCreator(program, ctx.admin, ctx.ammPda, ctx.creator.publicKey);
+        const {poolPda} = await createPool(program, ctx.creator, ctx.ammPda, ctx.mintA.publicKey, ctx.mintC.publicKey, 0, null, poolCreatorPda);
+        const poolData = await program.account.ammPool.fetch(poolPda);
+        assert.strictEqual(poolData.amm.toBase58(), ctx.ammPda.toBase58());
+    });
+
+    it("Allowlist entry of another key can't be used", async () => {
+        const ctx = await setup(1001);
+
+        const poolCreatorPda = await addPoolCreator(program, ctx.admin, ctx.ammPda, ctx.creator.publicKey);
+        try {
+            await createPool(program, ctx.signer, ctx.ammPda, ctx.mintA.publicKey, ctx.mintB.publicKey, 0, null, poolCreatorPda);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("ConstraintSeeds") || err.toString().includes("2006"), `Expected ConstraintSeeds error, got: ${err.toString()}`);
+        }
+    });
+
+    it("Removed creator can't create pools anymore", async () => {
+        const ctx = await setup(1002);
+
+        const poolCreatorPda = await addPoolCreator(program, ctx.admin, ctx.ammPda, ctx.creator.publicKey);
+        await removePoolCreator(program, ctx.admin, ctx.ammPda, poolCreatorPda);
+
+        try {
+            await createPool(program, ctx.creator, ctx.ammPda, ctx.mintA.publicKey, ctx.mintB.publicKey);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("PoolCreationNotAllowed"), `Expected PoolCreationNotAllowed error, got: ${err.toString()}`);
+        }
+
+        // anyone can create pools again once the AMM is open
+        await setPermissionedPoolCreation(program, ctx.admin, ctx.ammPda, false);
+        await createPool(program, ctx.signer, ctx.ammPda, ctx.mintA.publicKey, ctx.mintB.publicKey);
+    });
+
+    it("Only the admin can manage pool creation", async () => {
+        const ctx = await setup(1003);
+
+        try {
+            await setPermissionedPoolCreation(program, ctx.signer, ctx.ammPda, false);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("Unauthorized"), `Expected Unauthorized error, got: ${err.toString()}`);
+        }
+
+        try {
+            await addPoolCreator(program, ctx.signer, ctx.ammPda, ctx.signer.publicKey);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("Unauthorized"), `Expected Unauthorized error, got: ${err.toString()}`);
+        }
+    });
+});
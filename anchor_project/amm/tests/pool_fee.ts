@@ -0,0 +1,134 @@
+import * as anchor from "@coral-xyz/anchor";
+import {Program} from "@coral-xyz/anchor";
+import {Amm} from "../target/types/amm";
+import {Keypair, Connection, PublicKey} from "@solana/web3.js";
+import {assert} from "chai";
+import {getAccount, getAssociatedTokenAddressSync} from "@solana/spl-token";
+import {
+    addLiquidity,
+    airdrop,
+    applyConfigChange,
+    ConfigChange,
+    ConfigChangeKind,
+    createAmm,
+    createMintSafe,
+    createPool,
+    executeConfigChange,
+    setPoolFee,
+    swap
+} from "./helper";
+
+describe("pool_fee", () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    let connection: Connection = anchor.getProvider().connection;
+
+    const program = anchor.workspace.amm as Program<Amm>;
+    const DECIMALS = new anchor.BN(10).pow(new anchor.BN(9));
+    const LIQUIDITY = new anchor.BN(1000).mul(DECIMALS);
+
+    function expectedOut(amount: anchor.BN, feeBps: number): anchor.BN {
+        const amountEff = amount.mul(new anchor.BN(10000 - feeBps)).div(new anchor.BN(10000));
+        return LIQUIDITY.sub(LIQUIDITY.mul(LIQUIDITY).div(LIQUIDITY.add(amountEff)));
+    }
+
+    async function swapAndGetOutput(trader: Keypair, provider: Keypair, ammPda: PublicKey, poolPda: PublicKey, mintA: PublicKey, mintB: PublicKey, amount: anchor.BN) {
+        await swap(program, connection, trader, provider, ammPda, poolPda, mintA, mintB, true, amount, new anchor.BN(0));
+        const traderAccountB = getAssociatedTokenAddressSync(mintB, trader.publicKey, false);
+        return new anchor.BN((await getAccount(connection, traderAccountB)).amount.toString());
+    }
+
+    it("Swap uses the pool fee instead of the AMM fee when it is set", async () => {
+        const provider = Keypair.generate();
+        const trader = Keypair.generate();
+        const admin = Keypair.generate();
+        const mintA = Keypair.generate();
+        const mintB = Keypair.generate();
+        const ammIndex = 800;
+        const ammFee = 30;
+        const poolFee = 1;
+
+        await airdrop(connection, provider.publicKey);
+        await airdrop(connection, trader.publicKey);
+        await airdrop(connection, admin.publicKey);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintA);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintB);
+
+        const {ammPda} = await createAmm(program, provider, admin.publicKey, ammFee, ammIndex);
+        const {poolPda, mintLiquidityPda} = await createPool(program, admin, ammPda, mintA.publicKey, mintB.publicKey, 0, poolFee);
+
+        const poolData = await program.account.ammPool.fetch(poolPda);
+        assert.strictEqual(poolData.feeBps, poolFee);
+
+        await addLiquidity(program, connection, provider, provider, poolPda, mintA.publicKey, mintB.publicKey, mintLiquidityPda, LIQUIDITY, LIQUIDITY);
+
+        const amount = new anchor.BN(10).mul(DECIMALS);
+        const output = await swapAndGetOutput(trader, provider, ammPda, poolPda, mintA.publicKey, mintB.publicKey, amount);
+        const expected = expectedOut(amount, poolFee);
+        assert.isTrue(output.eq(expected), `Trader should get ${expected.toString()} B, got ${output.toString()}`);
+    });
+
+    it("Admin can set and clear the pool fee later", async () => {
+        const provider = Keypair.generate();
+        const admin = Keypair.generate();
+        const mintA = Keypair.generate();
+        const mintB = Keypair.generate();
+        const ammIndex = 801;
+
+        await airdrop(connection, provider.publicKey);
+        await airdrop(connection, admin.publicKey);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintA);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintB);
+
+        const {ammPda} = await createAmm(program, provider, admin.publicKey, 30, ammIndex);
+        const {poolPda} = await createPool(program, provider, ammPda, mintA.publicKey, mintB.publicKey);
+
+        let poolData = await program.account.ammPool.fetch(poolPda);
+        assert.isNull(poolData.feeBps);
+
+        await applyConfigChange(program, admin, ammPda, ConfigChange.poolFee(poolPda, 100), poolPda);
+        poolData = await program.account.ammPool.fetch(poolPda);
+        assert.strictEqual(poolData.feeBps, 100);
+
+        await setPoolFee(program, admin, ammPda, poolPda, null);
+        await executeConfigChange(program, ammPda, ConfigChangeKind.poolFee, poolPda);
+        poolData = await program.account.ammPool.fetch(poolPda);
+        assert.isNull(poolData.feeBps);
+
+        try {
+            await setPoolFee(program, admin, ammPda, poolPda, 10000);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("InvalidFee"), `Expected InvalidFee error, got: ${err.toString()}`);
+        }
+    });
+
+    it("Only the admin can set the pool fee", async () => {
+        const provider = Keypair.generate();
+        const admin = Keypair.generate();
+        const mintA = Keypair.generate();
+        const mintB = Keypair.generate();
+        const ammIndex = 802;
+
+        await airdrop(connection, provider.publicKey);
+        await airdrop(connection, admin.publicKey);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintA);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintB);
+
+        const {ammPda} = await createAmm(program, provider, admin.publicKey, 30, ammIndex);
+
+        try {
+            await createPool(program, provider, ammPda, mintA.publicKey, mintB.publicKey, 0, 1);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("Unauthorized"), `Expected Unauthorized error, got: ${err.toString()}`);
+        }
+
+        const {poolPda} = await createPool(program, provider, ammPda, mintA.publicKey, mintB.publicKey);
+        try {
+            await setPoolFee(program, provider, ammPda, poolPda, 1);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("Unauthorized"), `Expected Unauthorized error, got: ${err.toString()}`);
+        }
+    });
+});
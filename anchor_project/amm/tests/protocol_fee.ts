@@ -0,0 +1,154 @@
+import * as anchor from "@coral-xyz/anchor";
+import {Program} from "@coral-xyz/anchor";
+import {Amm} from "../target/types/amm";
+import {Keypair, Connection} from "@solana/web3.js";
+import {assert} from "chai";
+import {getAccount, getAssociatedTokenAddressSync} from "@solana/spl-token";
+import {
+    addLiquidity,
+    airdrop,
+    applyConfigChange,
+    ConfigChange,
+    ConfigChangeKind,
+    collectProtocolFees,
+    createAmm,
+    createMintSafe,
+    createPool,
+    executeConfigChange,
+    getPoolReserves,
+    Role,
+    setProtocolFee,
+    setRole,
+    swap
+} from "./helper";
+
+describe("protocol_fee", () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    let connection: Connection = anchor.getProvider().connection;
+
+    const program = anchor.workspace.amm as Program<Amm>;
+    const DECIMALS = new anchor.BN(10).pow(new anchor.BN(9));
+
+    it("Protocol share of the swap fee is kept out of the reserves and sent to the treasury", async () => {
+        const provider = Keypair.generate();
+        const trader = Keypair.generate();
+        const admin = Keypair.generate();
+        const treasury = Keypair.generate();
+        const mintA = Keypair.generate();
+        const mintB = Keypair.generate();
+        const ammIndex = 700;
+        const fee = 1000; // 10% fee
+
+        await airdrop(connection, provider.publicKey);
+        await airdrop(connection, trader.publicKey);
+        await airdrop(connection, admin.publicKey);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintA);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintB);
+
+        const {ammPda} = await createAmm(program, provider, admin.publicKey, fee, ammIndex);
+        await setProtocolFee(program, admin, ammPda, 5000); // half of the fee
+        await executeConfigChange(program, ammPda, ConfigChangeKind.protocolFee);
+        await setRole(program, admin, ammPda, Role.treasury, treasury.publicKey);
+
+        const {poolPda, mintLiquidityPda, authorityPda} = await createPool(program, provider, ammPda, mintA.publicKey, mintB.publicKey);
+        const liquidityAmount = new anchor.BN(1000).mul(DECIMALS);
+        await addLiquidity(program, connection, provider, provider, poolPda, mintA.publicKey, mintB.publicKey, mintLiquidityPda, liquidityAmount, liquidityAmount);
+
+        // 100 A in: 10 A fee, 5 A of it to the protocol
+        const swapAmount = new anchor.BN(100).mul(DECIMALS);
+        await swap(program, connection, trader, provider, ammPda, poolPda, mintA.publicKey, mintB.publicKey, true, swapAmount, new anchor.BN(0));
+
+        const expectedProtocolFee = new anchor.BN(5).mul(DECIMALS);
+        let poolData = await program.account.ammPool.fetch(poolPda);
+        assert.isTrue(poolData.protocolFeesA.eq(expectedProtocolFee), `Protocol fees A should be ${expectedProtocolFee.toString()}, got ${poolData.protocolFeesA.toString()}`);
+        assert.isTrue(poolData.protocolFeesB.eqn(0));
+
+        // the trader got the output computed without the protocol fee
+        const amountEff = new anchor.BN(90).mul(DECIMALS);
+        const expectedOut = liquidityAmount.sub(liquidityAmount.mul(liquidityAmount).div(liquidityAmount.add(amountEff)));
+        const traderAccountB = getAssociatedTokenAddressSync(mintB.publicKey, trader.publicKey, false);
+        const traderBalanceB = new anchor.BN((await getAccount(connection, traderAccountB)).amount.toString());
+        assert.isTrue(traderBalanceB.eq(expectedOut), `Trader should get ${expectedOut.toString()} B, got ${traderBalanceB.toString()}`);
+
+        await collectProtocolFees(program, trader, ammPda, poolPda, mintA.publicKey, mintB.publicKey, treasury.publicKey);
+
+        const treasuryAccountA = getAssociatedTokenAddressSync(mintA.publicKey, treasury.publicKey, false);
+        const treasuryBalanceA = new anchor.BN((await getAccount(connection, treasuryAccountA)).amount.toString());
+        assert.isTrue(treasuryBalanceA.eq(expectedProtocolFee), `Treasury should get ${expectedProtocolFee.toString()} A, got ${treasuryBalanceA.toString()}`);
+
+        poolData = await program.account.ammPool.fetch(poolPda);
+        assert.isTrue(poolData.protocolFeesA.eqn(0));
+
+        const {reserveA} = await getPoolReserves(connection, mintA.publicKey, mintB.publicKey, authorityPda);
+        const expectedReserveA = liquidityAmount.add(swapAmount).sub(expectedProtocolFee);
+        assert.isTrue(reserveA.eq(expectedReserveA), `Pool A should be ${expectedReserveA.toString()}, got ${reserveA.toString()}`);
+    });
+
+    it("Fees can only be collected to the configured treasury", async () => {
+        const provider = Keypair.generate();
+        const trader = Keypair.generate();
+        const admin = Keypair.generate();
+        const mintA = Keypair.generate();
+        const mintB = Keypair.generate();
+        const ammIndex = 701;
+
+        await airdrop(connection, provider.publicKey);
+        await airdrop(connection, trader.publicKey);
+        await airdrop(connection, admin.publicKey);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintA);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintB);
+
+        const {ammPda} = await createAmm(program, provider, admin.publicKey, 1000, ammIndex);
+        await applyConfigChange(program, admin, ammPda, ConfigChange.protocolFee(10000));
+
+        const {poolPda, mintLiquidityPda} = await createPool(program, provider, ammPda, mintA.publicKey, mintB.publicKey);
+        const liquidityAmount = new anchor.BN(1000).mul(DECIMALS);
+        await addLiquidity(program, connection, provider, provider, poolPda, mintA.publicKey, mintB.publicKey, mintLiquidityPda, liquidityAmount, liquidityAmount);
+        await swap(program, connection, trader, provider, ammPda, poolPda, mintA.publicKey, mintB.publicKey, false, new anchor.BN(10).mul(DECIMALS), new anchor.BN(0));
+
+        try {
+            await collectProtocolFees(program, trader, ammPda, poolPda, mintA.publicKey, mintB.publicKey, trader.publicKey);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("ConstraintHasOne") || err.toString().includes("2001"), `Expected has_one error, got: ${err.toString()}`);
+        }
+
+        // treasury defaults to the admin
+        await collectProtocolFees(program, trader, ammPda, poolPda, mintA.publicKey, mintB.publicKey, admin.publicKey);
+        const adminAccountB = getAssociatedTokenAddressSync(mintB.publicKey, admin.publicKey, false);
+        const adminBalanceB = new anchor.BN((await getAccount(connection, adminAccountB)).amount.toString());
+        assert.isTrue(adminBalanceB.eq(new anchor.BN(1).mul(DECIMALS)), `Treasury should get 1 B, got ${adminBalanceB.toString()}`);
+    });
+
+    it("Only the admin can change the protocol fee and the treasury", async () => {
+        const signer = Keypair.generate();
+        const admin = Keypair.generate();
+        const ammIndex = 702;
+
+        await airdrop(connection, signer.publicKey);
+        await airdrop(connection, admin.publicKey);
+
+        const {ammPda} = await createAmm(program, signer, admin.publicKey, 100, ammIndex);
+
+        try {
+            await setProtocolFee(program, signer, ammPda, 5000);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("Unauthorized"), `Expected Unauthorized error, got: ${err.toString()}`);
+        }
+
+        try {
+            await setRole(program, signer, ammPda, Role.treasury, signer.publicKey);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("Unauthorized"), `Expected Unauthorized error, got: ${err.toString()}`);
+        }
+
+        try {
+            await setProtocolFee(program, admin, ammPda, 10001);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("InvalidFee"), `Expected InvalidFee error, got: ${err.toString()}`);
+        }
+    });
+});
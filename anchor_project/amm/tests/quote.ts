@@ -0,0 +1,121 @@
+import * as anchor from "@coral-xyz/anchor";
+import {Program} from "@coral-xyz/anchor";
+import {Amm} from "../target/types/amm";
+import {Keypair, Connection, PublicKey} from "@solana/web3.js";
+import {assert} from "chai";
+import {getAccount, getAssociatedTokenAddressSync} from "@solana/spl-token";
+import {
+    addLiquidity,
+    airdrop,
+    applyConfigChange,
+    ConfigChange,
+    createAmm,
+    createMintSafe,
+    createPool,
+    getPoolReserves,
+    pause,
+    quotePoolAccounts,
+    swap,
+    swapExactOut,
+    withdrawLiquidity
+} from "./helper";
+
+describe("quote", () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    let connection: Connection = anchor.getProvider().connection;
+
+    const program = anchor.workspace.amm as Program<Amm>;
+    const DECIMALS = new anchor.BN(10).pow(new anchor.BN(9));
+
+    async function setupPool(ammIndex: number) {
+        const provider = Keypair.generate();
+        const trader = Keypair.generate();
+        const mintA = Keypair.generate();
+        const mintB = Keypair.generate();
+
+        await airdrop(connection, provider.publicKey);
+        await airdrop(connection, trader.publicKey);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintA);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintB);
+
+        const {ammPda} = await createAmm(program, provider, provider.publicKey, 30, ammIndex);
+        await applyConfigChange(program, provider, ammPda, ConfigChange.protocolFee(2000));
+        const pool = await createPool(program, provider, ammPda, mintA.publicKey, mintB.publicKey);
+        const {lpAmount} = await addLiquidity(program, connection, provider, provider, pool.poolPda, mintA.publicKey, mintB.publicKey, pool.mintLiquidityPda, new anchor.BN(1000).mul(DECIMALS), new anchor.BN(3000).mul(DECIMALS));
+
+        const accounts = quotePoolAccounts(ammPda, pool, mintA.publicKey, mintB.publicKey);
+        return {provider, trader, mintA, mintB, ammPda, lpAmount, accounts, ...pool};
+    }
+
+    async function balance(mint: PublicKey, owner: PublicKey) {
+        return new anchor.BN((await getAccount(connection, getAssociatedTokenAddressSync(mint, owner, false))).amount.toString());
+    }
+
+    it("Swap quotes match the executed swaps and move no tokens", async () => {
+        const ctx = await setupPool(1900);
+        const amount = new anchor.BN(10).mul(DECIMALS);
+
+        const reservesBefore = await getPoolReserves(connection, ctx.mintA.publicKey, ctx.mintB.publicKey, ctx.authorityPda);
+        const quote = await program.methods.quoteSwap(true, amount).accounts(ctx.accounts).view();
+        const reservesAfter = await getPoolReserves(connection, ctx.mintA.publicKey, ctx.mintB.publicKey, ctx.authorityPda);
+        assert.isTrue(reservesBefore.reserveA.eq(reservesAfter.reserveA) && reservesBefore.reserveB.eq(reservesAfter.reserveB));
+
+        assert.isTrue(quote.amountIn.eq(amount));
+        assert.isTrue(quote.fee.eq(amount.muln(30).divn(10000)));
+        assert.isTrue(quote.protocolFee.eq(quote.fee.muln(2000).divn(10000)));
+
+        await swap(program, connection, ctx.trader, ctx.provider, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, true, amount, quote.amountOut);
+        assert.isTrue((await balance(ctx.mintB.publicKey, ctx.trader.publicKey)).eq(quote.amountOut));
+        assert.isTrue((await program.account.ammPool.fetch(ctx.poolPda)).protocolFeesA.eq(quote.protocolFee));
+
+        const amountOut = new anchor.BN(5).mul(DECIMALS);
+        const exactOutQuote = await program.methods.quoteSwapExactOut(false, amountOut).accounts(ctx.accounts).view();
+        assert.isTrue(exactOutQuote.amountOut.eq(amountOut));
+
+        await swapExactOut(program, connection, ctx.trader, ctx.provider, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, false, amountOut, exactOutQuote.amountIn);
+        assert.isTrue((await balance(ctx.mintA.publicKey, ctx.trader.publicKey)).eq(amountOut));
+    });
+
+    it("Liquidity previews match add and withdraw", async () => {
+        const ctx = await setupPool(1901);
+        const amount = new anchor.BN(100).mul(DECIMALS);
+
+        // more B than the pool price asks for, only 300 B are taken
+        const addPreview = await program.methods.previewAddLiquidity(amount, amount.muln(5)).accounts(ctx.accounts).view();
+        assert.isTrue(addPreview.amountA.eq(amount));
+        assert.isTrue(addPreview.amountB.eq(amount.muln(3)));
+
+        const {lpAmount} = await addLiquidity(program, connection, ctx.provider, ctx.provider, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, ctx.mintLiquidityPda, amount, amount.muln(5));
+        // the helper returns the whole LP balance of the provider
+        assert.isTrue(lpAmount.sub(ctx.lpAmount).eq(addPreview.lpAmount));
+
+        const withdrawPreview = await program.methods.previewWithdraw(ctx.lpAmount).accounts(ctx.accounts).view();
+        const {amountAOut, amountBOut} = await withdrawLiquidity(program, connection, ctx.provider, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, ctx.mintLiquidityPda, ctx.authorityPda, ctx.lpAmount);
+        assert.isTrue(withdrawPreview.amountA.eq(amountAOut));
+        assert.isTrue(withdrawPreview.amountB.eq(amountBOut));
+    });
+
+    it("Quotes fail like the instructions they mirror", async () => {
+        const ctx = await setupPool(1902);
+        const reserves = await getPoolReserves(connection, ctx.mintA.publicKey, ctx.mintB.publicKey, ctx.authorityPda);
+
+        try {
+            await program.methods.quoteSwapExactOut(true, reserves.reserveB).accounts(ctx.accounts).view();
+            assert.fail("Expected view to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("InsufficientLiquidity"), `Expected InsufficientLiquidity error, got: ${err.toString()}`);
+        }
+
+        await pause(program, ctx.provider, ctx.ammPda);
+        try {
+            await program.methods.quoteSwap(true, new anchor.BN(1)).accounts(ctx.accounts).view();
+            assert.fail("Expected view to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("Paused"), `Expected Paused error, got: ${err.toString()}`);
+        }
+
+        // withdrawals still work while paused, so does their preview
+        const preview = await program.methods.previewWithdraw(ctx.lpAmount).accounts(ctx.accounts).view();
+        assert.isTrue(preview.amountA.eq(reserves.reserveA));
+    });
+});
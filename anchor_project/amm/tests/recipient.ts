@@ -0,0 +1,92 @@
+import * as anchor from "@coral-xyz/anchor";
+import {Program} from "@coral-xyz/anchor";
+import {Amm} from "../target/types/amm";
+import {Keypair, Connection, PublicKey} from "@solana/web3.js";
+import {assert} from "chai";
+import {getAccount, getAssociatedTokenAddressSync, TOKEN_PROGRAM_ID} from "@solana/spl-token";
+import {
+    addLiquidity,
+    airdrop,
+    createAmm,
+    createMintSafe,
+    createPool,
+    swap,
+    swapExactOut,
+    withdrawLiquidity
+} from "./helper";
+
+describe("recipient", () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    let connection: Connection = anchor.getProvider().connection;
+
+    const program = anchor.workspace.amm as Program<Amm>;
+    const DECIMALS = new anchor.BN(10).pow(new anchor.BN(9));
+    const LIQUIDITY = new anchor.BN(1000).mul(DECIMALS);
+
+    async function setupPool(ammIndex: number) {
+        const provider = Keypair.generate();
+        const trader = Keypair.generate();
+        const merchant = Keypair.generate();
+        const mintA = Keypair.generate();
+        const mintB = Keypair.generate();
+
+        await airdrop(connection, provider.publicKey);
+        await airdrop(connection, trader.publicKey);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintA);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintB);
+
+        const {ammPda} = await createAmm(program, provider, provider.publicKey, 30, ammIndex);
+        const pool = await createPool(program, provider, ammPda, mintA.publicKey, mintB.publicKey);
+        const {lpAmount} = await addLiquidity(program, connection, provider, provider, pool.poolPda, mintA.publicKey, mintB.publicKey, pool.mintLiquidityPda, LIQUIDITY, LIQUIDITY);
+
+        return {provider, trader, merchant, mintA, mintB, ammPda, lpAmount, ...pool};
+    }
+
+    async function balance(mint: PublicKey, owner: PublicKey) {
+        return new anchor.BN((await getAccount(connection, getAssociatedTokenAddressSync(mint, owner, false))).amount.toString());
+    }
+
+    it("Swap pays the output to the recipient", async () => {
+        const ctx = await setupPool(1700);
+        const amount = new anchor.BN(10).mul(DECIMALS);
+
+        await swap(program, connection, ctx.trader, ctx.provider, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, true, amount, new anchor.BN(1), null, ctx.merchant.publicKey);
+        assert.isTrue((await balance(ctx.mintB.publicKey, ctx.merchant.publicKey)).gtn(0));
+
+        const amountOut = new anchor.BN(5).mul(DECIMALS);
+        await swapExactOut(program, connection, ctx.trader, ctx.provider, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, false, amountOut, amountOut.muln(2), null, ctx.merchant.publicKey);
+        assert.isTrue((await balance(ctx.mintA.publicKey, ctx.merchant.publicKey)).eq(amountOut));
+    });
+
+    it("Swap rejects an output mint that does not match the direction", async () => {
+        const ctx = await setupPool(1701);
+        const amount = new anchor.BN(10).mul(DECIMALS);
+
+        try {
+            await program.methods.swap(true, amount, new anchor.BN(0), null, false).accounts({
+                amm: ctx.ammPda,
+                pool: ctx.poolPda,
+                trader: ctx.provider.publicKey,
+                mintA: ctx.mintA.publicKey,
+                mintB: ctx.mintB.publicKey,
+                outputMint: ctx.mintA.publicKey,
+                recipient: ctx.merchant.publicKey,
+                recipientAccount: getAssociatedTokenAddressSync(ctx.mintA.publicKey, ctx.merchant.publicKey, false),
+                payer: ctx.provider.publicKey,
+                outputTokenProgram: TOKEN_PROGRAM_ID,
+            }).signers([ctx.provider]).rpc({commitment: "confirmed"});
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("InvalidOutputMint"), `Expected InvalidOutputMint error, got: ${err.toString()}`);
+        }
+    });
+
+    it("Withdraw pays both tokens to the recipient", async () => {
+        const ctx = await setupPool(1702);
+
+        const {amountAOut, amountBOut} = await withdrawLiquidity(program, connection, ctx.provider, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, ctx.mintLiquidityPda, ctx.authorityPda, ctx.lpAmount, null, ctx.merchant.publicKey);
+
+        assert.isTrue((await balance(ctx.mintA.publicKey, ctx.merchant.publicKey)).eq(amountAOut));
+        assert.isTrue((await balance(ctx.mintB.publicKey, ctx.merchant.publicKey)).eq(amountBOut));
+    });
+});
@@ -0,0 +1,142 @@
+import * as anchor from "@coral-xyz/anchor";
+import {Program} from "@coral-xyz/anchor";
+import {Amm} from "../target/types/amm";
+import {Keypair, Connection} from "@solana/web3.js";
+import {assert} from "chai";
+import {
+    addFeeTier,
+    airdrop,
+    applyConfigChange,
+    cancelConfigChange,
+    ConfigChange,
+    ConfigChangeKind,
+    createAmm,
+    MintPolicy,
+    pause,
+    Role,
+    setMintPolicy,
+    setRole,
+    updateAmmConfig
+} from "./helper";
+
+describe("roles", () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    let connection: Connection = anchor.getProvider().connection;
+
+    const program = anchor.workspace.amm as Program<Amm>;
+
+    async function setupAmm(ammIndex: number) {
+        const admin = Keypair.generate();
+        const operator = Keypair.generate();
+
+        await airdrop(connection, admin.publicKey);
+        await airdrop(connection, operator.publicKey);
+
+        const {ammPda} = await createAmm(program, admin, admin.publicKey, 30, ammIndex);
+        return {admin, operator, ammPda};
+    }
+
+    function assertUnauthorized(err: any) {
+        assert.isTrue(err.toString().includes("Unauthorized"), `Expected Unauthorized error, got: ${err.toString()}`);
+    }
+
+    it("All roles default to the admin", async () => {
+        const {admin, ammPda} = await setupAmm(1400);
+
+        const ammData = await program.account.amm.fetch(ammPda);
+        for (const key of [ammData.feeManager, ammData.pauser, ammData.poolCurator, ammData.treasury]) {
+            assert.strictEqual(key.toBase58(), admin.publicKey.toBase58());
+        }
+    });
+
+    it("Pauser can pause but cannot touch fees", async () => {
+        const {admin, operator, ammPda} = await setupAmm(1401);
+        await setRole(program, admin, ammPda, Role.pauser, operator.publicKey);
+
+        await pause(program, operator, ammPda);
+        assert.isTrue((await program.account.amm.fetch(ammPda)).paused);
+
+        try {
+            await updateAmmConfig(program, operator, ammPda, ConfigChange.fee(9999));
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertUnauthorized(err);
+        }
+
+        try {
+            await addFeeTier(program, operator, ammPda, 5);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertUnauthorized(err);
+        }
+
+        // the admin gave the role away
+        try {
+            await pause(program, admin, ammPda);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertUnauthorized(err);
+        }
+
+        await applyConfigChange(program, operator, ammPda, ConfigChange.unpause());
+        assert.isFalse((await program.account.amm.fetch(ammPda)).paused);
+    });
+
+    it("Fee manager and pool curator only control their own settings", async () => {
+        const {admin, operator, ammPda} = await setupAmm(1402);
+        const curator = Keypair.generate();
+        await airdrop(connection, curator.publicKey);
+        await setRole(program, admin, ammPda, Role.feeManager, operator.publicKey);
+        await setRole(program, admin, ammPda, Role.poolCurator, curator.publicKey);
+
+        await applyConfigChange(program, operator, ammPda, ConfigChange.fee(50));
+        await addFeeTier(program, operator, ammPda, 5);
+        await setMintPolicy(program, curator, ammPda, MintPolicy.allowlist);
+
+        const ammData = await program.account.amm.fetch(ammPda);
+        assert.strictEqual(ammData.fee, 50);
+        assert.deepEqual(ammData.feeTiers, [5]);
+        assert.deepEqual(ammData.mintPolicy, MintPolicy.allowlist);
+
+        try {
+            await setMintPolicy(program, operator, ammPda, MintPolicy.open);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertUnauthorized(err);
+        }
+    });
+
+    it("Only the role of a queued change can cancel it", async () => {
+        const {admin, operator, ammPda} = await setupAmm(1403);
+        await setRole(program, admin, ammPda, Role.feeManager, operator.publicKey);
+        await updateAmmConfig(program, admin, ammPda, ConfigChange.timelockDelay(60));
+
+        try {
+            await cancelConfigChange(program, operator, ammPda, ConfigChangeKind.timelockDelay);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertUnauthorized(err);
+        }
+
+        await cancelConfigChange(program, admin, ammPda, ConfigChangeKind.timelockDelay);
+        assert.lengthOf((await program.account.amm.fetch(ammPda)).pendingConfigChanges, 0);
+    });
+
+    it("Only the admin assigns roles and the admin role is not assignable", async () => {
+        const {admin, operator, ammPda} = await setupAmm(1404);
+
+        try {
+            await setRole(program, operator, ammPda, Role.pauser, operator.publicKey);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertUnauthorized(err);
+        }
+
+        try {
+            await setRole(program, admin, ammPda, Role.admin, operator.publicKey);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("InvalidRole"), `Expected InvalidRole error, got: ${err.toString()}`);
+        }
+    });
+});
@@ -8,9 +8,10 @@ import {
     getAccount,
     getAssociatedTokenAddressSync,
     getMint,
-    mintTo
+    mintTo,
+    TOKEN_PROGRAM_ID
 } from "@solana/spl-token";
-import {airdrop, createAmm, createMintSafe, createPool, addLiquidity, getPoolReserves, withdrawLiquidity} from "./helper";
+import {airdrop, createAmm, createMintSafe, createPool, addLiquidity, feeTierToSeed, getPoolReserves, withdrawLiquidity} from "./helper";
 
 describe("swap", () => {
     anchor.setProvider(anchor.AnchorProvider.env());
@@ -50,6 +51,7 @@ describe("swap", () => {
                 ammPda.toBuffer(),
                 mintA.toBuffer(),
                 mintB.toBuffer(),
+                feeTierToSeed(0),
             ],
             program.programId
         );
@@ -58,13 +60,17 @@ describe("swap", () => {
             ? (await getAccount(connection, traderAccountB)).amount
             : (await getAccount(connection, traderAccountA)).amount;
 
-        await program.methods.swap(isSwapA, amount, minOutAmount).accounts({
+        await program.methods.swap(isSwapA, amount, minOutAmount, null, false).accounts({
             amm: ammPda,
             pool: poolPda,
             trader: trader.publicKey,
             mintA: mintA,
             mintB: mintB,
+            outputMint: isSwapA ? mintB : mintA,
+            recipient: trader.publicKey,
+            recipientAccount: isSwapA ? traderAccountB : traderAccountA,
             payer: trader.publicKey,
+            outputTokenProgram: TOKEN_PROGRAM_ID,
         }).signers([trader]).rpc({commitment: "confirmed"});
 
         const traderOutputAfter = isSwapA 
@@ -0,0 +1,93 @@
+import * as anchor from "@coral-xyz/anchor";
+import {Program} from "@coral-xyz/anchor";
+import {Amm} from "../target/types/amm";
+import {Keypair, Connection, PublicKey} from "@solana/web3.js";
+import {assert} from "chai";
+import {getAccount, getAssociatedTokenAddressSync} from "@solana/spl-token";
+import {
+    addLiquidity,
+    airdrop,
+    createAmm,
+    createMintSafe,
+    createPool,
+    swapExactOut
+} from "./helper";
+
+describe("swap_exact_out", () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    let connection: Connection = anchor.getProvider().connection;
+
+    const program = anchor.workspace.amm as Program<Amm>;
+    const DECIMALS = new anchor.BN(10).pow(new anchor.BN(9));
+    const LIQUIDITY = new anchor.BN(1000).mul(DECIMALS);
+    const FEE = 30;
+
+    function divCeil(a: anchor.BN, b: anchor.BN): anchor.BN {
+        return a.add(b).sub(new anchor.BN(1)).div(b);
+    }
+
+    function expectedIn(amountOut: anchor.BN): anchor.BN {
+        const amountEff = divCeil(LIQUIDITY.mul(LIQUIDITY), LIQUIDITY.sub(amountOut)).sub(LIQUIDITY);
+        return divCeil(amountEff.mul(new anchor.BN(10000)), new anchor.BN(10000 - FEE));
+    }
+
+    async function balance(mint: PublicKey, owner: PublicKey) {
+        return new anchor.BN((await getAccount(connection, getAssociatedTokenAddressSync(mint, owner, false))).amount.toString());
+    }
+
+    async function setupPool(ammIndex: number) {
+        const provider = Keypair.generate();
+        const trader = Keypair.generate();
+        const mintA = Keypair.generate();
+        const mintB = Keypair.generate();
+
+        await airdrop(connection, provider.publicKey);
+        await airdrop(connection, trader.publicKey);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintA);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintB);
+
+        const {ammPda} = await createAmm(program, provider, provider.publicKey, FEE, ammIndex);
+        const pool = await createPool(program, provider, ammPda, mintA.publicKey, mintB.publicKey);
+        await addLiquidity(program, connection, provider, provider, pool.poolPda, mintA.publicKey, mintB.publicKey, pool.mintLiquidityPda, LIQUIDITY, LIQUIDITY);
+
+        return {provider, trader, mintA, mintB, ammPda, ...pool};
+    }
+
+    it("Trader receives the exact output and pays the rounded up input", async () => {
+        const ctx = await setupPool(1500);
+        const amountOut = new anchor.BN(10).mul(DECIMALS);
+        const maxAmountIn = new anchor.BN(11).mul(DECIMALS);
+
+        await swapExactOut(program, connection, ctx.trader, ctx.provider, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, true, amountOut, maxAmountIn);
+
+        const received = await balance(ctx.mintB.publicKey, ctx.trader.publicKey);
+        assert.isTrue(received.eq(amountOut), `Trader should get ${amountOut.toString()} B, got ${received.toString()}`);
+
+        const spent = maxAmountIn.sub(await balance(ctx.mintA.publicKey, ctx.trader.publicKey));
+        const expected = expectedIn(amountOut);
+        assert.isTrue(spent.eq(expected), `Trader should pay ${expected.toString()} A, paid ${spent.toString()}`);
+    });
+
+    it("Fails when the required input is above max_amount_in", async () => {
+        const ctx = await setupPool(1501);
+        const amountOut = new anchor.BN(10).mul(DECIMALS);
+
+        try {
+            await swapExactOut(program, connection, ctx.trader, ctx.provider, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, true, amountOut, expectedIn(amountOut).subn(1));
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("InputAmountTooHigh"), `Expected InputAmountTooHigh error, got: ${err.toString()}`);
+        }
+    });
+
+    it("Cannot take the whole output reserve", async () => {
+        const ctx = await setupPool(1502);
+
+        try {
+            await swapExactOut(program, connection, ctx.trader, ctx.provider, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, true, LIQUIDITY, new anchor.BN(Number.MAX_SAFE_INTEGER));
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("InsufficientLiquidity"), `Expected InsufficientLiquidity error, got: ${err.toString()}`);
+        }
+    });
+});
@@ -0,0 +1,156 @@
+import * as anchor from "@coral-xyz/anchor";
+import {Program} from "@coral-xyz/anchor";
+import {Amm} from "../target/types/amm";
+import {Keypair, Connection, PublicKey} from "@solana/web3.js";
+import {assert} from "chai";
+import {getAccount, getAssociatedTokenAddressSync} from "@solana/spl-token";
+import {
+    addLiquidity,
+    airdrop,
+    createAmm,
+    createMintSafe,
+    createPool,
+    RouteHop,
+    swapRoute
+} from "./helper";
+
+describe("swap_route", () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    let connection: Connection = anchor.getProvider().connection;
+
+    const program = anchor.workspace.amm as Program<Amm>;
+    const DECIMALS = new anchor.BN(10).pow(new anchor.BN(9));
+    const FEE = 30;
+    const OBSERVATION_PERIOD = 60;
+
+    function expectedOut(amount: anchor.BN, inputReserve: anchor.BN, outputReserve: anchor.BN): anchor.BN {
+        const amountEff = amount.muln(10000 - FEE).divn(10000);
+        const k = inputReserve.mul(outputReserve);
+        return outputReserve.sub(k.div(inputReserve.add(amountEff)));
+    }
+
+    async function balance(mint: PublicKey, owner: PublicKey) {
+        return new anchor.BN((await getAccount(connection, getAssociatedTokenAddressSync(mint, owner, false))).amount.toString());
+    }
+
+    // A/B pool with 1000/2000 and B/C pool with 3000/1000
+    async function setupRoute(ammIndex: number) {
+        const provider = Keypair.generate();
+        const trader = Keypair.generate();
+        const mintA = Keypair.generate();
+        const mintB = Keypair.generate();
+        const mintC = Keypair.generate();
+
+        await airdrop(connection, provider.publicKey);
+        await airdrop(connection, trader.publicKey);
+        for (const mint of [mintA, mintB, mintC]) {
+            await createMintSafe(connection, provider, provider.publicKey, 9, mint);
+        }
+
+        const {ammPda} = await createAmm(program, provider, provider.publicKey, FEE, ammIndex);
+        const poolAB = await createPool(program, provider, ammPda, mintA.publicKey, mintB.publicKey);
+        const poolBC = await createPool(program, provider, ammPda, mintB.publicKey, mintC.publicKey);
+        await addLiquidity(program, connection, provider, provider, poolAB.poolPda, mintA.publicKey, mintB.publicKey, poolAB.mintLiquidityPda, new anchor.BN(1000).mul(DECIMALS), new anchor.BN(2000).mul(DECIMALS));
+        await addLiquidity(program, connection, provider, provider, poolBC.poolPda, mintB.publicKey, mintC.publicKey, poolBC.mintLiquidityPda, new anchor.BN(3000).mul(DECIMALS), new anchor.BN(1000).mul(DECIMALS));
+
+        const hopAB: RouteHop = {...poolAB, mintA: mintA.publicKey, mintB: mintB.publicKey};
+        const hopBC: RouteHop = {...poolBC, mintA: mintB.publicKey, mintB: mintC.publicKey};
+        return {provider, trader, mintA, mintB, mintC, ammPda, hopAB, hopBC};
+    }
+
+    function assertError(err: any, name: string) {
+        assert.isTrue(err.toString().includes(name), `Expected ${name} error, got: ${err.toString()}`);
+    }
+
+    it("Chains two hops without touching the intermediate token", async () => {
+        const ctx = await setupRoute(1800);
+        const amount = new anchor.BN(10).mul(DECIMALS);
+
+        const outB = expectedOut(amount, new anchor.BN(1000).mul(DECIMALS), new anchor.BN(2000).mul(DECIMALS));
+        const outC = expectedOut(outB, new anchor.BN(3000).mul(DECIMALS), new anchor.BN(1000).mul(DECIMALS));
+
+        await swapRoute(program, connection, ctx.trader, ctx.provider, ctx.ammPda, [ctx.hopAB, ctx.hopBC], ctx.mintA.publicKey, ctx.mintC.publicKey, amount, outC);
+
+        assert.isTrue((await balance(ctx.mintC.publicKey, ctx.trader.publicKey)).eq(outC));
+        assert.isTrue((await balance(ctx.mintA.publicKey, ctx.trader.publicKey)).eqn(0));
+        assert.isNull(await connection.getAccountInfo(getAssociatedTokenAddressSync(ctx.mintB.publicKey, ctx.trader.publicKey, false)));
+
+        // and back the other way
+        await swapRoute(program, connection, ctx.trader, ctx.provider, ctx.ammPda, [ctx.hopBC, ctx.hopAB], ctx.mintC.publicKey, ctx.mintA.publicKey, outC, new anchor.BN(1));
+        assert.isTrue((await balance(ctx.mintA.publicKey, ctx.trader.publicKey)).gtn(0));
+    });
+
+    it("Records an observation in every pool of the route", async () => {
+        const ctx = await setupRoute(1803);
+        // a pool writes one observation per period, the deposits wrote the first one
+        await new Promise((resolve) => setTimeout(resolve, (OBSERVATION_PERIOD + 1) * 1000));
+
+        await swapRoute(program, connection, ctx.trader, ctx.provider, ctx.ammPda, [ctx.hopAB, ctx.hopBC], ctx.mintA.publicKey, ctx.mintC.publicKey, new anchor.BN(10).mul(DECIMALS), new anchor.BN(1));
+
+        for (const hop of [ctx.hopAB, ctx.hopBC]) {
+            // one observation from the first deposit and one from the route
+            const {observations} = await program.account.observations.fetch(hop.observationsPda);
+            assert.equal(observations.length, 2);
+            const pool = await program.account.ammPool.fetch(hop.poolPda);
+            assert.isTrue(pool.lastUpdateTimestamp.eq(observations[1].timestamp));
+            assert.isTrue(pool.priceACumulative.eq(observations[1].priceACumulative));
+        }
+
+        // the observations of another pool are rejected
+        try {
+            await swapRoute(program, connection, ctx.trader, ctx.provider, ctx.ammPda, [{...ctx.hopAB, observationsPda: ctx.hopBC.observationsPda}, ctx.hopBC], ctx.mintA.publicKey, ctx.mintC.publicKey, new anchor.BN(10).mul(DECIMALS), new anchor.BN(1));
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertError(err, "InvalidRoute");
+        }
+    });
+
+    it("Checks the minimum output of the whole route", async () => {
+        const ctx = await setupRoute(1801);
+        const amount = new anchor.BN(10).mul(DECIMALS);
+
+        try {
+            await swapRoute(program, connection, ctx.trader, ctx.provider, ctx.ammPda, [ctx.hopAB, ctx.hopBC], ctx.mintA.publicKey, ctx.mintC.publicKey, amount, amount);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertError(err, "OutputAmountTooLow");
+        }
+    });
+
+    it("Rejects routes that do not connect the input to the output", async () => {
+        const ctx = await setupRoute(1802);
+        const amount = new anchor.BN(10).mul(DECIMALS);
+
+        // the second hop does not take the output of the first
+        try {
+            await swapRoute(program, connection, ctx.trader, ctx.provider, ctx.ammPda, [ctx.hopBC, ctx.hopAB], ctx.mintA.publicKey, ctx.mintC.publicKey, amount, new anchor.BN(0));
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertError(err, "InvalidRoute");
+        }
+
+        // the route ends in B, not C
+        try {
+            await swapRoute(program, connection, ctx.trader, ctx.provider, ctx.ammPda, [ctx.hopAB], ctx.mintA.publicKey, ctx.mintC.publicKey, amount, new anchor.BN(0));
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertError(err, "InvalidRoute");
+        }
+
+        // the same pool twice in a row
+        try {
+            await swapRoute(program, connection, ctx.trader, ctx.provider, ctx.ammPda, [ctx.hopAB, ctx.hopAB], ctx.mintA.publicKey, ctx.mintA.publicKey, amount, new anchor.BN(0));
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertError(err, "InvalidRoute");
+        }
+
+        // no hops at all
+        try {
+            await swapRoute(program, connection, ctx.trader, ctx.provider, ctx.ammPda, [], ctx.mintA.publicKey, ctx.mintC.publicKey, amount, new anchor.BN(0));
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertError(err, "InvalidRoute");
+        }
+    });
+});
@@ -0,0 +1,230 @@
+import * as anchor from "@coral-xyz/anchor";
+import {Program} from "@coral-xyz/anchor";
+import {Amm} from "../target/types/amm";
+import {Keypair, Connection} from "@solana/web3.js";
+import {assert} from "chai";
+import {
+    airdrop,
+    applyConfigChange,
+    cancelConfigChange,
+    ConfigChange,
+    ConfigChangeKind,
+    createAmm,
+    createMintSafe,
+    createPool,
+    executeConfigChange,
+    pause,
+    pausePool,
+    setPoolFee,
+    updateAmmConfig
+} from "./helper";
+
+describe("timelock", () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    let connection: Connection = anchor.getProvider().connection;
+
+    const program = anchor.workspace.amm as Program<Amm>;
+    const DELAY = 2;
+
+    async function setupAmm(ammIndex: number, timelockDelay: number) {
+        const signer = Keypair.generate();
+        const admin = Keypair.generate();
+
+        await airdrop(connection, signer.publicKey);
+        await airdrop(connection, admin.publicKey);
+
+        const {ammPda} = await createAmm(program, signer, admin.publicKey, 30, ammIndex, timelockDelay);
+        return {signer, admin, ammPda};
+    }
+
+    async function waitForTimelock() {
+        await new Promise((resolve) => setTimeout(resolve, (DELAY + 1) * 1000));
+    }
+
+    function assertError(err: any, name: string) {
+        assert.isTrue(err.toString().includes(name), `Expected ${name} error, got: ${err.toString()}`);
+    }
+
+    it("Fee change is only applied after the delay", async () => {
+        const {admin, ammPda} = await setupAmm(1200, DELAY);
+
+        await updateAmmConfig(program, admin, ammPda, ConfigChange.fee(9999));
+        let ammData = await program.account.amm.fetch(ammPda);
+        assert.strictEqual(ammData.fee, 30);
+        assert.lengthOf(ammData.pendingConfigChanges, 1);
+
+        try {
+            await executeConfigChange(program, ammPda, ConfigChangeKind.fee);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertError(err, "TimelockNotElapsed");
+        }
+
+        await waitForTimelock();
+        await executeConfigChange(program, ammPda, ConfigChangeKind.fee);
+
+        ammData = await program.account.amm.fetch(ammPda);
+        assert.strictEqual(ammData.fee, 9999);
+        assert.lengthOf(ammData.pendingConfigChanges, 0);
+    });
+
+    it("Only one change per kind can be queued and the admin can cancel it", async () => {
+        const {signer, admin, ammPda} = await setupAmm(1201, DELAY);
+
+        await updateAmmConfig(program, admin, ammPda, ConfigChange.fee(100));
+        try {
+            await updateAmmConfig(program, admin, ammPda, ConfigChange.fee(200));
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertError(err, "ConfigChangeAlreadyQueued");
+        }
+
+        try {
+            await cancelConfigChange(program, signer, ammPda, ConfigChangeKind.fee);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertError(err, "Unauthorized");
+        }
+
+        await cancelConfigChange(program, admin, ammPda, ConfigChangeKind.fee);
+        const ammData = await program.account.amm.fetch(ammPda);
+        assert.lengthOf(ammData.pendingConfigChanges, 0);
+
+        try {
+            await executeConfigChange(program, ammPda, ConfigChangeKind.fee);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertError(err, "NoPendingConfigChange");
+        }
+    });
+
+    it("Changes of different kinds are queued independently", async () => {
+        const {admin, ammPda} = await setupAmm(1206, DELAY);
+
+        await pause(program, admin, ammPda);
+        await updateAmmConfig(program, admin, ammPda, ConfigChange.fee(100));
+        await updateAmmConfig(program, admin, ammPda, ConfigChange.unpause());
+        assert.lengthOf((await program.account.amm.fetch(ammPda)).pendingConfigChanges, 2);
+
+        await waitForTimelock();
+        await executeConfigChange(program, ammPda, ConfigChangeKind.unpause);
+        let ammData = await program.account.amm.fetch(ammPda);
+        assert.isFalse(ammData.paused);
+        assert.strictEqual(ammData.fee, 30);
+        assert.lengthOf(ammData.pendingConfigChanges, 1);
+
+        await executeConfigChange(program, ammPda, ConfigChangeKind.fee);
+        ammData = await program.account.amm.fetch(ammPda);
+        assert.strictEqual(ammData.fee, 100);
+        assert.lengthOf(ammData.pendingConfigChanges, 0);
+    });
+
+    it("Pausing is immediate but lifting the pause waits for the delay", async () => {
+        const {signer, admin, ammPda} = await setupAmm(1202, DELAY);
+        const mintA = Keypair.generate();
+        const mintB = Keypair.generate();
+        await createMintSafe(connection, signer, signer.publicKey, 9, mintA);
+        await createMintSafe(connection, signer, signer.publicKey, 9, mintB);
+        const {poolPda} = await createPool(program, signer, ammPda, mintA.publicKey, mintB.publicKey);
+
+        await pausePool(program, admin, ammPda, poolPda);
+        assert.isTrue((await program.account.ammPool.fetch(poolPda)).paused);
+
+        await updateAmmConfig(program, admin, ammPda, ConfigChange.unpausePool(poolPda), poolPda);
+        await waitForTimelock();
+
+        try {
+            await executeConfigChange(program, ammPda, ConfigChangeKind.unpausePool);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertError(err, "PoolMismatch");
+        }
+
+        await executeConfigChange(program, ammPda, ConfigChangeKind.unpausePool, poolPda);
+        assert.isFalse((await program.account.ammPool.fetch(poolPda)).paused);
+
+        await pause(program, admin, ammPda);
+        assert.isTrue((await program.account.amm.fetch(ammPda)).paused);
+    });
+
+    it("Each pool queues its own pool changes", async () => {
+        const {signer, admin, ammPda} = await setupAmm(1207, DELAY);
+        const pools = [];
+        for (let i = 0; i < 2; i++) {
+            const mintA = Keypair.generate();
+            const mintB = Keypair.generate();
+            await createMintSafe(connection, signer, signer.publicKey, 9, mintA);
+            await createMintSafe(connection, signer, signer.publicKey, 9, mintB);
+            const {poolPda} = await createPool(program, signer, ammPda, mintA.publicKey, mintB.publicKey);
+            pools.push(poolPda);
+        }
+
+        for (const [i, poolPda] of pools.entries()) {
+            await pausePool(program, admin, ammPda, poolPda);
+            await setPoolFee(program, admin, ammPda, poolPda, 100 * (i + 1));
+            await updateAmmConfig(program, admin, ammPda, ConfigChange.unpausePool(poolPda), poolPda);
+            assert.lengthOf((await program.account.ammPool.fetch(poolPda)).pendingConfigChanges, 2);
+        }
+        assert.lengthOf((await program.account.amm.fetch(ammPda)).pendingConfigChanges, 0);
+
+        // a change queued for one pool cannot be put into the queue of another
+        try {
+            await updateAmmConfig(program, admin, ammPda, ConfigChange.poolFee(pools[0], 50), pools[1]);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertError(err, "PoolMismatch");
+        }
+
+        await cancelConfigChange(program, admin, ammPda, ConfigChangeKind.unpausePool, pools[1]);
+        await waitForTimelock();
+        for (const poolPda of pools) {
+            await executeConfigChange(program, ammPda, ConfigChangeKind.poolFee, poolPda);
+        }
+        await executeConfigChange(program, ammPda, ConfigChangeKind.unpausePool, pools[0]);
+
+        const [first, second] = await Promise.all(pools.map((poolPda) => program.account.ammPool.fetch(poolPda)));
+        assert.strictEqual(first.feeBps, 100);
+        assert.strictEqual(second.feeBps, 200);
+        assert.isFalse(first.paused);
+        assert.isTrue(second.paused);
+        assert.lengthOf(first.pendingConfigChanges, 0);
+        assert.lengthOf(second.pendingConfigChanges, 0);
+    });
+
+    it("Admin transfer is queued before it can be accepted", async () => {
+        const {admin, ammPda} = await setupAmm(1203, DELAY);
+        const newAdmin = Keypair.generate();
+
+        await updateAmmConfig(program, admin, ammPda, ConfigChange.admin(newAdmin.publicKey));
+        assert.isNull((await program.account.amm.fetch(ammPda)).pendingAdmin);
+
+        await waitForTimelock();
+        await executeConfigChange(program, ammPda, ConfigChangeKind.admin);
+
+        const ammData = await program.account.amm.fetch(ammPda);
+        assert.strictEqual(ammData.pendingAdmin.toBase58(), newAdmin.publicKey.toBase58());
+    });
+
+    it("Timelock delay itself can only be changed through the queue", async () => {
+        const {admin, ammPda} = await setupAmm(1204, 0);
+
+        await applyConfigChange(program, admin, ammPda, ConfigChange.timelockDelay(DELAY));
+        assert.strictEqual((await program.account.amm.fetch(ammPda)).timelockDelay.toNumber(), DELAY);
+
+        try {
+            await updateAmmConfig(program, admin, ammPda, ConfigChange.timelockDelay(-1));
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertError(err, "InvalidTimelockDelay");
+        }
+    });
+
+    it("Cannot create an AMM with a delay above the maximum", async () => {
+        try {
+            await setupAmm(1205, 30 * 24 * 60 * 60 + 1);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertError(err, "InvalidTimelockDelay");
+        }
+    });
+});
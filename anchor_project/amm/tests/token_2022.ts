@@ -0,0 +1,184 @@
+import * as anchor from "@coral-xyz/anchor";
+import {Program} from "@coral-xyz/anchor";
+import {Amm} from "../target/types/amm";
+import {Keypair, Connection, PublicKey, SystemProgram, Transaction, sendAndConfirmTransaction} from "@solana/web3.js";
+import {assert} from "chai";
+import {
+    createInitializeMintCloseAuthorityInstruction,
+    createInitializeMintInstruction,
+    createInitializePermanentDelegateInstruction,
+    createInitializeTransferFeeConfigInstruction,
+    ExtensionType,
+    getAccount,
+    getAssociatedTokenAddressSync,
+    getMintLen,
+    TOKEN_2022_PROGRAM_ID,
+    TOKEN_PROGRAM_ID
+} from "@solana/spl-token";
+import {
+    addLiquidity,
+    airdrop,
+    createAmm,
+    createMintSafe,
+    createPool,
+    getPoolReserves,
+    quotePoolAccounts,
+    swap,
+    withdrawLiquidity
+} from "./helper";
+
+describe("token_2022", () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    let connection: Connection = anchor.getProvider().connection;
+
+    const program = anchor.workspace.amm as Program<Amm>;
+    const DECIMALS = new anchor.BN(10).pow(new anchor.BN(9));
+    const TRANSFER_FEE_BPS = 100;
+    const MAX_TRANSFER_FEE = BigInt(1_000_000) * BigInt(1_000_000_000);
+
+    // Token-2022 mint withholding `TRANSFER_FEE_BPS` of every transfer
+    async function createTransferFeeMint(payer: Keypair, mint: Keypair) {
+        const space = getMintLen([ExtensionType.TransferFeeConfig]);
+        const lamports = await connection.getMinimumBalanceForRentExemption(space);
+        const tx = new Transaction().add(
+            SystemProgram.createAccount({
+                fromPubkey: payer.publicKey,
+                newAccountPubkey: mint.publicKey,
+                space,
+                lamports,
+                programId: TOKEN_2022_PROGRAM_ID,
+            }),
+            createInitializeTransferFeeConfigInstruction(mint.publicKey, payer.publicKey, payer.publicKey, TRANSFER_FEE_BPS, MAX_TRANSFER_FEE, TOKEN_2022_PROGRAM_ID),
+            createInitializeMintInstruction(mint.publicKey, 9, payer.publicKey, null, TOKEN_2022_PROGRAM_ID),
+        );
+        await sendAndConfirmTransaction(connection, tx, [payer, mint], {commitment: "confirmed"});
+    }
+
+    // Token-2022 mint with a single extension set up by `initExtension`
+    async function createMintWithExtension(payer: Keypair, mint: Keypair, extension: ExtensionType, initExtension: any) {
+        const space = getMintLen([extension]);
+        const lamports = await connection.getMinimumBalanceForRentExemption(space);
+        const tx = new Transaction().add(
+            SystemProgram.createAccount({
+                fromPubkey: payer.publicKey,
+                newAccountPubkey: mint.publicKey,
+                space,
+                lamports,
+                programId: TOKEN_2022_PROGRAM_ID,
+            }),
+            initExtension,
+            createInitializeMintInstruction(mint.publicKey, 9, payer.publicKey, null, TOKEN_2022_PROGRAM_ID),
+        );
+        await sendAndConfirmTransaction(connection, tx, [payer, mint], {commitment: "confirmed"});
+    }
+
+    function transferFee(amount: anchor.BN) {
+        return amount.muln(TRANSFER_FEE_BPS).addn(9_999).divn(10_000);
+    }
+
+    // pool of a classic SPL token A against a Token-2022 token B with a transfer fee
+    async function setupPool(ammIndex: number) {
+        const provider = Keypair.generate();
+        const trader = Keypair.generate();
+        const mintA = Keypair.generate();
+        const mintB = Keypair.generate();
+
+        await airdrop(connection, provider.publicKey);
+        await airdrop(connection, trader.publicKey);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintA);
+        await createTransferFeeMint(provider, mintB);
+
+        const {ammPda} = await createAmm(program, provider, provider.publicKey, 30, ammIndex);
+        const pool = await createPool(program, provider, ammPda, mintA.publicKey, mintB.publicKey);
+        const accounts = quotePoolAccounts(ammPda, pool, mintA.publicKey, mintB.publicKey);
+        return {provider, trader, mintA, mintB, ammPda, accounts, ...pool};
+    }
+
+    async function balance(mint: PublicKey, owner: PublicKey, tokenProgram: PublicKey) {
+        const account = getAssociatedTokenAddressSync(mint, owner, false, tokenProgram);
+        return new anchor.BN((await getAccount(connection, account, "confirmed", tokenProgram)).amount.toString());
+    }
+
+    it("Creates a pool across SPL Token and Token-2022 and deposits net of the transfer fee", async () => {
+        const ctx = await setupPool(2700);
+        const pool = await program.account.ammPool.fetch(ctx.poolPda);
+        assert.isTrue(pool.tokenProgramA.equals(TOKEN_PROGRAM_ID));
+        assert.isTrue(pool.tokenProgramB.equals(TOKEN_2022_PROGRAM_ID));
+
+        const amountA = new anchor.BN(1000).mul(DECIMALS);
+        const amountB = new anchor.BN(1000).mul(DECIMALS);
+        await addLiquidity(program, connection, ctx.provider, ctx.provider, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, ctx.mintLiquidityPda, amountA, amountB);
+
+        const reserves = await getPoolReserves(connection, ctx.mintA.publicKey, ctx.mintB.publicKey, ctx.authorityPda);
+        assert.isTrue(reserves.reserveA.eq(amountA));
+        assert.isTrue(reserves.reserveB.eq(amountB.sub(transferFee(amountB))));
+    });
+
+    it("Prices swaps on what reaches the pool and quotes the transfer fees", async () => {
+        const ctx = await setupPool(2701);
+        await addLiquidity(program, connection, ctx.provider, ctx.provider, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, ctx.mintLiquidityPda, new anchor.BN(1000).mul(DECIMALS), new anchor.BN(1000).mul(DECIMALS));
+        const amount = new anchor.BN(10).mul(DECIMALS);
+
+        // B in, the pool only receives what the transfer fee leaves
+        const quoteIn = await program.methods.quoteSwap(false, amount).accounts(ctx.accounts).view();
+        assert.isTrue(quoteIn.amountIn.eq(amount));
+        assert.isTrue(quoteIn.transferFeeIn.eq(transferFee(amount)));
+        assert.isTrue(quoteIn.transferFeeOut.eqn(0));
+
+        const reservesBefore = await getPoolReserves(connection, ctx.mintA.publicKey, ctx.mintB.publicKey, ctx.authorityPda);
+        await swap(program, connection, ctx.trader, ctx.provider, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, false, amount, quoteIn.amountOut);
+        const reservesAfter = await getPoolReserves(connection, ctx.mintA.publicKey, ctx.mintB.publicKey, ctx.authorityPda);
+        assert.isTrue(reservesAfter.reserveB.sub(reservesBefore.reserveB).eq(amount.sub(quoteIn.transferFeeIn)));
+        assert.isTrue((await balance(ctx.mintA.publicKey, ctx.trader.publicKey, TOKEN_PROGRAM_ID)).eq(quoteIn.amountOut));
+
+        // B out, the recipient bears the transfer fee on what the pool sends
+        const quoteOut = await program.methods.quoteSwap(true, amount).accounts(ctx.accounts).view();
+        assert.isTrue(quoteOut.transferFeeIn.eqn(0));
+        assert.isTrue(quoteOut.transferFeeOut.gtn(0));
+
+        const before = await balance(ctx.mintB.publicKey, ctx.trader.publicKey, TOKEN_2022_PROGRAM_ID);
+        await swap(program, connection, ctx.trader, ctx.provider, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, true, amount, quoteOut.amountOut);
+        const after = await balance(ctx.mintB.publicKey, ctx.trader.publicKey, TOKEN_2022_PROGRAM_ID);
+        assert.isTrue(after.sub(before).eq(quoteOut.amountOut));
+    });
+
+    it("Withdraws with the transfer fee borne by the recipient", async () => {
+        const ctx = await setupPool(2702);
+        const {lpAmount} = await addLiquidity(program, connection, ctx.provider, ctx.provider, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, ctx.mintLiquidityPda, new anchor.BN(1000).mul(DECIMALS), new anchor.BN(1000).mul(DECIMALS));
+
+        const preview = await program.methods.previewWithdraw(lpAmount.divn(2)).accounts(ctx.accounts).view();
+        const beforeA = await balance(ctx.mintA.publicKey, ctx.provider.publicKey, TOKEN_PROGRAM_ID);
+        const beforeB = await balance(ctx.mintB.publicKey, ctx.provider.publicKey, TOKEN_2022_PROGRAM_ID);
+        const {amountBOut} = await withdrawLiquidity(program, connection, ctx.provider, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, ctx.mintLiquidityPda, ctx.authorityPda, lpAmount.divn(2));
+        const receivedA = (await balance(ctx.mintA.publicKey, ctx.provider.publicKey, TOKEN_PROGRAM_ID)).sub(beforeA);
+        const receivedB = (await balance(ctx.mintB.publicKey, ctx.provider.publicKey, TOKEN_2022_PROGRAM_ID)).sub(beforeB);
+
+        assert.isTrue(receivedB.eq(amountBOut.sub(transferFee(amountBOut))));
+        assert.isTrue(receivedA.eq(preview.amountA));
+        assert.isTrue(receivedB.eq(preview.amountB));
+    });
+
+    it("Rejects pools of mints with extensions outside the allowlist", async () => {
+        const provider = Keypair.generate();
+        await airdrop(connection, provider.publicKey);
+        const {ammPda} = await createAmm(program, provider, provider.publicKey, 30, 2703);
+        const mintA = Keypair.generate();
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintA);
+
+        const closable = Keypair.generate();
+        await createMintWithExtension(provider, closable, ExtensionType.MintCloseAuthority,
+            createInitializeMintCloseAuthorityInstruction(closable.publicKey, provider.publicKey, TOKEN_2022_PROGRAM_ID));
+        const delegated = Keypair.generate();
+        await createMintWithExtension(provider, delegated, ExtensionType.PermanentDelegate,
+            createInitializePermanentDelegateInstruction(delegated.publicKey, provider.publicKey, TOKEN_2022_PROGRAM_ID));
+
+        for (const mintB of [closable, delegated]) {
+            try {
+                await createPool(program, provider, ammPda, mintA.publicKey, mintB.publicKey);
+                assert.fail("Expected transaction to fail");
+            } catch (err) {
+                assert.isTrue(err.toString().includes("UnsupportedMintExtension"), `Expected UnsupportedMintExtension error, got: ${err.toString()}`);
+            }
+        }
+    });
+});
@@ -0,0 +1,223 @@
+import * as anchor from "@coral-xyz/anchor";
+import {Program} from "@coral-xyz/anchor";
+import {Amm} from "../target/types/amm";
+import {AccountMeta, Keypair, Connection, PublicKey, SystemProgram, Transaction, sendAndConfirmTransaction} from "@solana/web3.js";
+import {assert} from "chai";
+import {
+    createInitializeMintInstruction,
+    createInitializeTransferHookInstruction,
+    ExtensionType,
+    getAccount,
+    getAssociatedTokenAddressSync,
+    getMintLen,
+    TOKEN_2022_PROGRAM_ID
+} from "@solana/spl-token";
+import {
+    addLiquidity,
+    addTransferHookProgram,
+    airdrop,
+    cancelLongTermOrder,
+    createAmm,
+    createMintSafe,
+    createPool,
+    placeLongTermOrder,
+    removeTransferHookProgram,
+    swap,
+    withdrawLiquidity,
+    withdrawLongTermOrder
+} from "./helper";
+
+describe("transfer_hook", () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    let connection: Connection = anchor.getProvider().connection;
+
+    const program = anchor.workspace.amm as Program<Amm>;
+    const hook = anchor.workspace.transferHookReceiver as Program;
+    const DECIMALS = new anchor.BN(10).pow(new anchor.BN(9));
+    const LIQUIDITY = new anchor.BN(1000).mul(DECIMALS);
+
+    function extraAccountMetaListPda(mint: PublicKey) {
+        const [pda] = PublicKey.findProgramAddressSync([Buffer.from("extra-account-metas"), mint.toBuffer()], hook.programId);
+        return pda;
+    }
+
+    function counterPda(mint: PublicKey) {
+        const [pda] = PublicKey.findProgramAddressSync([Buffer.from("COUNTER"), mint.toBuffer()], hook.programId);
+        return pda;
+    }
+
+    // hook program, validation account and the counter it lists, forwarded as remaining accounts
+    function hookAccounts(mint: PublicKey): AccountMeta[] {
+        return [
+            {pubkey: hook.programId, isSigner: false, isWritable: false},
+            {pubkey: extraAccountMetaListPda(mint), isSigner: false, isWritable: false},
+            {pubkey: counterPda(mint), isSigner: false, isWritable: true},
+        ];
+    }
+
+    function sleep(ms: number) {
+        return new Promise((resolve) => setTimeout(resolve, ms));
+    }
+
+    async function transfers(mint: PublicKey) {
+        return (await hook.account.counter.fetch(counterPda(mint))).transfers.toNumber();
+    }
+
+    // Token-2022 mint whose transfers run the test hook, without an authority to switch the hook program by default
+    async function createTransferHookMint(payer: Keypair, mint: Keypair, authority: PublicKey = PublicKey.default) {
+        const space = getMintLen([ExtensionType.TransferHook]);
+        const lamports = await connection.getMinimumBalanceForRentExemption(space);
+        const tx = new Transaction().add(
+            SystemProgram.createAccount({
+                fromPubkey: payer.publicKey,
+                newAccountPubkey: mint.publicKey,
+                space,
+                lamports,
+                programId: TOKEN_2022_PROGRAM_ID,
+            }),
+            createInitializeTransferHookInstruction(mint.publicKey, authority, hook.programId, TOKEN_2022_PROGRAM_ID),
+            createInitializeMintInstruction(mint.publicKey, 9, payer.publicKey, null, TOKEN_2022_PROGRAM_ID),
+        );
+        await sendAndConfirmTransaction(connection, tx, [payer, mint], {commitment: "confirmed"});
+
+        await hook.methods.initializeExtraAccountMetaList().accounts({
+            payer: payer.publicKey,
+            extraAccountMetaList: extraAccountMetaListPda(mint.publicKey),
+            mint: mint.publicKey,
+            counter: counterPda(mint.publicKey),
+            systemProgram: SystemProgram.programId,
+        }).signers([payer]).rpc({commitment: "confirmed"});
+    }
+
+    // AMM of `admin` with a classic SPL token A and a hooked Token-2022 token B
+    async function setup(ammIndex: number) {
+        const provider = Keypair.generate();
+        const trader = Keypair.generate();
+        const admin = Keypair.generate();
+        const mintA = Keypair.generate();
+        const mintB = Keypair.generate();
+
+        await airdrop(connection, provider.publicKey);
+        await airdrop(connection, trader.publicKey);
+        await airdrop(connection, admin.publicKey);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintA);
+        await createTransferHookMint(provider, mintB);
+
+        const {ammPda} = await createAmm(program, provider, admin.publicKey, 30, ammIndex);
+        return {provider, trader, admin, mintA, mintB, ammPda};
+    }
+
+    it("Only creates pools of hooked mints once the admin approved the hook program", async () => {
+        const ctx = await setup(2800);
+
+        try {
+            await createPool(program, ctx.provider, ctx.ammPda, ctx.mintA.publicKey, ctx.mintB.publicKey);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("TransferHookProgramNotApproved"), `Expected TransferHookProgramNotApproved error, got: ${err.toString()}`);
+        }
+
+        try {
+            await addTransferHookProgram(program, ctx.provider, ctx.ammPda, hook.programId);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("Unauthorized"), `Expected Unauthorized error, got: ${err.toString()}`);
+        }
+
+        await addTransferHookProgram(program, ctx.admin, ctx.ammPda, hook.programId);
+        const amm = await program.account.amm.fetch(ctx.ammPda);
+        assert.isTrue(amm.transferHookPrograms[0].equals(hook.programId));
+
+        try {
+            await addTransferHookProgram(program, ctx.admin, ctx.ammPda, hook.programId);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("TransferHookProgramAlreadyApproved"), `Expected TransferHookProgramAlreadyApproved error, got: ${err.toString()}`);
+        }
+
+        await createPool(program, ctx.provider, ctx.ammPda, ctx.mintA.publicKey, ctx.mintB.publicKey);
+
+        await removeTransferHookProgram(program, ctx.admin, ctx.ammPda, hook.programId);
+        assert.lengthOf((await program.account.amm.fetch(ctx.ammPda)).transferHookPrograms, 0);
+        try {
+            await removeTransferHookProgram(program, ctx.admin, ctx.ammPda, hook.programId);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("TransferHookProgramNotApproved"), `Expected TransferHookProgramNotApproved error, got: ${err.toString()}`);
+        }
+    });
+
+    it("Runs the hook on deposits, swaps and withdrawals", async () => {
+        const ctx = await setup(2801);
+        await addTransferHookProgram(program, ctx.admin, ctx.ammPda, hook.programId);
+        const pool = await createPool(program, ctx.provider, ctx.ammPda, ctx.mintA.publicKey, ctx.mintB.publicKey);
+        const accounts = hookAccounts(ctx.mintB.publicKey);
+
+        const {lpAmount} = await addLiquidity(program, connection, ctx.provider, ctx.provider, pool.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, pool.mintLiquidityPda, LIQUIDITY, LIQUIDITY, null, accounts);
+        assert.strictEqual(await transfers(ctx.mintB.publicKey), 1);
+
+        const amount = new anchor.BN(10).mul(DECIMALS);
+        await swap(program, connection, ctx.trader, ctx.provider, ctx.ammPda, pool.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, true, amount, new anchor.BN(1), null, ctx.trader.publicKey, accounts);
+        assert.strictEqual(await transfers(ctx.mintB.publicKey), 2);
+
+        await swap(program, connection, ctx.trader, ctx.provider, ctx.ammPda, pool.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, false, amount, new anchor.BN(1), null, ctx.trader.publicKey, accounts);
+        assert.strictEqual(await transfers(ctx.mintB.publicKey), 3);
+
+        await withdrawLiquidity(program, connection, ctx.provider, pool.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, pool.mintLiquidityPda, pool.authorityPda, lpAmount.divn(2), null, ctx.provider.publicKey, accounts);
+        assert.strictEqual(await transfers(ctx.mintB.publicKey), 4);
+    });
+
+    it("Fails to move hooked tokens without the hook accounts", async () => {
+        const ctx = await setup(2802);
+        await addTransferHookProgram(program, ctx.admin, ctx.ammPda, hook.programId);
+        const pool = await createPool(program, ctx.provider, ctx.ammPda, ctx.mintA.publicKey, ctx.mintB.publicKey);
+        await addLiquidity(program, connection, ctx.provider, ctx.provider, pool.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, pool.mintLiquidityPda, LIQUIDITY, LIQUIDITY, null, hookAccounts(ctx.mintB.publicKey));
+
+        try {
+            await swap(program, connection, ctx.trader, ctx.provider, ctx.ammPda, pool.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, true, new anchor.BN(10).mul(DECIMALS), new anchor.BN(1));
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isFalse(err.toString().includes("Expected transaction to fail"), `Expected the swap to fail, got: ${err.toString()}`);
+        }
+        assert.strictEqual(await transfers(ctx.mintB.publicKey), 1);
+    });
+
+    it("Runs the hook when a long-term order pays out", async () => {
+        const ctx = await setup(2803);
+        await addTransferHookProgram(program, ctx.admin, ctx.ammPda, hook.programId);
+        const pool = await createPool(program, ctx.provider, ctx.ammPda, ctx.mintA.publicKey, ctx.mintB.publicKey);
+        const accounts = hookAccounts(ctx.mintB.publicKey);
+        await addLiquidity(program, connection, ctx.provider, ctx.provider, pool.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, pool.mintLiquidityPda, LIQUIDITY, LIQUIDITY, null, accounts);
+
+        // sells the classic token A for the hooked token B over two intervals
+        const order = await placeLongTermOrder(program, connection, ctx.trader, ctx.provider, ctx.ammPda, pool.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, 0, true, new anchor.BN(720).mul(DECIMALS), 2, accounts);
+        assert.strictEqual(await transfers(ctx.mintB.publicKey), 1);
+
+        await sleep(3000);
+        await withdrawLongTermOrder(program, ctx.trader, ctx.ammPda, pool.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, order, accounts);
+        assert.strictEqual(await transfers(ctx.mintB.publicKey), 2);
+        const traderB = getAssociatedTokenAddressSync(ctx.mintB.publicKey, ctx.trader.publicKey, false, TOKEN_2022_PROGRAM_ID);
+        const proceeds = (await getAccount(connection, traderB, "confirmed", TOKEN_2022_PROGRAM_ID)).amount;
+        assert.isTrue(new anchor.BN(proceeds.toString()).gtn(0));
+
+        // cancelling pays what was bought since in B and returns the unsold A
+        await sleep(2000);
+        await cancelLongTermOrder(program, ctx.trader, ctx.ammPda, pool.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, order, accounts);
+        assert.strictEqual(await transfers(ctx.mintB.publicKey), 3);
+        assert.isNull(await connection.getAccountInfo(order));
+    });
+
+    it("Rejects hooked mints whose hook program can still be switched", async () => {
+        const ctx = await setup(2804);
+        const switchable = Keypair.generate();
+        await createTransferHookMint(ctx.provider, switchable, ctx.provider.publicKey);
+        await addTransferHookProgram(program, ctx.admin, ctx.ammPda, hook.programId);
+
+        try {
+            await createPool(program, ctx.provider, ctx.ammPda, ctx.mintA.publicKey, switchable.publicKey);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("TransferHookAuthoritySet"), `Expected TransferHookAuthoritySet error, got: ${err.toString()}`);
+        }
+    });
+});
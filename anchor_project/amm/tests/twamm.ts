@@ -0,0 +1,179 @@
+import * as anchor from "@coral-xyz/anchor";
+import {Program} from "@coral-xyz/anchor";
+import {Amm} from "../target/types/amm";
+import {Keypair, Connection, PublicKey} from "@solana/web3.js";
+import {assert} from "chai";
+import {getAccount, getAssociatedTokenAddressSync} from "@solana/spl-token";
+import {
+    addLiquidity,
+    airdrop,
+    cancelLongTermOrder,
+    createAmm,
+    createMintSafe,
+    createPool,
+    placeLongTermOrder,
+    pruneTwammExpiry,
+    swap,
+    withdrawLiquidity,
+    withdrawLongTermOrder
+} from "./helper";
+
+describe("twamm", () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    let connection: Connection = anchor.getProvider().connection;
+
+    const program = anchor.workspace.amm as Program<Amm>;
+    const DECIMALS = new anchor.BN(10).pow(new anchor.BN(9));
+    const TWAMM_INTERVAL = 3600;
+
+    // pool at 1000 A / 3000 B
+    async function setupPool(ammIndex: number) {
+        const provider = Keypair.generate();
+        const owner = Keypair.generate();
+        const trader = Keypair.generate();
+        const mintA = Keypair.generate();
+        const mintB = Keypair.generate();
+
+        await airdrop(connection, provider.publicKey);
+        await airdrop(connection, owner.publicKey);
+        await airdrop(connection, trader.publicKey);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintA);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintB);
+
+        const {ammPda} = await createAmm(program, provider, provider.publicKey, 30, ammIndex);
+        const pool = await createPool(program, provider, ammPda, mintA.publicKey, mintB.publicKey);
+        await addLiquidity(program, connection, provider, provider, pool.poolPda, mintA.publicKey, mintB.publicKey, pool.mintLiquidityPda, new anchor.BN(1000).mul(DECIMALS), new anchor.BN(3000).mul(DECIMALS));
+
+        return {provider, owner, trader, mintA, mintB, ammPda, ...pool};
+    }
+
+    function sleep(ms: number) {
+        return new Promise((resolve) => setTimeout(resolve, ms));
+    }
+
+    function assertError(err: any, name: string) {
+        assert.isTrue(err.toString().includes(name), `Expected ${name} error, got: ${err.toString()}`);
+    }
+
+    async function balance(mint: PublicKey, owner: PublicKey) {
+        return new anchor.BN((await getAccount(connection, getAssociatedTokenAddressSync(mint, owner, false))).amount.toString());
+    }
+
+    // two intervals keep the orders open for at least an hour
+    function place(ctx: Awaited<ReturnType<typeof setupPool>>, id: number, isSellA: boolean, amount: anchor.BN) {
+        return placeLongTermOrder(program, connection, ctx.owner, ctx.provider, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, id, isSellA, amount, 2);
+    }
+
+    it("Orders sell every second and settle when the pool is next touched", async () => {
+        const ctx = await setupPool(2500);
+        const amount = new anchor.BN(720).mul(DECIMALS);
+
+        const order = await place(ctx, 0, true, amount);
+        const stored = await program.account.longTermOrder.fetch(order);
+        assert.equal(stored.expiry.toNumber() % TWAMM_INTERVAL, 0);
+        assert.isTrue(stored.sellRate.gtn(0));
+
+        let {twamm} = await program.account.ammPool.fetch(ctx.poolPda);
+        assert.isTrue(twamm.sellRateA.eq(stored.sellRate));
+        assert.equal(twamm.expiries.length, 1);
+        assert.isTrue(twamm.balanceB.isZero());
+
+        // a swap executes the order up to its own slot before it trades
+        await sleep(3000);
+        await swap(program, connection, ctx.trader, ctx.provider, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, false, DECIMALS, new anchor.BN(0));
+        ({twamm} = await program.account.ammPool.fetch(ctx.poolPda));
+        assert.isTrue(twamm.balanceB.gtn(0));
+        assert.isTrue(twamm.earningsPerRateAX64.gtn(0));
+
+        await withdrawLongTermOrder(program, ctx.owner, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, order);
+        const proceeds = await balance(ctx.mintB.publicKey, ctx.owner.publicKey);
+        assert.isTrue(proceeds.gtn(0));
+
+        // the order stays open until its expiry
+        assert.isNotNull(await connection.getAccountInfo(order));
+        assert.isTrue((await program.account.longTermOrder.fetch(order)).earningsPerRateX64.gtn(0));
+    });
+
+    it("Cancel pays the proceeds and returns the unsold input", async () => {
+        const ctx = await setupPool(2501);
+        const amount = new anchor.BN(2160).mul(DECIMALS);
+
+        const order = await place(ctx, 3, false, amount);
+        // what the deposit left over after rounding to a whole sell rate
+        const leftover = await balance(ctx.mintB.publicKey, ctx.owner.publicKey);
+
+        await sleep(2000);
+        await cancelLongTermOrder(program, ctx.owner, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, order);
+
+        const refund = (await balance(ctx.mintB.publicKey, ctx.owner.publicKey)).sub(leftover);
+        assert.isTrue(refund.gtn(0));
+        assert.isTrue(refund.lt(amount));
+        assert.isTrue((await balance(ctx.mintA.publicKey, ctx.owner.publicKey)).gtn(0));
+        assert.isNull(await connection.getAccountInfo(order));
+
+        const {twamm} = await program.account.ammPool.fetch(ctx.poolPda);
+        assert.isTrue(twamm.sellRateB.isZero());
+        assert.equal(twamm.expiries.length, 0);
+    });
+
+    it("The last LP can withdraw everything while orders are open", async () => {
+        const ctx = await setupPool(2502);
+        const amount = new anchor.BN(720).mul(DECIMALS);
+        const order = await place(ctx, 0, true, amount);
+        const leftover = await balance(ctx.mintA.publicKey, ctx.owner.publicKey);
+
+        const lpAmount = await balance(ctx.mintLiquidityPda, ctx.provider.publicKey);
+        await withdrawLiquidity(program, connection, ctx.provider, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, ctx.mintLiquidityPda, ctx.authorityPda, lpAmount);
+        // everything the order had not sold when the liquidity left
+        const unsold = (await program.account.ammPool.fetch(ctx.poolPda)).twamm.balanceA;
+        assert.isTrue(unsold.gtn(0));
+
+        // without liquidity the order sells nothing, cancel refunds the idle input with the unsold rest
+        await sleep(3000);
+        await cancelLongTermOrder(program, ctx.owner, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, order);
+        const refund = (await balance(ctx.mintA.publicKey, ctx.owner.publicKey)).sub(leftover);
+
+        const {twamm} = await program.account.ammPool.fetch(ctx.poolPda);
+        assert.isTrue(twamm.idleSeconds.gtn(0));
+        assert.isTrue(twamm.balanceA.isZero());
+        assert.isTrue(twamm.sellRateA.isZero());
+        assert.isTrue(refund.eq(unsold));
+        assert.isNull(await connection.getAccountInfo(order));
+    });
+
+    it("Rejects dust orders and orders past the interval cap", async () => {
+        const ctx = await setupPool(2503);
+
+        // 0.1% of the 1000 A reserve per interval is 1 A, 1 A over two intervals sells less
+        for (const [id, amount, intervals] of [[0, DECIMALS, 2], [1, new anchor.BN(720).mul(DECIMALS), 25]] as const) {
+            try {
+                await placeLongTermOrder(program, connection, ctx.owner, ctx.provider, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, id, true, amount, intervals);
+                assert.fail("Expected transaction to fail");
+            } catch (err) {
+                assertError(err, "InvalidLongTermOrder");
+            }
+        }
+
+        await placeLongTermOrder(program, connection, ctx.owner, ctx.provider, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, 2, true, new anchor.BN(720).mul(DECIMALS), 24);
+    });
+
+    it("Only prunes expiries the orders were executed past", async () => {
+        const ctx = await setupPool(2504);
+        const order = await place(ctx, 0, true, new anchor.BN(720).mul(DECIMALS));
+        const {expiry} = await program.account.longTermOrder.fetch(order);
+
+        try {
+            await pruneTwammExpiry(program, ctx.trader, ctx.poolPda, expiry);
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertError(err, "TwammExpiryNotCrossed");
+        }
+        try {
+            await pruneTwammExpiry(program, ctx.trader, ctx.poolPda, new anchor.BN(TWAMM_INTERVAL));
+            assert.fail("Expected transaction to fail");
+        } catch (err) {
+            assertError(err, "TwammExpiryNotFound");
+        }
+        assert.equal((await program.account.ammPool.fetch(ctx.poolPda)).twamm.expiries.length, 1);
+    });
+});
@@ -0,0 +1,132 @@
+import * as anchor from "@coral-xyz/anchor";
+import {Program} from "@coral-xyz/anchor";
+import {Amm} from "../target/types/amm";
+import {Keypair, Connection} from "@solana/web3.js";
+import {assert} from "chai";
+import {
+    addLiquidity,
+    airdrop,
+    createAmm,
+    createMintSafe,
+    createPool,
+    getPoolReserves,
+    swap
+} from "./helper";
+
+describe("twap", () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    let connection: Connection = anchor.getProvider().connection;
+
+    const program = anchor.workspace.amm as Program<Amm>;
+    const DECIMALS = new anchor.BN(10).pow(new anchor.BN(9));
+
+    async function setupPool(ammIndex: number) {
+        const provider = Keypair.generate();
+        const trader = Keypair.generate();
+        const mintA = Keypair.generate();
+        const mintB = Keypair.generate();
+
+        await airdrop(connection, provider.publicKey);
+        await airdrop(connection, trader.publicKey);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintA);
+        await createMintSafe(connection, provider, provider.publicKey, 9, mintB);
+
+        const {ammPda} = await createAmm(program, provider, provider.publicKey, 30, ammIndex);
+        const pool = await createPool(program, provider, ammPda, mintA.publicKey, mintB.publicKey);
+        await addLiquidity(program, connection, provider, provider, pool.poolPda, mintA.publicKey, mintB.publicKey, pool.mintLiquidityPda, new anchor.BN(1000).mul(DECIMALS), new anchor.BN(3000).mul(DECIMALS));
+
+        const accounts = {
+            pool: pool.poolPda,
+            observations: pool.observationsPda,
+            mintA: mintA.publicKey,
+            mintB: mintB.publicKey,
+            authority: pool.authorityPda,
+            poolAccountA: pool.poolAccountA,
+            poolAccountB: pool.poolAccountB,
+        };
+        return {provider, trader, mintA, mintB, ammPda, accounts, ...pool};
+    }
+
+    function sleep(ms: number) {
+        return new Promise((resolve) => setTimeout(resolve, ms));
+    }
+
+    // Q64.64 price of A in B and of B in A at the current reserves
+    async function spotPrices(ctx: Awaited<ReturnType<typeof setupPool>>) {
+        const {reserveA, reserveB} = await getPoolReserves(connection, ctx.mintA.publicKey, ctx.mintB.publicKey, ctx.authorityPda);
+        return {
+            priceA: reserveB.shln(64).div(reserveA),
+            priceB: reserveA.shln(64).div(reserveB),
+        };
+    }
+
+    it("Swaps bring the accumulators up to date and record at most one observation per period", async () => {
+        const ctx = await setupPool(2200);
+        const before = await spotPrices(ctx);
+
+        await sleep(2000);
+        await swap(program, connection, ctx.trader, ctx.provider, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, true, new anchor.BN(100).mul(DECIMALS), new anchor.BN(0));
+
+        // the first deposit, into an empty pool, is the only observation within the period
+        const {observations} = await program.account.observations.fetch(ctx.observationsPda);
+        assert.equal(observations.length, 1);
+        assert.isTrue(observations[0].priceACumulative.isZero());
+
+        // the swap still accumulated the price it found before moving it
+        const pool = await program.account.ammPool.fetch(ctx.poolPda);
+        const elapsed = pool.lastUpdateTimestamp.sub(observations[0].timestamp);
+        assert.isTrue(elapsed.gtn(0));
+        assert.isTrue(pool.priceACumulative.eq(before.priceA.mul(elapsed)));
+        assert.isTrue(pool.priceBCumulative.eq(before.priceB.mul(elapsed)));
+    });
+
+    it("Consult returns the time weighted price over the window", async () => {
+        const ctx = await setupPool(2201);
+
+        await sleep(2000);
+        await swap(program, connection, ctx.trader, ctx.provider, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, true, new anchor.BN(100).mul(DECIMALS), new anchor.BN(0));
+        const after = await spotPrices(ctx);
+        await sleep(3000);
+
+        // the first deposit is the only observation, the average spans the prices before and after the swap
+        const {observations} = await program.account.observations.fetch(ctx.observationsPda);
+        const pool = await program.account.ammPool.fetch(ctx.poolPda);
+        const twap = await program.methods.consult(1).accounts(ctx.accounts).view();
+        const now = observations[0].timestamp.add(twap.windowSeconds);
+        const sinceSwap = now.sub(pool.lastUpdateTimestamp);
+        const expected = pool.priceACumulative.add(after.priceA.mul(sinceSwap)).div(twap.windowSeconds);
+        assert.isTrue(twap.priceAX64.eq(expected));
+        assert.isTrue(twap.priceAX64.gt(after.priceA));
+    });
+
+    it("Keeps a window much longer than the buffer however often the pool trades", async () => {
+        const ctx = await setupPool(2203);
+        const amount = new anchor.BN(1).mul(DECIMALS);
+
+        // more swaps than the buffer has slots, each in its own second
+        for (let i = 0; i < 70; i++) {
+            await swap(program, connection, ctx.trader, ctx.provider, ctx.ammPda, ctx.poolPda, ctx.mintA.publicKey, ctx.mintB.publicKey, i % 2 == 0, amount, new anchor.BN(0));
+        }
+
+        // the first deposit was not pushed out and still anchors the average
+        const {observations} = await program.account.observations.fetch(ctx.observationsPda);
+        const pool = await program.account.ammPool.fetch(ctx.poolPda);
+        assert.isTrue(observations[0].priceACumulative.isZero());
+        const traded = pool.lastUpdateTimestamp.sub(observations[0].timestamp).toNumber();
+        assert.isAbove(traded, 0);
+
+        const twap = await program.methods.consult(traded).accounts(ctx.accounts).view();
+        assert.isTrue(twap.windowSeconds.gten(traded));
+    });
+
+    it("Consult fails without an observation old enough for the window", async () => {
+        const ctx = await setupPool(2202);
+
+        try {
+            await program.methods.consult(3600).accounts(ctx.accounts).view();
+            assert.fail("Expected view to fail");
+        } catch (err) {
+            assert.isTrue(err.toString().includes("ObservationNotFound"), `Expected ObservationNotFound error, got: ${err.toString()}`);
+        }
+    });
+});
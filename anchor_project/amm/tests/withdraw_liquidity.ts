@@ -48,7 +48,7 @@ describe("withdraw_liquidity", () => {
         await mintTo(connection, mintAuthority, mintA, depositorAccountA, mintAuthority, amountA.toNumber());
         await mintTo(connection, mintAuthority, mintB, depositorAccountB, mintAuthority, amountB.toNumber());
 
-        await program.methods.addLiquidity(amountA, amountB).accounts({
+        await program.methods.addLiquidity(amountA, amountB, null, false).accounts({
             pool: poolPda,
             mintA: mintA,
             mintB: mintB,
@@ -89,15 +89,16 @@ describe("withdraw_liquidity", () => {
         const expectedAmountAOut = lpAmountToBurn.mul(reserveABefore).div(totalLp);
         const expectedAmountBOut = lpAmountToBurn.mul(reserveBBefore).div(totalLp);
 
-        await program.methods.withdrawLiquidity(lpAmountToBurn).accounts({
+        await program.methods.withdrawLiquidity(lpAmountToBurn, null, false).accounts({
             pool: poolPda,
             mintA: mintA,
             mintB: mintB,
             mintLiquidity: mintLiquidityPda,
             depositor: signer.publicKey,
+            recipient: signer.publicKey,
             depositorAccountLiquidity: depositorAccountLiquidity,
-            depositorAccountA: depositorAccountA,
-            depositorAccountB: depositorAccountB,
+            recipientAccountA: depositorAccountA,
+            recipientAccountB: depositorAccountB,
             payer: signer.publicKey,
         }).signers([signer]).rpc({commitment: "confirmed"});
 
@@ -286,15 +287,16 @@ describe("withdraw_liquidity", () => {
         );
 
         try {
-            await program.methods.withdrawLiquidity(lpAmount).accounts({
+            await program.methods.withdrawLiquidity(lpAmount, null, false).accounts({
                 pool: poolAB,
                 mintA: mintA.publicKey,
                 mintB: mintB.publicKey,
                 mintLiquidity: mintLiquidityAB,
                 depositor: user.publicKey,
+                recipient: user.publicKey,
                 depositorAccountLiquidity: getAssociatedTokenAddressSync(mintLiquidityAB, user.publicKey, false),
-                depositorAccountA: getAssociatedTokenAddressSync(mintA.publicKey, user.publicKey, false),
-                depositorAccountB: getAssociatedTokenAddressSync(mintB.publicKey, user.publicKey, false),
+                recipientAccountA: getAssociatedTokenAddressSync(mintA.publicKey, user.publicKey, false),
+                recipientAccountB: getAssociatedTokenAddressSync(mintB.publicKey, user.publicKey, false),
                 payer: user.publicKey,
             }).signers([user]).rpc({commitment: "confirmed"});
             assert.fail("Expected transaction to fail when withdrawing from wrong pool");
@@ -307,15 +309,16 @@ describe("withdraw_liquidity", () => {
         }
 
         try {
-            await program.methods.withdrawLiquidity(new anchor.BN(0)).accounts({
+            await program.methods.withdrawLiquidity(new anchor.BN(0), null, false).accounts({
                 pool: poolBC,
                 mintA: mintB.publicKey,
                 mintB: mintC.publicKey,
                 mintLiquidity: mintLiquidityBC,
                 depositor: user.publicKey,
+                recipient: user.publicKey,
                 depositorAccountLiquidity: getAssociatedTokenAddressSync(mintLiquidityBC, user.publicKey, false),
-                depositorAccountA: getAssociatedTokenAddressSync(mintB.publicKey, user.publicKey, false),
-                depositorAccountB: getAssociatedTokenAddressSync(mintC.publicKey, user.publicKey, false),
+                recipientAccountA: getAssociatedTokenAddressSync(mintB.publicKey, user.publicKey, false),
+                recipientAccountB: getAssociatedTokenAddressSync(mintC.publicKey, user.publicKey, false),
                 payer: user.publicKey,
             }).signers([user]).rpc({commitment: "confirmed"});
             assert.fail("Expected transaction to fail when withdrawing 0 LP");
@@ -329,15 +332,16 @@ describe("withdraw_liquidity", () => {
 
         const tooMuchLp = lpAmount.add(new anchor.BN(1));
         try {
-            await program.methods.withdrawLiquidity(tooMuchLp).accounts({
+            await program.methods.withdrawLiquidity(tooMuchLp, null, false).accounts({
                 pool: poolBC,
                 mintA: mintB.publicKey,
                 mintB: mintC.publicKey,
                 mintLiquidity: mintLiquidityBC,
                 depositor: user.publicKey,
+                recipient: user.publicKey,
                 depositorAccountLiquidity: getAssociatedTokenAddressSync(mintLiquidityBC, user.publicKey, false),
-                depositorAccountA: getAssociatedTokenAddressSync(mintB.publicKey, user.publicKey, false),
-                depositorAccountB: getAssociatedTokenAddressSync(mintC.publicKey, user.publicKey, false),
+                recipientAccountA: getAssociatedTokenAddressSync(mintB.publicKey, user.publicKey, false),
+                recipientAccountB: getAssociatedTokenAddressSync(mintC.publicKey, user.publicKey, false),
                 payer: user.publicKey,
             }).signers([user]).rpc({commitment: "confirmed"});
             assert.fail("Expected transaction to fail when withdrawing more LP than user has");